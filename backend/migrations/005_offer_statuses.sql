@@ -0,0 +1,3 @@
+-- Add terminal success states to the application pipeline
+ALTER TYPE application_status ADD VALUE IF NOT EXISTS 'offer';
+ALTER TYPE application_status ADD VALUE IF NOT EXISTS 'accepted';
@@ -0,0 +1,20 @@
+-- Offered or expected compensation per application
+ALTER TABLE applications ADD COLUMN IF NOT EXISTS salary_min INTEGER;
+ALTER TABLE applications ADD COLUMN IF NOT EXISTS salary_max INTEGER;
+ALTER TABLE applications ADD COLUMN IF NOT EXISTS currency VARCHAR(3);
+
+DO $$ 
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'check_salary_range') THEN
+        ALTER TABLE applications ADD CONSTRAINT check_salary_range
+        CHECK (salary_min IS NULL OR salary_max IS NULL OR salary_min <= salary_max);
+    END IF;
+END $$;
+
+DO $$ 
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'check_currency_format') THEN
+        ALTER TABLE applications ADD CONSTRAINT check_currency_format
+        CHECK (currency IS NULL OR currency ~ '^[A-Z]{3}$');
+    END IF;
+END $$;
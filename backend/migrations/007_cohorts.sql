@@ -0,0 +1,20 @@
+-- Student cohorts for multi-tenant deployments
+CREATE TABLE IF NOT EXISTS cohorts (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Users without a cohort are unscoped; an admin without a cohort is a super-admin
+ALTER TABLE users ADD COLUMN IF NOT EXISTS cohort_id INTEGER REFERENCES cohorts(id) ON DELETE SET NULL;
+
+-- Denormalized from the owning user so admin queries can filter without joining users
+ALTER TABLE applications ADD COLUMN IF NOT EXISTS cohort_id INTEGER REFERENCES cohorts(id) ON DELETE SET NULL;
+
+UPDATE applications a
+SET cohort_id = u.cohort_id
+FROM users u
+WHERE a.user_id = u.id AND a.cohort_id IS DISTINCT FROM u.cohort_id;
+
+CREATE INDEX IF NOT EXISTS idx_users_cohort ON users(cohort_id);
+CREATE INDEX IF NOT EXISTS idx_applications_cohort ON applications(cohort_id);
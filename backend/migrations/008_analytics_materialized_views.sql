@@ -0,0 +1,55 @@
+-- Materialized views backing the heavy admin analytics aggregations.
+-- Rows are grouped per cohort so cohort-scoped admins can sum their slice;
+-- cohort_key is 0 for applications without a cohort. The unique indexes
+-- allow REFRESH MATERIALIZED VIEW CONCURRENTLY.
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS analytics_status_summary AS
+SELECT COALESCE(cohort_id, 0) AS cohort_key,
+       status::text AS status,
+       COUNT(*)::bigint AS application_count
+FROM applications
+GROUP BY COALESCE(cohort_id, 0), status;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_analytics_status_summary_key
+ON analytics_status_summary(cohort_key, status);
+
+-- A student belongs to a single cohort, so unique_students sums correctly across cohorts
+CREATE MATERIALIZED VIEW IF NOT EXISTS analytics_company_summary AS
+SELECT COALESCE(cohort_id, 0) AS cohort_key,
+       company,
+       COUNT(*)::bigint AS application_count,
+       COUNT(DISTINCT user_id)::bigint AS unique_students
+FROM applications
+GROUP BY COALESCE(cohort_id, 0), company;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_analytics_company_summary_key
+ON analytics_company_summary(cohort_key, company);
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS analytics_success_summary AS
+WITH interview_passes AS (
+    SELECT application_id, COUNT(*) AS passed
+    FROM interviews
+    WHERE result = 'passed'
+    GROUP BY application_id
+),
+screening_passes AS (
+    SELECT application_id, COUNT(*) AS passed
+    FROM screenings
+    WHERE result = 'passed'
+    GROUP BY application_id
+)
+SELECT COALESCE(a.cohort_id, 0) AS cohort_key,
+       COUNT(*)::bigint AS total_apps,
+       COALESCE(SUM(ip.passed), 0)::bigint AS interview_passed,
+       COALESCE(SUM(sp.passed), 0)::bigint AS screening_passed,
+       COUNT(*) FILTER (WHERE a.job_url IS NOT NULL)::bigint AS apps_with_urls,
+       COUNT(*) FILTER (WHERE a.job_url IS NULL)::bigint AS apps_without_urls,
+       COUNT(*) FILTER (WHERE a.status IN ('offer', 'accepted'))::bigint AS offers_received,
+       COUNT(*) FILTER (WHERE a.status = 'accepted')::bigint AS offers_accepted
+FROM applications a
+LEFT JOIN interview_passes ip ON ip.application_id = a.id
+LEFT JOIN screening_passes sp ON sp.application_id = a.id
+GROUP BY COALESCE(a.cohort_id, 0);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_analytics_success_summary_key
+ON analytics_success_summary(cohort_key);
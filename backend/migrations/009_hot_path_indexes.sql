@@ -0,0 +1,29 @@
+-- Indexes for the hot query paths.
+--
+-- Already covered by earlier migrations (listed so they aren't re-added):
+--   screenings(application_id)   idx_screenings_application_id (001)
+--     used by the ANY($1) batch lookups in get_applications
+--   interviews(application_id)   idx_interviews_application_id (001)
+--   cache_store(expires_at)      idx_cache_expires (004)
+--     used by cache cleanup and the expires_at > NOW() lookups
+--
+-- Expected plans (check with EXPLAIN after schema changes):
+--   SELECT * FROM applications WHERE user_id = $1 ORDER BY created_at DESC
+--     -> Index Scan using idx_applications_user_created, no Sort node
+--   SELECT * FROM applications WHERE updated_at < $1 AND status IN ('waiting', 'next_stage')
+--     -> Index/Bitmap Scan using idx_applications_status_updated with
+--        Index Cond on both status and updated_at
+
+-- Per-student application list, newest first
+CREATE INDEX IF NOT EXISTS idx_applications_user_created
+ON applications(user_id, created_at DESC);
+
+-- Stale-application scans: equality on status first, then the updated_at range.
+-- idx_applications_updated_status (004) leads with updated_at and can only use
+-- the range, so it reads every status.
+CREATE INDEX IF NOT EXISTS idx_applications_status_updated
+ON applications(status, updated_at);
+
+-- Stale applications for a single student (notification digests)
+CREATE INDEX IF NOT EXISTS idx_applications_user_status_updated
+ON applications(user_id, status, updated_at);
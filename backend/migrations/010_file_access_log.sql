@@ -0,0 +1,13 @@
+-- Compliance log of recording downloads
+CREATE TABLE IF NOT EXISTS file_access_log (
+    id BIGSERIAL PRIMARY KEY,
+    filename VARCHAR(500) NOT NULL,
+    accessed_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    via VARCHAR(16) NOT NULL CHECK (via IN ('direct', 'token', 'archive')),
+    bytes_served BIGINT NOT NULL DEFAULT 0,
+    accessed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_access_log_accessed_at ON file_access_log(accessed_at DESC);
+CREATE INDEX IF NOT EXISTS idx_file_access_log_filename ON file_access_log(filename);
+CREATE INDEX IF NOT EXISTS idx_file_access_log_user ON file_access_log(accessed_by);
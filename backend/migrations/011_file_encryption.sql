@@ -0,0 +1,3 @@
+-- AES-GCM nonce for encrypted recordings; NULL means the file is stored in plaintext
+ALTER TABLE screenings ADD COLUMN IF NOT EXISTS file_nonce BYTEA;
+ALTER TABLE interviews ADD COLUMN IF NOT EXISTS file_nonce BYTEA;
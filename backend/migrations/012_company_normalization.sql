@@ -0,0 +1,36 @@
+-- Grouping key for company names; the original spelling stays in `company` for display.
+-- Mirrors normalize_company in src/utils/company.rs.
+CREATE OR REPLACE FUNCTION normalize_company(name TEXT) RETURNS TEXT AS $$
+    SELECT lower(trim(
+        regexp_replace(
+            regexp_replace(
+                trim(regexp_replace(regexp_replace(name, '[«»"'']', '', 'g'), '\s+', ' ', 'g')),
+                '^(ооо|зао|оао|пао|ао)\s+', '', 'i'
+            ),
+            '[\s,.]+(inc|incorporated|llc|ltd|limited|corp|corporation|co|gmbh|plc|ag)\.?$', '', 'i'
+        )
+    ))
+$$ LANGUAGE SQL IMMUTABLE;
+
+ALTER TABLE applications ADD COLUMN IF NOT EXISTS company_normalized VARCHAR(255);
+
+UPDATE applications
+SET company_normalized = normalize_company(company)
+WHERE company_normalized IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_applications_company_normalized ON applications(company_normalized);
+
+-- Regroup the company analytics view by the normalized key, showing the most common spelling
+DROP MATERIALIZED VIEW IF EXISTS analytics_company_summary;
+
+CREATE MATERIALIZED VIEW analytics_company_summary AS
+SELECT COALESCE(cohort_id, 0) AS cohort_key,
+       COALESCE(company_normalized, normalize_company(company)) AS company_key,
+       MODE() WITHIN GROUP (ORDER BY company) AS company,
+       COUNT(*)::bigint AS application_count,
+       COUNT(DISTINCT user_id)::bigint AS unique_students
+FROM applications
+GROUP BY COALESCE(cohort_id, 0), COALESCE(company_normalized, normalize_company(company));
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_analytics_company_summary_key
+ON analytics_company_summary(cohort_key, company_key);
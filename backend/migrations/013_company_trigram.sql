@@ -0,0 +1,5 @@
+-- Fuzzy company suggestions (GET /companies/suggest) match on the normalized name
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_applications_company_normalized_trgm
+ON applications USING GIN (company_normalized gin_trgm_ops);
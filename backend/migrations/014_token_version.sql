@@ -0,0 +1,2 @@
+-- Bumped to revoke every access token issued to a user ("log out everywhere")
+ALTER TABLE users ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,27 @@
+-- Records deleted applications so delta-sync clients (GET /applications?since=) can drop them locally
+CREATE TABLE IF NOT EXISTS application_tombstones (
+    application_id INTEGER PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    deleted_at TIMESTAMP WITH TIME ZONE DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_application_tombstones_user_deleted
+ON application_tombstones(user_id, deleted_at);
+
+CREATE OR REPLACE FUNCTION record_application_tombstone()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO application_tombstones (application_id, user_id)
+    VALUES (OLD.id, OLD.user_id)
+    ON CONFLICT (application_id) DO UPDATE SET deleted_at = NOW();
+    RETURN OLD;
+END;
+$$ language 'plpgsql';
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'record_application_tombstone') THEN
+        CREATE TRIGGER record_application_tombstone AFTER DELETE ON applications
+            FOR EACH ROW EXECUTE FUNCTION record_application_tombstone();
+    END IF;
+END $$;
@@ -0,0 +1,2 @@
+-- Set by POST /admin/password-migration/flag-legacy for users still on bcrypt hashes
+ALTER TABLE users ADD COLUMN IF NOT EXISTS password_reset_required BOOLEAN NOT NULL DEFAULT FALSE;
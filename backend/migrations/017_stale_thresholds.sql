@@ -0,0 +1,11 @@
+-- How long an application may sit in each status before it counts as stale.
+-- Statuses without a row never go stale.
+CREATE TABLE IF NOT EXISTS stale_thresholds (
+    status application_status PRIMARY KEY,
+    days INTEGER NOT NULL CHECK (days > 0)
+);
+
+INSERT INTO stale_thresholds (status, days) VALUES
+    ('waiting', 7),
+    ('next_stage', 3)
+ON CONFLICT (status) DO NOTHING;
@@ -0,0 +1,12 @@
+-- Documents attached to an application (job description, submitted resume, ...)
+CREATE TABLE IF NOT EXISTS documents (
+    id SERIAL PRIMARY KEY,
+    application_id INTEGER NOT NULL REFERENCES applications(id) ON DELETE CASCADE,
+    file_path VARCHAR(500) NOT NULL UNIQUE,
+    file_nonce BYTEA,
+    original_filename VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_documents_application ON documents(application_id);
@@ -0,0 +1,63 @@
+-- Latest activity on an application, including its screening, interview and
+-- documents. Staleness is measured from here rather than from updated_at.
+ALTER TABLE applications ADD COLUMN IF NOT EXISTS last_activity_at TIMESTAMP WITH TIME ZONE;
+
+-- Backfill without firing the updated_at and audit triggers
+ALTER TABLE applications DISABLE TRIGGER USER;
+UPDATE applications a
+SET last_activity_at = COALESCE(
+    GREATEST(
+        a.updated_at,
+        (SELECT MAX(s.updated_at) FROM screenings s WHERE s.application_id = a.id),
+        (SELECT MAX(i.updated_at) FROM interviews i WHERE i.application_id = a.id),
+        (SELECT MAX(d.created_at) FROM documents d WHERE d.application_id = a.id)
+    ),
+    a.created_at,
+    NOW()
+)
+WHERE last_activity_at IS NULL;
+ALTER TABLE applications ENABLE TRIGGER USER;
+
+ALTER TABLE applications ALTER COLUMN last_activity_at SET DEFAULT NOW();
+ALTER TABLE applications ALTER COLUMN last_activity_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_applications_last_activity ON applications(last_activity_at);
+
+-- Direct updates move both timestamps. Updates made by the activity trigger
+-- below run nested (pg_trigger_depth() > 1) and only move last_activity_at.
+CREATE OR REPLACE FUNCTION update_application_timestamps()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF pg_trigger_depth() > 1 THEN
+        RETURN NEW;
+    END IF;
+    NEW.updated_at = NOW();
+    NEW.last_activity_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS update_applications_updated_at ON applications;
+DROP TRIGGER IF EXISTS update_application_timestamps ON applications;
+CREATE TRIGGER update_application_timestamps BEFORE UPDATE ON applications
+    FOR EACH ROW EXECUTE FUNCTION update_application_timestamps();
+
+CREATE OR REPLACE FUNCTION touch_application_activity()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE applications SET last_activity_at = NOW() WHERE id = NEW.application_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS touch_application_activity ON screenings;
+CREATE TRIGGER touch_application_activity AFTER INSERT OR UPDATE ON screenings
+    FOR EACH ROW EXECUTE FUNCTION touch_application_activity();
+
+DROP TRIGGER IF EXISTS touch_application_activity ON interviews;
+CREATE TRIGGER touch_application_activity AFTER INSERT OR UPDATE ON interviews
+    FOR EACH ROW EXECUTE FUNCTION touch_application_activity();
+
+DROP TRIGGER IF EXISTS touch_application_activity ON documents;
+CREATE TRIGGER touch_application_activity AFTER INSERT ON documents
+    FOR EACH ROW EXECUTE FUNCTION touch_application_activity();
@@ -0,0 +1,13 @@
+-- Why a screening or interview ended the way it did.
+-- reason_code is validated by the API against the built-in codes plus the
+-- `outcome.reason_codes` setting, so the column itself stays free-form.
+ALTER TABLE screenings ADD COLUMN IF NOT EXISTS reason_code VARCHAR(50);
+ALTER TABLE screenings ADD COLUMN IF NOT EXISTS feedback TEXT;
+
+ALTER TABLE interviews ADD COLUMN IF NOT EXISTS reason_code VARCHAR(50);
+ALTER TABLE interviews ADD COLUMN IF NOT EXISTS feedback TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_screenings_failed_reason
+    ON screenings(reason_code) WHERE result = 'failed' AND reason_code IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_interviews_failed_reason
+    ON interviews(reason_code) WHERE result = 'failed' AND reason_code IS NOT NULL;
@@ -0,0 +1,31 @@
+-- Name a recording is downloaded under. The stored file keeps its UUID key.
+ALTER TABLE screenings ADD COLUMN IF NOT EXISTS original_filename VARCHAR(255);
+ALTER TABLE interviews ADD COLUMN IF NOT EXISTS original_filename VARCHAR(255);
+
+-- Existing recordings get a company_date_kind.ext name. Backfilled without
+-- firing the timestamp and activity triggers, so nothing looks freshly touched.
+ALTER TABLE screenings DISABLE TRIGGER USER;
+UPDATE screenings s
+SET original_filename = LEFT(
+    regexp_replace(a.company, '[^[:alnum:]-]+', '_', 'g')
+        || '_' || to_char(COALESCE(s.screening_date, s.created_at::date), 'YYYY-MM-DD')
+        || '_screening'
+        || COALESCE(substring(s.file_path from '\.[A-Za-z0-9]+$'), ''),
+    255
+)
+FROM applications a
+WHERE a.id = s.application_id AND s.file_path IS NOT NULL AND s.original_filename IS NULL;
+ALTER TABLE screenings ENABLE TRIGGER USER;
+
+ALTER TABLE interviews DISABLE TRIGGER USER;
+UPDATE interviews i
+SET original_filename = LEFT(
+    regexp_replace(a.company, '[^[:alnum:]-]+', '_', 'g')
+        || '_' || to_char(COALESCE(i.interview_date, i.created_at::date), 'YYYY-MM-DD')
+        || '_interview'
+        || COALESCE(substring(i.file_path from '\.[A-Za-z0-9]+$'), ''),
+    255
+)
+FROM applications a
+WHERE a.id = i.application_id AND i.file_path IS NOT NULL AND i.original_filename IS NULL;
+ALTER TABLE interviews ENABLE TRIGGER USER;
@@ -0,0 +1,17 @@
+-- Browser-playable copies of recordings in formats browsers can't play.
+-- One row per original file; `pending` rows are being transcoded.
+CREATE TABLE IF NOT EXISTS file_previews (
+    file_path VARCHAR(500) PRIMARY KEY,
+    preview_path VARCHAR(500) UNIQUE,
+    preview_nonce BYTEA,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'ready', 'failed')),
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Inline playback through the preview endpoint is logged as its own access kind
+ALTER TABLE file_access_log DROP CONSTRAINT IF EXISTS file_access_log_via_check;
+ALTER TABLE file_access_log ADD CONSTRAINT file_access_log_via_check
+    CHECK (via IN ('direct', 'token', 'archive', 'preview'));
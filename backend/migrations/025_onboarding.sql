@@ -0,0 +1,2 @@
+-- Set by POST /auth/onboarding/complete once the user has been through the first-run flow
+ALTER TABLE users ADD COLUMN IF NOT EXISTS onboarding_completed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Waiting applications with a screening on the calendar, split out of 'waiting'
+ALTER TYPE application_status ADD VALUE IF NOT EXISTS 'screening_scheduled' AFTER 'waiting';
@@ -0,0 +1,5 @@
+-- Separate from 027: a new enum value can't be used in the transaction that adds it.
+-- Longer than 'waiting', since the screening itself is often a week or two out.
+INSERT INTO stale_thresholds (status, days) VALUES
+    ('screening_scheduled', 14)
+ON CONFLICT (status) DO NOTHING;
@@ -0,0 +1,26 @@
+-- Notification channels and outbox states as enums, like application_status
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'notification_channel') THEN
+        CREATE TYPE notification_channel AS ENUM ('log', 'email', 'webhook', 'slack');
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'notification_delivery_status') THEN
+        CREATE TYPE notification_delivery_status AS ENUM ('pending', 'sent', 'failed');
+    END IF;
+END $$;
+
+-- The CHECK constraint, default and partial index all depend on the text column
+DROP INDEX IF EXISTS idx_notification_outbox_pending;
+ALTER TABLE notification_outbox DROP CONSTRAINT IF EXISTS notification_outbox_status_check;
+ALTER TABLE notification_outbox ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE notification_outbox
+    ALTER COLUMN status TYPE notification_delivery_status
+    USING status::notification_delivery_status;
+ALTER TABLE notification_outbox ALTER COLUMN status SET DEFAULT 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_notification_outbox_pending
+    ON notification_outbox(next_attempt_at) WHERE status = 'pending';
+
+-- Everything queued so far went out through the log channel
+ALTER TABLE notification_outbox
+    ADD COLUMN IF NOT EXISTS channel notification_channel NOT NULL DEFAULT 'log';
@@ -0,0 +1,36 @@
+-- Backfills of derived columns run with `otchet.backfill` set for their
+-- transaction. Like the one-off backfill in 020, they recompute data rather
+-- than change it, so they must not bump updated_at or write audit rows.
+CREATE OR REPLACE FUNCTION update_application_timestamps()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF pg_trigger_depth() > 1 OR current_setting('otchet.backfill', true) = 'on' THEN
+        RETURN NEW;
+    END IF;
+    NEW.updated_at = NOW();
+    NEW.last_activity_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION audit_trigger()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF current_setting('otchet.backfill', true) = 'on' THEN
+        RETURN COALESCE(NEW, OLD);
+    END IF;
+    INSERT INTO audit_log (table_name, operation, old_data, new_data, user_id)
+    VALUES (
+        TG_TABLE_NAME,
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' THEN row_to_json(OLD) ELSE NULL END,
+        CASE WHEN TG_OP IN ('INSERT', 'UPDATE') THEN row_to_json(NEW) ELSE NULL END,
+        CASE
+            WHEN TG_OP = 'DELETE' THEN OLD.user_id
+            WHEN TG_TABLE_NAME = 'users' THEN NEW.id
+            ELSE NEW.user_id
+        END
+    );
+    RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,8 @@
+-- A graded outcome next to the pass/fail result, for processes that rate
+-- candidates on a scale. The API checks it against the scale in the
+-- `outcome.score_scale` setting (1-5 or 0-100); the column accepts either.
+ALTER TABLE screenings ADD COLUMN IF NOT EXISTS score SMALLINT
+    CHECK (score BETWEEN 0 AND 100);
+
+ALTER TABLE interviews ADD COLUMN IF NOT EXISTS score SMALLINT
+    CHECK (score BETWEEN 0 AND 100);
@@ -0,0 +1,13 @@
+-- Last run of each background job, so a job that stopped firing or keeps
+-- failing shows up in GET /admin/scheduler/status instead of only in the logs.
+-- registered_at is when the job was first scheduled; until its first run,
+-- missed runs are counted from there.
+CREATE TABLE IF NOT EXISTS scheduler_runs (
+    job VARCHAR(50) PRIMARY KEY,
+    schedule VARCHAR(100) NOT NULL,
+    registered_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_started_at TIMESTAMP WITH TIME ZONE,
+    last_finished_at TIMESTAMP WITH TIME ZONE,
+    last_outcome VARCHAR(16) CHECK (last_outcome IN ('success', 'skipped', 'failed')),
+    last_error TEXT
+);
@@ -0,0 +1,4 @@
+-- Status history for GET /applications/:id/timeline is read from the audit log by application id
+CREATE INDEX IF NOT EXISTS idx_audit_log_application_id
+    ON audit_log (((new_data->>'id')::int), timestamp)
+    WHERE table_name = 'applications';
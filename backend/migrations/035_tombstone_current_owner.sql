@@ -0,0 +1,12 @@
+-- A reassigned application already has a tombstone for its previous owner.
+-- Deleting it later must move the tombstone to the owner at deletion time.
+CREATE OR REPLACE FUNCTION record_application_tombstone()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO application_tombstones (application_id, user_id)
+    VALUES (OLD.id, OLD.user_id)
+    ON CONFLICT (application_id) DO UPDATE
+    SET user_id = EXCLUDED.user_id, deleted_at = NOW();
+    RETURN OLD;
+END;
+$$ language 'plpgsql';
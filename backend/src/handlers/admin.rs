@@ -1,22 +1,38 @@
 use axum::{
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json,
+    },
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
 
 use crate::{
-    middleware::auth::AuthUser,
+    middleware::{
+        auth::{session_still_valid, AuthUser},
+        request_id::RequestId,
+    },
     models::{
         application::{Application, ApplicationResponse, ApplicationStatus},
-        user::{User, UserResponse},
+        user::{StudentResponse, StudentRow, User},
     },
+    services::events::AppEvent,
+    utils::{database::escape_like, errors::AppError, messages, pagination::Pagination},
     AppState,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AnalyticsResponse {
+    /// False while the scope has no applications yet; every rate below is
+    /// then 0 for lack of data, not a measured 0%
+    pub has_data: bool,
+    /// Applications the figures are computed from
+    pub sample_size: i64,
     pub total_students: i64,
     pub total_applications: i64,
     pub status_breakdown: HashMap<String, i64>,
@@ -29,9 +45,29 @@ pub struct AnalyticsResponse {
     pub success_rate: SuccessRateStats,
     pub response_times: ResponseTimeStats,
     pub top_performing_students: Vec<StudentPerformance>,
+    pub compensation: CompensationStats,
+    /// Entries cached before reason codes existed deserialize with an empty list
+    #[serde(default)]
+    pub failure_reasons: Vec<FailureReasonStats>,
+    #[serde(default)]
+    pub company_scores: Vec<CompanyScoreStats>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompensationStats {
+    pub by_company: Vec<CompensationGroup>,
+    pub by_industry: Vec<CompensationGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompensationGroup {
+    pub name: String,
+    pub currency: String,
+    pub median_offered: f64,
+    pub offers_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ScreeningStats {
     pub total_screenings: i64,
     pub passed: i64,
@@ -39,7 +75,7 @@ pub struct ScreeningStats {
     pub pending: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct InterviewStats {
     pub total_interviews: i64,
     pub passed: i64,
@@ -47,24 +83,37 @@ pub struct InterviewStats {
     pub pending: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailureReasonStats {
+    /// `screening` or `interview`
+    pub stage: String,
+    pub reason_code: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DailyStat {
+    /// ISO 8601 calendar date (`YYYY-MM-DD`), not an instant
     pub date: String,
     pub applications_count: i64,
     pub screenings_count: i64,
     pub interviews_count: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SuccessRateStats {
     pub overall_success_rate: f64,
     pub screening_to_interview_rate: f64,
     pub interview_success_rate: f64,
+    pub offer_rate: f64,
+    pub offer_acceptance_rate: f64,
+    pub offers_received: i64,
+    pub offers_accepted: i64,
     pub applications_with_urls: i64,
     pub applications_without_urls: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ResponseTimeStats {
     pub avg_days_to_screening: f64,
     pub avg_days_to_interview: f64,
@@ -72,24 +121,36 @@ pub struct ResponseTimeStats {
     pub slowest_screening_days: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StudentPerformance {
     pub student_email: String,
     pub student_name: String,
     pub total_applications: i64,
     pub screenings_passed: i64,
     pub interviews_passed: i64,
+    pub offers_received: i64,
     pub success_rate: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CompanyStats {
     pub company: String,
     pub application_count: i64,
     pub unique_students: i64,
 }
 
-#[derive(Debug, Serialize)]
+/// Average screening and interview scores at one company
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanyScoreStats {
+    pub company: String,
+    /// `None` when no screening there has a score yet
+    pub avg_screening_score: Option<f64>,
+    pub avg_interview_score: Option<f64>,
+    /// Screenings and interviews with a score
+    pub scored_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JobUrlStats {
     pub job_url: String,
     pub application_count: i64,
@@ -103,12 +164,25 @@ pub struct AdminQuery {
     pub days_stale: Option<i32>,
 }
 
+/// How long clients are asked to wait when analytics are at their concurrency limit
+const ANALYTICS_BUSY_RETRY_AFTER_SECS: u64 = 5;
+
+/// Response when every analytics computation slot stayed taken
+pub(crate) fn analytics_busy() -> AppError {
+    AppError::ServiceUnavailable {
+        message: messages::text("analytics.busy"),
+        retry_after_secs: ANALYTICS_BUSY_RETRY_AFTER_SECS,
+    }
+}
+
 pub async fn get_analytics(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
     Query(_query): Query<AdminQuery>,
-) -> Result<Json<AnalyticsResponse>, StatusCode> {
+) -> Result<Json<AnalyticsResponse>, AppError> {
     use crate::services::analytics::{AnalyticsError, AnalyticsService};
+    use crate::services::cache::CacheContext;
     use crate::utils::logger::LOGGER;
 
     // Check if user is admin
@@ -124,14 +198,20 @@ pub async fn get_analytics(
             .cloned()
             .collect(),
         );
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text("analytics.admin_only")));
     }
 
     LOGGER.log_request("GET", "/admin/analytics", Some(auth_user.user_id), 200);
 
-    let analytics_service = AnalyticsService::new(state.db.clone());
-
-    match analytics_service.get_comprehensive_analytics().await {
+    let analytics_service = AnalyticsService::new(state.db.clone(), auth_user.cohort_scope());
+    match analytics_service
+        .get_cached_analytics(
+            &state.cache,
+            &state.analytics_limit,
+            Some(&CacheContext::new(auth_user.user_id, &request_id)),
+        )
+        .await
+    {
         Ok(analytics) => {
             LOGGER.log_business_event(
                 "analytics_request_completed",
@@ -151,46 +231,206 @@ pub async fn get_analytics(
                 serde_json::Value::String("database".to_string()),
             );
             LOGGER.log_error(&msg, context);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "analytics.fetch_failed",
+            )))
+        }
+        Err(AnalyticsError::QueryTimeout) => Err(AppError::QueryTimeout(messages::text(
+            "analytics.query_timeout",
+        ))),
+        Err(AnalyticsError::Overloaded) => Err(analytics_busy()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalyticsRefreshResponse {
+    pub refreshed: bool,
+    pub duration_ms: u64,
+}
+
+/// Force a refresh of the analytics materialized views, e.g. after bulk imports
+pub async fn refresh_analytics(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
+) -> Result<Json<AnalyticsRefreshResponse>, AppError> {
+    use crate::services::analytics::{AnalyticsError, AnalyticsService};
+    use crate::services::cache::CacheContext;
+    use crate::utils::logger::LOGGER;
+
+    // The views span every cohort, so only super-admins may trigger a refresh
+    if !auth_user.is_super_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_analytics_refresh",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "analytics.super_admin_only_refresh",
+        )));
+    }
+
+    let start_time = std::time::Instant::now();
+
+    match AnalyticsService::refresh_materialized_views(
+        &state.db,
+        &state.cache,
+        Some(&CacheContext::new(auth_user.user_id, &request_id)),
+    )
+    .await
+    {
+        Ok(()) => {
+            let duration_ms = start_time.elapsed().as_millis() as u64;
+            LOGGER.log_business_event(
+                "analytics_views_refreshed",
+                Some(auth_user.user_id),
+                [(
+                    "duration_ms".to_string(),
+                    serde_json::Value::Number(serde_json::Number::from(duration_ms)),
+                )]
+                .iter()
+                .cloned()
+                .collect(),
+            );
+            Ok(Json(AnalyticsRefreshResponse {
+                refreshed: true,
+                duration_ms,
+            }))
+        }
+        Err(AnalyticsError::QueryTimeout) => Err(AppError::QueryTimeout(messages::text(
+            "analytics.refresh_timeout",
+        ))),
+        Err(AnalyticsError::Overloaded) => Err(analytics_busy()),
+        Err(AnalyticsError::DatabaseError(msg)) => {
+            LOGGER.log_error(&msg, HashMap::new());
+            Err(AppError::InternalServerError(messages::text(
+                "analytics.refresh_failed",
+            )))
         }
-        Err(AnalyticsError::PermissionDenied) => Err(StatusCode::FORBIDDEN),
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StudentSort {
+    /// Most recently registered first
+    #[default]
+    Newest,
+    Oldest,
+    /// Most applications first
+    Applications,
+}
+
+impl StudentSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            StudentSort::Newest => "u.created_at DESC, u.id DESC",
+            StudentSort::Oldest => "u.created_at ASC, u.id ASC",
+            StudentSort::Applications => "application_count DESC, u.created_at DESC, u.id DESC",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StudentsQuery {
+    /// Matched against email, first, last and full name, case-insensitively
+    pub q: Option<String>,
+    #[serde(default)]
+    pub sort: StudentSort,
+    /// Deprecated 1-based alias for `offset`; when sent, it and `per_page`
+    /// replace `limit`/`offset`
+    pub page: Option<i64>,
+    /// Deprecated alias for `limit`, only read together with `page`
+    pub per_page: Option<i64>,
+}
+
 pub async fn get_all_students(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<Vec<UserResponse>>, StatusCode> {
+    Query(query): Query<StudentsQuery>,
+    pagination: Pagination,
+) -> Result<Json<Vec<StudentResponse>>, AppError> {
+    let pagination = match query.page {
+        Some(page) => Pagination::from_page(page, query.per_page),
+        None => pagination,
+    };
+
     // Check if user is admin
     if !auth_user.is_admin() {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text(
+            "users.admin_only_list_students",
+        )));
     }
 
-    let students = sqlx::query_as::<_, User>(
-        "SELECT * FROM users WHERE role = 'student' ORDER BY created_at DESC",
-    )
+    let pattern = query
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{}%", escape_like(q)));
+
+    let students = sqlx::query_as::<_, StudentRow>(&format!(
+        r#"
+        SELECT u.*, COALESCE(a.application_count, 0) AS application_count
+        FROM users u
+        LEFT JOIN (
+            SELECT user_id, COUNT(*)::bigint AS application_count
+            FROM applications
+            GROUP BY user_id
+        ) a ON a.user_id = u.id
+        WHERE u.role = 'student' AND ($1::int IS NULL OR u.cohort_id = $1)
+        AND ($2::text IS NULL
+             OR u.email ILIKE $2
+             OR u.first_name ILIKE $2
+             OR u.last_name ILIKE $2
+             OR u.first_name || ' ' || u.last_name ILIKE $2)
+        ORDER BY {}
+        LIMIT $3 OFFSET $4
+        "#,
+        query.sort.order_by()
+    ))
+    .bind(auth_user.cohort_scope())
+    .bind(pattern)
+    .bind(pagination.limit)
+    .bind(pagination.offset)
     .fetch_all(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
-    Ok(Json(students.into_iter().map(UserResponse::from).collect()))
+    Ok(Json(
+        students.into_iter().map(StudentResponse::from).collect(),
+    ))
 }
 
 pub async fn get_all_applications(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Query(_query): Query<AdminQuery>,
-) -> Result<Json<Vec<ApplicationResponse>>, StatusCode> {
+    pagination: Pagination,
+) -> Result<Json<Vec<ApplicationResponse>>, AppError> {
     // Check if user is admin
     if !auth_user.is_admin() {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text(
+            "applications.admin_only_list_all",
+        )));
     }
 
-    let applications =
-        sqlx::query_as::<_, Application>("SELECT * FROM applications ORDER BY created_at DESC")
-            .fetch_all(&state.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let applications = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications
+         WHERE $1::int IS NULL OR cohort_id = $1
+         ORDER BY created_at DESC, id DESC
+         LIMIT $2 OFFSET $3",
+    )
+    .bind(auth_user.cohort_scope())
+    .bind(pagination.limit)
+    .bind(pagination.offset)
+    .fetch_all(&state.db)
+    .await?;
 
     let mut responses = Vec::new();
     for app in applications {
@@ -227,7 +467,7 @@ pub async fn get_all_applications(
 pub async fn get_admin_activity(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<Vec<crate::services::activity::ActivityData>>, StatusCode> {
+) -> Result<Json<Vec<crate::services::activity::ActivityData>>, AppError> {
     use crate::services::activity::{ActivityError, ActivityService};
     use crate::utils::logger::LOGGER;
 
@@ -243,14 +483,17 @@ pub async fn get_admin_activity(
             .cloned()
             .collect(),
         );
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text("activity.admin_only")));
     }
 
     LOGGER.log_request("GET", "/admin/activity", Some(auth_user.user_id), 200);
 
     let activity_service = ActivityService::new(state.db.clone());
 
-    match activity_service.get_admin_activity().await {
+    match activity_service
+        .get_admin_activity(auth_user.cohort_scope())
+        .await
+    {
         Ok(activity_data) => {
             LOGGER.log_business_event(
                 "admin_activity_request_completed",
@@ -276,17 +519,110 @@ pub async fn get_admin_activity(
                 serde_json::Value::String("database".to_string()),
             );
             LOGGER.log_error(&msg, context);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "activity.fetch_failed",
+            )))
         }
-        Err(ActivityError::PermissionDenied) => Err(StatusCode::FORBIDDEN),
+        Err(ActivityError::PermissionDenied) => {
+            Err(AppError::Forbidden(messages::text("activity.not_allowed")))
+        }
+    }
+}
+
+/// Events buffered for a slow activity stream client before the forwarder waits
+const ACTIVITY_STREAM_BUFFER: usize = 64;
+/// How often an open activity stream re-checks the admin's session, matching
+/// the default SSE keep-alive
+const ACTIVITY_STREAM_RECHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+pub async fn stream_admin_activity(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    use crate::utils::logger::LOGGER;
+
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_admin_activity_stream_access",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text("activity.admin_only")));
     }
+
+    LOGGER.log_request(
+        "GET",
+        "/admin/activity/stream",
+        Some(auth_user.user_id),
+        200,
+    );
+
+    let cohort_scope = auth_user.cohort_scope();
+    let mut events = state.events.subscribe_all();
+    let (sender, receiver) = mpsc::channel(ACTIVITY_STREAM_BUFFER);
+
+    // Forwarded from a task so the session can be re-checked between events;
+    // the stream ends once the token is revoked or the user is no longer an admin
+    tokio::spawn(async move {
+        let mut recheck = tokio::time::interval(ACTIVITY_STREAM_RECHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        let Some(sse_event) = activity_sse_event(&event, cohort_scope) else {
+                            continue;
+                        };
+                        if sender.send(Ok(sse_event)).await.is_err() {
+                            break;
+                        }
+                    }
+                    // A slow client missed some events; keep streaming the newer ones
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                _ = recheck.tick() => {
+                    let valid = session_still_valid(
+                        &state,
+                        auth_user.user_id,
+                        auth_user.token_version,
+                        true,
+                    )
+                    .await;
+                    if !matches!(valid, Ok(true)) {
+                        break;
+                    }
+                }
+                _ = sender.closed() => break,
+            }
+        }
+    });
+
+    Ok(Sse::new(ReceiverStream::new(receiver)).keep_alive(KeepAlive::default()))
+}
+
+/// The SSE event for an activity event inside `cohort_scope`
+fn activity_sse_event(event: &AppEvent, cohort_scope: Option<i32>) -> Option<Event> {
+    if !event.is_activity() || !event.in_cohort_scope(cohort_scope) {
+        return None;
+    }
+    let payload = serde_json::to_string(event).ok()?;
+    let event_name = serde_json::to_value(event.event_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))?;
+    Some(Event::default().event(event_name).data(payload))
 }
 
 pub async fn get_user_activity_admin(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Path(user_id): Path<i32>,
-) -> Result<Json<Vec<crate::services::activity::ActivityData>>, StatusCode> {
+) -> Result<Json<Vec<crate::services::activity::ActivityData>>, AppError> {
     use crate::services::activity::{ActivityError, ActivityService};
     use crate::utils::logger::LOGGER;
 
@@ -303,7 +639,22 @@ pub async fn get_user_activity_admin(
             .cloned()
             .collect(),
         );
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text("activity.admin_only")));
+    }
+
+    // Cohort admins may only inspect students of their own cohort
+    if let Some(cohort_id) = auth_user.cohort_scope() {
+        let in_cohort = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND cohort_id = $2)",
+        )
+        .bind(user_id)
+        .bind(cohort_id)
+        .fetch_one(&state.db)
+        .await?;
+
+        if !in_cohort {
+            return Err(AppError::NotFound(messages::text("users.not_found")));
+        }
     }
 
     LOGGER.log_business_event(
@@ -356,8 +707,441 @@ pub async fn get_user_activity_admin(
                 serde_json::Value::String("database".to_string()),
             );
             LOGGER.log_error(&msg, context);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "activity.fetch_failed",
+            )))
+        }
+        Err(ActivityError::PermissionDenied) => {
+            Err(AppError::Forbidden(messages::text("activity.not_allowed")))
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeSessionsResponse {
+    pub user_id: i32,
+    pub token_version: i32,
+}
+
+/// Revokes every token issued to a user; cohort admins may only target their cohort
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(user_id): Path<i32>,
+) -> Result<Json<RevokeSessionsResponse>, AppError> {
+    use crate::middleware::auth::revoke_sessions;
+    use crate::utils::logger::LOGGER;
+
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_session_revoke",
+            Some(auth_user.user_id),
+            [(
+                "target_user_id".to_string(),
+                serde_json::Value::Number(user_id.into()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "users.admin_only_revoke",
+        )));
+    }
+
+    let in_scope = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND ($2::int IS NULL OR cohort_id = $2))",
+    )
+    .bind(user_id)
+    .bind(auth_user.cohort_scope())
+    .fetch_one(&state.db)
+    .await?;
+    if !in_scope {
+        return Err(AppError::NotFound(messages::text("users.not_found")));
+    }
+
+    let token_version = revoke_sessions(&state, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    LOGGER.log_business_event(
+        "sessions_revoked",
+        Some(auth_user.user_id),
+        [(
+            "user_id".to_string(),
+            serde_json::Value::Number(user_id.into()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(RevokeSessionsResponse {
+        user_id,
+        token_version,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImpersonationResponse {
+    /// Send as the bearer token; only read requests are accepted with it
+    pub token: String,
+    pub user_id: i32,
+    pub impersonated_by: i32,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Issues a short-lived, read-only token acting as a student, for support.
+///
+/// Cohort admins may only impersonate students of their cohort. The grant and
+/// every request made with the token are written to `audit_log`.
+pub async fn impersonate_user(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(user_id): Path<i32>,
+) -> Result<Json<ImpersonationResponse>, AppError> {
+    use crate::utils::jwt::{create_impersonation_jwt, IMPERSONATION_TOKEN_MINUTES};
+    use crate::utils::logger::LOGGER;
+    use sqlx::Row;
+
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_impersonation",
+            Some(auth_user.user_id),
+            [(
+                "target_user_id".to_string(),
+                serde_json::Value::Number(user_id.into()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "users.admin_only_impersonate",
+        )));
+    }
+
+    let user = sqlx::query(
+        "SELECT role::text AS role, cohort_id, token_version FROM users
+         WHERE id = $1 AND ($2::int IS NULL OR cohort_id = $2)",
+    )
+    .bind(user_id)
+    .bind(auth_user.cohort_scope())
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    if user.get::<String, _>("role") != "student" {
+        return Err(AppError::BadRequest(messages::text(
+            "users.impersonate_students_only",
+        )));
+    }
+    let cohort_id: Option<i32> = user.get("cohort_id");
+
+    // Revoking the admin's own sessions ends their impersonation sessions too
+    let admin_version = state
+        .token_versions
+        .current_version(&state.db, auth_user.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    let token = create_impersonation_jwt(
+        user_id,
+        cohort_id,
+        user.get("token_version"),
+        auth_user.user_id,
+        admin_version,
+        &state.jwt_keys,
+    )
+    .map_err(|_| AppError::InternalServerError("Failed to create token".into()))?;
+    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(IMPERSONATION_TOKEN_MINUTES);
+
+    sqlx::query(
+        "INSERT INTO audit_log (table_name, operation, new_data, user_id)
+         VALUES ('impersonation', 'GRANT', $1, $2)",
+    )
+    .bind(serde_json::json!({
+        "impersonated_user_id": user_id,
+        "expires_at": crate::utils::time::rfc3339::format(&expires_at),
+    }))
+    .bind(auth_user.user_id)
+    .execute(&state.db)
+    .await?;
+
+    LOGGER.log_business_event(
+        "impersonation_granted",
+        Some(auth_user.user_id),
+        [(
+            "impersonated_user_id".to_string(),
+            serde_json::Value::Number(user_id.into()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(ImpersonationResponse {
+        token,
+        user_id,
+        impersonated_by: auth_user.user_id,
+        expires_at,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReassignApplicationRequest {
+    pub user_id: i32,
+}
+
+/// Moves an application, with its screening, interview and documents, to another student
+pub async fn reassign_application(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ReassignApplicationRequest>,
+) -> Result<Json<ApplicationResponse>, AppError> {
+    use crate::models::user::UserRole;
+    use crate::services::cache::CacheContext;
+    use crate::utils::logger::LOGGER;
+
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_application_reassign",
+            Some(auth_user.user_id),
+            [(
+                "application_id".to_string(),
+                serde_json::Value::Number(id.into()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "applications.admin_only_reassign",
+        )));
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    let application = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE id = $1 AND ($2::int IS NULL OR cohort_id = $2) FOR UPDATE",
+    )
+    .bind(id)
+    .bind(auth_user.cohort_scope())
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let target = sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE id = $1 AND ($2::int IS NULL OR cohort_id = $2)",
+    )
+    .bind(payload.user_id)
+    .bind(auth_user.cohort_scope())
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    if !matches!(target.role, UserRole::Student) {
+        return Err(AppError::BadRequest(messages::text(
+            "applications.reassign_students_only",
+        )));
+    }
+
+    // Screenings, interviews and documents hang off the application id and move with it
+    let reassigned = sqlx::query_as::<_, Application>(
+        "UPDATE applications SET user_id = $1, cohort_id = $2 WHERE id = $3 RETURNING *",
+    )
+    .bind(target.id)
+    .bind(target.cohort_id)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // To the previous owner's delta sync the application is gone
+    if application.user_id != target.id {
+        sqlx::query(
+            r#"
+            INSERT INTO application_tombstones (application_id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT (application_id) DO UPDATE
+            SET user_id = EXCLUDED.user_id, deleted_at = NOW()
+            "#,
+        )
+        .bind(id)
+        .bind(application.user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    LOGGER.log_business_event(
+        "application_reassigned",
+        Some(auth_user.user_id),
+        [
+            (
+                "application_id".to_string(),
+                serde_json::Value::Number(id.into()),
+            ),
+            (
+                "from_user_id".to_string(),
+                serde_json::Value::Number(application.user_id.into()),
+            ),
+            (
+                "to_user_id".to_string(),
+                serde_json::Value::Number(target.id.into()),
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    // Both owners' numbers feed the cached analytics and metrics
+    let context = CacheContext::new(auth_user.user_id, &request_id);
+    for pattern in ["analytics_", "metrics_"] {
+        if let Err(e) = state
+            .cache
+            .invalidate_pattern(pattern, Some(&context))
+            .await
+        {
+            tracing::warn!(
+                "Failed to invalidate {} cache after reassign: {:?}",
+                pattern,
+                e
+            );
         }
-        Err(ActivityError::PermissionDenied) => Err(StatusCode::FORBIDDEN),
     }
+
+    Ok(Json(ApplicationResponse::from(reassigned)))
+}
+
+/// SQL classifying `password_hash` by scheme, from its PHC / modular-crypt prefix
+const PASSWORD_SCHEME_SQL: &str = "
+    CASE
+        WHEN password_hash LIKE '$argon2%' THEN 'argon2'
+        WHEN password_hash LIKE '$2a$%' OR password_hash LIKE '$2b$%'
+             OR password_hash LIKE '$2y$%' THEN 'bcrypt'
+        ELSE 'unknown'
+    END";
+
+#[derive(Debug, Serialize)]
+pub struct PasswordMigrationStatus {
+    pub total_users: i64,
+    pub argon2: i64,
+    pub bcrypt: i64,
+    pub unknown: i64,
+    /// Users flagged for a forced password reset
+    pub reset_required: i64,
+    pub migrated_percent: f64,
+}
+
+/// Progress of the bcrypt to Argon2 migration, which happens on each user's next login
+pub async fn get_password_migration_status(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<PasswordMigrationStatus>, AppError> {
+    use sqlx::Row;
+
+    if !auth_user.is_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "users.admin_only_password_migration",
+        )));
+    }
+
+    let row = sqlx::query(&format!(
+        "SELECT
+            COUNT(*)::bigint,
+            COUNT(*) FILTER (WHERE scheme = 'argon2')::bigint,
+            COUNT(*) FILTER (WHERE scheme = 'bcrypt')::bigint,
+            COUNT(*) FILTER (WHERE scheme = 'unknown')::bigint,
+            COUNT(*) FILTER (WHERE password_reset_required)::bigint
+         FROM (
+            SELECT {} AS scheme, password_reset_required
+            FROM users
+            WHERE $1::int IS NULL OR cohort_id = $1
+         ) classified",
+        PASSWORD_SCHEME_SQL
+    ))
+    .bind(auth_user.cohort_scope())
+    .fetch_one(&state.db)
+    .await?;
+
+    let total_users: i64 = row.get(0);
+    let argon2: i64 = row.get(1);
+
+    Ok(Json(PasswordMigrationStatus {
+        total_users,
+        argon2,
+        bcrypt: row.get(2),
+        unknown: row.get(3),
+        reset_required: row.get(4),
+        migrated_percent: if total_users > 0 {
+            argon2 as f64 * 100.0 / total_users as f64
+        } else {
+            100.0
+        },
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlagLegacyPasswordsResponse {
+    pub flagged: u64,
+}
+
+/// Flags every user still on a bcrypt hash for a forced password reset.
+///
+/// Their sessions are revoked too; until they change their password through
+/// `/auth/change-password`, login answers `PASSWORD_RESET_REQUIRED`.
+pub async fn flag_legacy_passwords(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<FlagLegacyPasswordsResponse>, AppError> {
+    use crate::utils::logger::LOGGER;
+
+    if !auth_user.is_super_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_password_reset_flagging",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "users.super_admin_only_password_reset",
+        )));
+    }
+
+    let flagged = sqlx::query_scalar::<_, i32>(&format!(
+        "UPDATE users SET password_reset_required = TRUE, token_version = token_version + 1
+         WHERE NOT password_reset_required AND ({}) = 'bcrypt'
+         RETURNING id",
+        PASSWORD_SCHEME_SQL
+    ))
+    .fetch_all(&state.db)
+    .await?;
+    for user_id in &flagged {
+        state.token_versions.invalidate(*user_id);
+    }
+    let flagged = flagged.len() as u64;
+
+    LOGGER.log_business_event(
+        "legacy_passwords_flagged",
+        Some(auth_user.user_id),
+        [(
+            "flagged".to_string(),
+            serde_json::Value::Number(flagged.into()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(FlagLegacyPasswordsResponse { flagged }))
 }
@@ -1,13 +1,19 @@
 use axum::{
-    extract::{Extension, Multipart, Path, State},
+    extract::{
+        multipart::{Field, MultipartError},
+        Extension, Multipart, Path, Query, State,
+    },
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
+use chrono::{DateTime, NaiveDate, Utc};
 use infer;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
-use tokio::fs;
+use std::sync::Arc;
+use tokio::{fs, io::AsyncWriteExt};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -15,32 +21,57 @@ use crate::{
     middleware::auth::AuthUser,
     models::{
         application::{
-            Application, ApplicationResponse, CreateApplicationRequest, UpdateApplicationRequest,
+            Application, ApplicationResponse, ApplicationStatus, ApplicationSyncResponse,
+            ApplicationTimelineResponse, ApplicationTombstone, ApplicationsCountQuery,
+            ApplicationsCountResponse, ApplicationsQuery, BatchApplicationsRequest,
+            CreateApplicationRequest, Includes, StatusChange, StatusTransitionsResponse,
+            TimelineEvent, TimelineEventKind, UpdateApplicationRequest,
         },
+        document::{Document, DocumentResponse},
         interview::{Interview, InterviewResponse, UpdateInterviewRequest},
+        list::ListResponse,
         screening::{Screening, ScreeningResponse, UpdateScreeningRequest},
     },
-    utils::errors::AppError,
+    services::{
+        events::{AppEvent, EventType},
+        settings::{OUTCOME_REASON_CODES, OUTCOME_SCORE_SCALE},
+        storage::{FileStore, StorageError},
+        webhooks::{self, WebhookEventType, WebhookPayload},
+    },
+    utils::{
+        company::normalize_company, errors::AppError, logger::LOGGER, messages,
+        pagination::Pagination,
+    },
     AppState,
 };
 
-const ALLOWED_EXTENSIONS: &[&str] = &[
-    "mp3", "wav", "ogg", "m4a", "aac", // Audio formats
-    "mp4", "webm", "mov", "avi", "mkv", // Video formats
+/// Allowed extensions and the MIME types `infer` may detect for each.
+///
+/// A file is only accepted when its content matches what its extension claims,
+/// so an `.mp3` carrying MP4 bytes is rejected.
+const ALLOWED_FILE_TYPES: &[(&str, &[&str])] = &[
+    // Audio formats
+    ("mp3", &["audio/mpeg"]),
+    ("wav", &["audio/x-wav", "audio/wav"]),
+    ("ogg", &["audio/ogg", "audio/opus"]),
+    ("m4a", &["audio/m4a", "audio/mp4", "video/mp4"]),
+    ("aac", &["audio/aac"]),
+    // Video formats
+    ("mp4", &["video/mp4", "audio/mp4", "audio/m4a"]),
+    ("webm", &["video/webm", "audio/webm"]),
+    ("mov", &["video/quicktime"]),
+    ("avi", &["video/x-msvideo", "video/avi"]),
+    ("mkv", &["video/x-matroska", "video/webm"]),
 ];
 
-const ALLOWED_MIME_TYPES: &[&str] = &[
-    "audio/mpeg",
-    "audio/wav",
-    "audio/ogg",
-    "audio/mp4",
-    "audio/webm",
-    "audio/aac",
-    "video/mp4",
-    "video/webm",
-    "video/quicktime",
-    "video/x-msvideo",
-    "video/avi",
+/// Allowed document attachments, checked the same way as recordings
+const ALLOWED_DOCUMENT_TYPES: &[(&str, &[&str])] = &[
+    ("pdf", &["application/pdf"]),
+    (
+        "docx",
+        &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"],
+    ),
+    ("png", &["image/png"]),
 ];
 
 fn get_max_file_size() -> usize {
@@ -52,52 +83,525 @@ fn get_max_file_size() -> usize {
         * 1024
 }
 
-fn validate_file_security(filename: &str, data: &[u8]) -> Result<String, StatusCode> {
-    // Validate file size
-    if data.len() > get_max_file_size() {
-        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+/// Reason codes a screening or interview outcome always accepts;
+/// the `outcome.reason_codes` setting can add more
+const DEFAULT_REASON_CODES: &[&str] = &[
+    "technical-skills",
+    "experience",
+    "communication",
+    "culture-fit",
+    "compensation",
+    "position-filled",
+    "no-response",
+    "other",
+];
+
+/// Longest outcome feedback accepted, in characters
+const MAX_FEEDBACK_CHARS: usize = 2000;
+/// Multipart cap for the feedback field; a character takes up to 4 bytes in UTF-8
+const MAX_FEEDBACK_FIELD_BYTES: usize = MAX_FEEDBACK_CHARS * 4;
+
+/// Longest download name kept from an upload, in characters
+const MAX_DOWNLOAD_NAME_CHARS: usize = 255;
+
+/// What recordings are named when downloaded, from `DOWNLOAD_FILENAME_STYLE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DownloadFilenameStyle {
+    /// The name the file was uploaded with
+    Original,
+    /// `company_date_kind.ext`
+    Generated,
+}
+
+fn get_download_filename_style() -> DownloadFilenameStyle {
+    match env::var("DOWNLOAD_FILENAME_STYLE").as_deref() {
+        Ok("generated") => DownloadFilenameStyle::Generated,
+        _ => DownloadFilenameStyle::Original,
+    }
+}
+
+/// Name a recording is downloaded under; the stored file keeps its UUID key.
+///
+/// Falls back to the generated name when nothing usable is left of the original.
+fn download_filename(
+    original: &str,
+    company: &str,
+    date: Option<NaiveDate>,
+    kind: &str,
+    extension: &str,
+) -> String {
+    if get_download_filename_style() == DownloadFilenameStyle::Original {
+        // Some browsers send the full client path
+        let name = original.rsplit(['/', '\\']).next().unwrap_or(original);
+        let name: String = name
+            .chars()
+            .filter(|c| !c.is_control() && *c != '"')
+            .take(MAX_DOWNLOAD_NAME_CHARS)
+            .collect();
+        let name = name.trim();
+        if !name.is_empty() && name != "." && name != ".." {
+            return name.to_string();
+        }
+    }
+
+    // Letters of any script are kept so Cyrillic company names survive
+    let company: String = company
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let date = date.unwrap_or_else(|| Utc::now().date_naive());
+    format!(
+        "{}_{}_{}.{}",
+        company,
+        date.format("%Y-%m-%d"),
+        kind,
+        extension
+    )
+    .chars()
+    .take(MAX_DOWNLOAD_NAME_CHARS)
+    .collect()
+}
+
+/// Cap for short multipart text fields (dates, statuses, codes), from `MAX_TEXT_FIELD_BYTES`
+fn get_max_text_field_bytes() -> usize {
+    env::var("MAX_TEXT_FIELD_BYTES")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(1024)
+}
+
+/// How much of a file the dry-run endpoint looks at; magic bytes live at the start
+const VALIDATION_SAMPLE_BYTES: usize = 64 * 1024;
+
+fn validate_file_size(size: usize) -> Result<(), AppError> {
+    if size > get_max_file_size() {
+        return Err(AppError::PayloadTooLarge(
+            format!(
+                "File exceeds the {} MB upload limit",
+                get_max_file_size() / 1024 / 1024
+            )
+            .into(),
+        ));
     }
+    Ok(())
+}
 
+/// Extension and magic-byte checks; `data` only needs to hold the start of the file
+fn validate_file_content(
+    filename: &str,
+    data: &[u8],
+    allowed_file_types: &[(&str, &[&str])],
+) -> Result<String, AppError> {
     // Validate file extension
     let extension = std::path::Path::new(filename)
         .extension()
         .and_then(|ext| ext.to_str())
-        .ok_or(StatusCode::BAD_REQUEST)?
+        .ok_or_else(|| AppError::BadRequest(messages::text("uploads.no_extension")))?
         .to_lowercase();
 
-    if !ALLOWED_EXTENSIONS.contains(&extension.as_str()) {
-        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
-    }
+    let allowed_types = allowed_file_types
+        .iter()
+        .find(|(allowed, _)| *allowed == extension)
+        .map(|(_, mime_types)| *mime_types)
+        .ok_or_else(|| {
+            AppError::UnsupportedMediaType(
+                format!("Files with extension '{}' are not allowed", extension).into(),
+            )
+        })?;
 
     // Validate magic bytes using infer crate
-    let kind = infer::get(data).ok_or(StatusCode::UNSUPPORTED_MEDIA_TYPE)?;
-
-    if !ALLOWED_MIME_TYPES.contains(&kind.mime_type()) {
-        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let kind = infer::get(data)
+        .ok_or_else(|| AppError::UnsupportedMediaType(messages::text("uploads.unknown_type")))?;
+
+    if !allowed_types.contains(&kind.mime_type()) {
+        return Err(AppError::UnsupportedMediaType(
+            format!(
+                "File claims to be '.{}' but its content is '{}'",
+                extension,
+                kind.mime_type()
+            )
+            .into(),
+        ));
     }
 
     Ok(extension)
 }
 
-pub async fn get_applications(
-    State(state): State<AppState>,
-    Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<Vec<ApplicationResponse>>, StatusCode> {
-    // For simplicity, use separate queries to avoid complex JOIN handling
-    let applications = sqlx::query_as::<_, Application>(
-        "SELECT * FROM applications WHERE user_id = $1 ORDER BY created_at DESC",
+/// Collects the first `VALIDATION_SAMPLE_BYTES` of a streamed upload and runs
+/// `validate` on them exactly once, as soon as the sample is full or, for
+/// smaller files, when the upload ends
+struct SampleValidator<F> {
+    sample: Vec<u8>,
+    extension: Option<String>,
+    validate: F,
+}
+
+impl<F> SampleValidator<F>
+where
+    F: FnMut(&[u8]) -> Result<String, AppError>,
+{
+    fn new(validate: F) -> Self {
+        Self {
+            sample: Vec::new(),
+            extension: None,
+            validate,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Result<(), AppError> {
+        if self.extension.is_none() {
+            let needed = VALIDATION_SAMPLE_BYTES - self.sample.len();
+            self.sample
+                .extend_from_slice(&chunk[..needed.min(chunk.len())]);
+            if self.sample.len() == VALIDATION_SAMPLE_BYTES {
+                self.extension = Some((self.validate)(&self.sample)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// The validated extension
+    fn finish(mut self) -> Result<String, AppError> {
+        match self.extension.take() {
+            Some(extension) => Ok(extension),
+            None => (self.validate)(&self.sample),
+        }
+    }
+}
+
+fn multipart_error(error: MultipartError) -> AppError {
+    AppError::BadRequest(format!("Malformed multipart body: {}", error.body_text()).into())
+}
+
+/// Reads a multipart text field, rejecting values that aren't UTF-8.
+///
+/// The field is read chunk by chunk and rejected as soon as it passes
+/// `max_bytes`, so an oversized value is never buffered whole.
+async fn read_text_field(mut field: Field<'_>, max_bytes: usize) -> Result<String, AppError> {
+    let name = field.name().unwrap_or("").to_string();
+    let mut data = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        if data.len() + chunk.len() > max_bytes {
+            return Err(AppError::BadRequest(
+                format!("Field '{}' exceeds {} bytes", name, max_bytes).into(),
+            ));
+        }
+        data.extend_from_slice(&chunk);
+    }
+
+    String::from_utf8(data)
+        .map_err(|_| AppError::BadRequest(format!("Field '{}' must be UTF-8 text", name).into()))
+}
+
+/// Empty means "leave unchanged"; anything else must be a known reason code
+fn parse_reason_code(state: &AppState, value: &str) -> Result<Option<String>, AppError> {
+    let code = value.trim();
+    if code.is_empty() {
+        return Ok(None);
+    }
+
+    let extra_codes: Vec<String> = state
+        .settings
+        .read()
+        .unwrap()
+        .get(OUTCOME_REASON_CODES)
+        .unwrap_or_default();
+    if DEFAULT_REASON_CODES.contains(&code) || extra_codes.iter().any(|extra| extra == code) {
+        Ok(Some(code.to_string()))
+    } else {
+        Err(AppError::BadRequest(
+            format!("Unknown reason code '{}'", code).into(),
+        ))
+    }
+}
+
+fn parse_feedback(value: String) -> Result<Option<String>, AppError> {
+    let feedback = value.trim();
+    if feedback.is_empty() {
+        return Ok(None);
+    }
+    if feedback.chars().count() > MAX_FEEDBACK_CHARS {
+        return Err(AppError::BadRequest(
+            format!("Feedback must be at most {} characters", MAX_FEEDBACK_CHARS).into(),
+        ));
+    }
+    Ok(Some(feedback.to_string()))
+}
+
+/// Scores accepted under the `outcome.score_scale` setting
+fn score_range(state: &AppState) -> std::ops::RangeInclusive<i16> {
+    match state
+        .settings
+        .read()
+        .unwrap()
+        .get::<i16>(OUTCOME_SCORE_SCALE)
+    {
+        Some(5) => 1..=5,
+        _ => 0..=100,
+    }
+}
+
+fn parse_score(state: &AppState, value: &str) -> Result<Option<i16>, AppError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let range = score_range(state);
+    match value.parse::<i16>() {
+        Ok(score) if range.contains(&score) => Ok(Some(score)),
+        _ => Err(AppError::BadRequest(
+            format!(
+                "Score must be a whole number between {} and {}",
+                range.start(),
+                range.end()
+            )
+            .into(),
+        )),
+    }
+}
+
+/// Attempts at finding an unused name before giving up
+const UNIQUE_NAME_ATTEMPTS: usize = 3;
+
+/// An upload written to the upload directory, waiting to be moved into the file store.
+///
+/// The temp file is removed when this is dropped, so every early return after
+/// staging cleans up. Once moved into the store there is nothing left to remove.
+struct StagedUpload {
+    temp_path: PathBuf,
+    extension: String,
+    nonce: Option<Vec<u8>>,
+    /// Size of the upload as received, before any encryption
+    size_bytes: usize,
+}
+
+impl Drop for StagedUpload {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.temp_path);
+    }
+}
+
+/// An upload moved into the file store under `key`.
+///
+/// Until `keep` is called, dropping it deletes the stored file again, so an
+/// upload whose database row never commits doesn't leak storage.
+struct StoredUpload {
+    key: String,
+    nonce: Option<Vec<u8>>,
+    discard_from: Option<Arc<dyn FileStore>>,
+}
+
+impl StoredUpload {
+    /// Call once the row referencing the file has committed
+    fn keep(mut self) {
+        self.discard_from = None;
+    }
+}
+
+impl Drop for StoredUpload {
+    fn drop(&mut self) {
+        if let Some(files) = self.discard_from.take() {
+            let key = std::mem::take(&mut self.key);
+            tokio::spawn(async move {
+                if let Err(e) = files.delete(&key).await {
+                    tracing::warn!("Failed to remove unreferenced file {}: {}", key, e);
+                }
+            });
+        }
+    }
+}
+
+/// The client error for a failed write of an upload. A full disk is a 507
+/// logged as `storage_full` so ops gets alerted; anything else is a 500.
+fn upload_write_error(state: &AppState, error: StorageError) -> AppError {
+    let StorageError::StorageFull(msg) = error else {
+        return AppError::InternalServerError(messages::text("uploads.store_failed"));
+    };
+
+    let mut context = HashMap::new();
+    context.insert(
+        "error_type".to_string(),
+        serde_json::Value::String("storage_full".to_string()),
+    );
+    context.insert(
+        "upload_dir".to_string(),
+        serde_json::Value::String(state.upload_dir.clone()),
+    );
+    LOGGER.log_error(&msg, context);
+    AppError::InsufficientStorage(messages::text("uploads.storage_full"))
+}
+
+/// Creates a fresh temp file in `upload_dir`.
+///
+/// The file is created with create-new semantics, so concurrent uploads can
+/// never write to the same temp file.
+async fn create_temp_file(upload_dir: &std::path::Path) -> std::io::Result<(PathBuf, fs::File)> {
+    for _ in 0..UNIQUE_NAME_ATTEMPTS {
+        let temp_path = upload_dir.join(format!("{}.tmp", Uuid::new_v4()));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+            .await
+        {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        "No unused temp file name found",
+    ))
+}
+
+/// Streams the `file` field into a temp file, validating it on the way, and
+/// returns the name it was uploaded with alongside the staged file.
+///
+/// Only the first `VALIDATION_SAMPLE_BYTES` are held in memory for the
+/// magic-byte check; the size limit is enforced per chunk, so an oversized or
+/// mislabelled file is rejected before it is written in full.
+async fn stage_file_field(
+    state: &AppState,
+    mut field: Field<'_>,
+    allowed_file_types: &[(&str, &[&str])],
+) -> Result<(String, StagedUpload), AppError> {
+    let filename = field
+        .file_name()
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| AppError::BadRequest(messages::text("uploads.filename_missing")))?
+        .to_string();
+
+    let (temp_path, mut file) = create_temp_file(std::path::Path::new(&state.upload_dir))
+        .await
+        .map_err(|e| upload_write_error(state, e.into()))?;
+    // From here on the temp file belongs to `staged` and goes away with it
+    let mut staged = StagedUpload {
+        temp_path,
+        extension: String::new(),
+        nonce: None,
+        size_bytes: 0,
+    };
+
+    let write_failed = |e: std::io::Error| upload_write_error(state, e.into());
+    let mut validator = SampleValidator::new(|sample: &[u8]| {
+        validate_file_content(&filename, sample, allowed_file_types)
+    });
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        validate_file_size(staged.size_bytes + chunk.len())?;
+        staged.size_bytes += chunk.len();
+        validator.feed(&chunk)?;
+        file.write_all(&chunk).await.map_err(write_failed)?;
+    }
+
+    if staged.size_bytes == 0 {
+        return Err(AppError::BadRequest(messages::text("uploads.empty_file")));
+    }
+    staged.extension = validator.finish()?;
+    file.flush().await.map_err(write_failed)?;
+    drop(file);
+
+    // Validation ran on the plaintext; only the stored bytes are encrypted.
+    // AES-GCM seals the file as a whole, so encrypted uploads are read back
+    // into memory once here.
+    if let Some(cipher) = &state.file_cipher {
+        let plaintext = fs::read(&staged.temp_path).await.map_err(write_failed)?;
+        let (ciphertext, nonce) = cipher
+            .encrypt(&plaintext)
+            .map_err(|_| AppError::InternalServerError("Failed to encrypt file".into()))?;
+        drop(plaintext);
+        fs::write(&staged.temp_path, ciphertext)
+            .await
+            .map_err(write_failed)?;
+        staged.nonce = Some(nonce);
+    }
+
+    Ok((filename, staged))
+}
+
+/// Moves a staged upload into the file store under a new unique key.
+///
+/// The store refuses to overwrite an existing key, in which case another name
+/// is tried.
+async fn store_staged_upload(
+    state: &AppState,
+    mut staged: StagedUpload,
+) -> Result<StoredUpload, AppError> {
+    for _ in 0..UNIQUE_NAME_ATTEMPTS {
+        let key = format!("{}.{}", Uuid::new_v4(), staged.extension);
+        match state.files.put_file(&key, &staged.temp_path).await {
+            Ok(()) => {
+                return Ok(StoredUpload {
+                    key,
+                    nonce: staged.nonce.take(),
+                    discard_from: Some(state.files.clone()),
+                })
+            }
+            Err(StorageError::AlreadyExists) => continue,
+            Err(e @ StorageError::StorageFull(_)) => return Err(upload_write_error(state, e)),
+            Err(_) => break,
+        }
+    }
+
+    Err(AppError::InternalServerError(messages::text(
+        "uploads.store_failed",
+    )))
+}
+
+/// Best-effort removal of a stored file no row references any more
+async fn discard_stored_file(state: &AppState, key: &str) {
+    if let Err(e) = state.files.delete(key).await {
+        tracing::warn!("Failed to remove unreferenced file {}: {}", key, e);
+    }
+
+    // A transcoded preview goes with its original
+    let preview = sqlx::query_scalar::<_, Option<String>>(
+        "DELETE FROM file_previews WHERE file_path = $1 RETURNING preview_path",
     )
-    .bind(auth_user.user_id)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .bind(key)
+    .fetch_optional(&state.db)
+    .await;
+    match preview {
+        Ok(Some(Some(preview_path))) => {
+            if let Err(e) = state.files.delete(&preview_path).await {
+                tracing::warn!("Failed to remove preview {}: {}", preview_path, e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to remove preview record for {}: {}", key, e),
+    }
+}
+
+/// Attaches screenings and interviews to applications, fetching each kind in one query
+pub(crate) async fn with_sub_resources(
+    db: &sqlx::PgPool,
+    applications: Vec<Application>,
+) -> Vec<ApplicationResponse> {
+    with_included(db, applications, Includes::ALL).await
+}
 
+/// Like `with_sub_resources`, skipping the query of every sub-resource not in
+/// `includes`; those stay `null`
+async fn with_included(
+    db: &sqlx::PgPool,
+    applications: Vec<Application>,
+    includes: Includes,
+) -> Vec<ApplicationResponse> {
     // Get all screenings for these applications in one query
     let app_ids: Vec<i32> = applications.iter().map(|a| a.id).collect();
-    let screenings = if !app_ids.is_empty() {
+    let screenings = if includes.screening && !app_ids.is_empty() {
         sqlx::query_as::<_, Screening>("SELECT * FROM screenings WHERE application_id = ANY($1)")
             .bind(&app_ids)
-            .fetch_all(&state.db)
+            .fetch_all(db)
             .await
             .unwrap_or_default()
     } else {
@@ -105,10 +609,10 @@ pub async fn get_applications(
     };
 
     // Get all interviews for these applications in one query
-    let interviews = if !app_ids.is_empty() {
+    let interviews = if includes.interview && !app_ids.is_empty() {
         sqlx::query_as::<_, Interview>("SELECT * FROM interviews WHERE application_id = ANY($1)")
             .bind(&app_ids)
-            .fetch_all(&state.db)
+            .fetch_all(db)
             .await
             .unwrap_or_default()
     } else {
@@ -143,22 +647,201 @@ pub async fn get_applications(
         responses.push(response);
     }
 
-    Ok(Json(responses))
+    responses
+}
+
+pub async fn get_applications(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<ApplicationsQuery>,
+) -> Result<Response, AppError> {
+    let includes = Includes::parse(query.include.as_deref()).map_err(|message| {
+        let mut errors = HashMap::new();
+        errors.insert("include".to_string(), vec![message]);
+        AppError::ValidationError(errors)
+    })?;
+
+    // A sync reports sub-resource changes, so it always carries them
+    if let Some(since) = query.since {
+        let sync = sync_applications(&state, auth_user.user_id, since).await?;
+        return Ok(Json(sync).into_response());
+    }
+
+    // For simplicity, use separate queries to avoid complex JOIN handling
+    let applications = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE user_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(auth_user.user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(with_included(&state.db, applications, includes).await).into_response())
+}
+
+/// Number of the caller's applications, optionally only those in one status
+pub async fn count_applications(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<ApplicationsCountQuery>,
+) -> Result<Json<ApplicationsCountResponse>, AppError> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM applications WHERE user_id = $1 AND ($2::application_status IS NULL OR status = $2)",
+    )
+    .bind(auth_user.user_id)
+    .bind(query.status)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(ApplicationsCountResponse { count }))
+}
+
+/// One page of the caller's applications, in the list envelope
+pub async fn list_applications(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    pagination: Pagination,
+) -> Result<Json<ListResponse<ApplicationResponse>>, AppError> {
+    let total =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM applications WHERE user_id = $1")
+            .bind(auth_user.user_id)
+            .fetch_one(&state.db)
+            .await?;
+
+    let applications = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3",
+    )
+    .bind(auth_user.user_id)
+    .bind(pagination.limit)
+    .bind(pagination.offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(ListResponse::new(
+        with_sub_resources(&state.db, applications).await,
+        total,
+    )))
+}
+
+/// How far `server_time` trails the database clock, from `SYNC_SAFETY_WINDOW_SECS`.
+/// Should cover the longest transaction that writes applications.
+fn get_sync_safety_window_secs() -> i64 {
+    env::var("SYNC_SAFETY_WINDOW_SECS")
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .filter(|secs| *secs >= 0)
+        .unwrap_or(60)
+}
+
+/// Applications whose row, screening or interview changed after `since`, plus
+/// the ones deleted since then.
+///
+/// `updated_at` is stamped when a writing transaction starts, but the row only
+/// becomes visible when it commits. `server_time` is therefore taken before
+/// the data is read and set back by the safety window, so a transaction still
+/// in flight now is picked up by the next sync. The cost is overlap: rows
+/// changed within the window are returned again on the next sync, and clients
+/// must treat the response as an upsert keyed on `id`.
+async fn sync_applications(
+    state: &AppState,
+    user_id: i32,
+    since: DateTime<Utc>,
+) -> Result<ApplicationSyncResponse, sqlx::Error> {
+    // Taken from the database clock, which also stamps `updated_at`
+    let server_time =
+        sqlx::query_scalar::<_, DateTime<Utc>>("SELECT NOW() - make_interval(secs => $1)")
+            .bind(get_sync_safety_window_secs() as f64)
+            .fetch_one(&state.db)
+            .await?;
+
+    let applications = sqlx::query_as::<_, Application>(
+        r#"
+        SELECT * FROM applications a
+        WHERE a.user_id = $1
+        AND (
+            a.updated_at > $2
+            OR EXISTS (SELECT 1 FROM screenings s WHERE s.application_id = a.id AND s.updated_at > $2)
+            OR EXISTS (SELECT 1 FROM interviews i WHERE i.application_id = a.id AND i.updated_at > $2)
+        )
+        ORDER BY a.updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(&state.db)
+    .await?;
+
+    let deleted = sqlx::query_as::<_, ApplicationTombstone>(
+        "SELECT application_id, deleted_at FROM application_tombstones
+         WHERE user_id = $1 AND deleted_at > $2
+         ORDER BY deleted_at",
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(ApplicationSyncResponse {
+        applications: with_sub_resources(&state.db, applications).await,
+        deleted,
+        server_time,
+    })
+}
+
+/// Upper bound on ids per batch request
+const MAX_BATCH_IDS: usize = 100;
+
+/// The status state machine, so clients only offer valid next statuses
+pub async fn get_status_transitions() -> Json<StatusTransitionsResponse> {
+    Json(StatusTransitionsResponse {
+        initial: ApplicationStatus::Waiting,
+        transitions: ApplicationStatus::ALL
+            .iter()
+            .map(|status| (status.as_str(), status.next_statuses()))
+            .collect(),
+    })
+}
+
+/// Fetches several of the caller's applications at once; ids that don't exist
+/// or belong to someone else are left out rather than failing the request
+pub async fn get_applications_batch(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<BatchApplicationsRequest>,
+) -> Result<Json<Vec<ApplicationResponse>>, AppError> {
+    if payload.ids.len() > MAX_BATCH_IDS {
+        return Err(AppError::BadRequest(
+            format!("At most {} ids can be requested at once", MAX_BATCH_IDS).into(),
+        ));
+    }
+
+    if payload.ids.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+
+    let applications = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE id = ANY($1) AND user_id = $2 ORDER BY created_at DESC",
+    )
+    .bind(&payload.ids)
+    .bind(auth_user.user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(with_sub_resources(&state.db, applications).await))
 }
 
 pub async fn get_application(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<i32>,
-) -> Result<Json<ApplicationResponse>, StatusCode> {
+) -> Result<Json<ApplicationResponse>, AppError> {
     let application = sqlx::query_as::<_, Application>(
         "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
     )
     .bind(id)
     .bind(auth_user.user_id)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|_| StatusCode::NOT_FOUND)?;
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
 
     let mut response = ApplicationResponse::from(application.clone());
 
@@ -185,6 +868,117 @@ pub async fn get_application(
     Ok(Json(response))
 }
 
+/// The history of one of the caller's applications as a list of typed events.
+///
+/// Status changes come from the audit log. Screenings and interviews only keep
+/// `created_at` and `updated_at`, so an upload is dated by when the row was
+/// created and a result by when the row was last changed.
+pub async fn get_application_timeline(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApplicationTimelineResponse>, AppError> {
+    let application = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
+    )
+    .bind(id)
+    .bind(auth_user.user_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let (screening, interview, status_changes) = tokio::try_join!(
+        sqlx::query_as::<_, Screening>("SELECT * FROM screenings WHERE application_id = $1")
+            .bind(id)
+            .fetch_optional(&state.db),
+        sqlx::query_as::<_, Interview>("SELECT * FROM interviews WHERE application_id = $1")
+            .bind(id)
+            .fetch_optional(&state.db),
+        // The INSERT row seeds the first `from_status` and is not itself a change
+        sqlx::query_as::<_, StatusChange>(
+            r#"
+            SELECT at, from_status, to_status
+            FROM (
+                SELECT timestamp AS at,
+                       operation,
+                       LAG((new_data->>'status')::application_status)
+                           OVER (ORDER BY timestamp, id) AS from_status,
+                       (new_data->>'status')::application_status AS to_status
+                FROM audit_log
+                WHERE table_name = 'applications'
+                AND (new_data->>'id')::int = $1
+                AND timestamp IS NOT NULL
+            ) history
+            WHERE operation = 'UPDATE' AND from_status IS DISTINCT FROM to_status
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&state.db),
+    )?;
+
+    let mut events = vec![TimelineEvent {
+        at: application.created_at,
+        kind: TimelineEventKind::Created {
+            status: status_changes
+                .first()
+                .and_then(|change| change.from_status.clone())
+                .unwrap_or_else(|| application.status.clone()),
+        },
+    }];
+
+    events.extend(status_changes.into_iter().map(|change| TimelineEvent {
+        at: change.at,
+        kind: TimelineEventKind::StatusChanged {
+            from: change.from_status,
+            to: change.to_status,
+        },
+    }));
+
+    if let Some(screening) = screening {
+        if screening.file_path.is_some() {
+            events.push(TimelineEvent {
+                at: screening.created_at,
+                kind: TimelineEventKind::ScreeningUploaded,
+            });
+        }
+        if let Some(result) = screening.result {
+            events.push(TimelineEvent {
+                at: screening.updated_at,
+                kind: TimelineEventKind::ScreeningResult {
+                    result,
+                    reason_code: screening.reason_code,
+                },
+            });
+        }
+    }
+
+    if let Some(interview) = interview {
+        if interview.file_path.is_some() {
+            events.push(TimelineEvent {
+                at: interview.created_at,
+                kind: TimelineEventKind::InterviewUploaded,
+            });
+        }
+        if let Some(result) = interview.result {
+            events.push(TimelineEvent {
+                at: interview.updated_at,
+                kind: TimelineEventKind::InterviewResult {
+                    result,
+                    reason_code: interview.reason_code,
+                },
+            });
+        }
+    }
+
+    // Stable, so events sharing a timestamp keep the order they were added in
+    events.sort_by_key(|event| event.at);
+
+    Ok(Json(ApplicationTimelineResponse {
+        application_id: application.id,
+        events,
+    }))
+}
+
 pub async fn create_application(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
@@ -192,21 +986,86 @@ pub async fn create_application(
 ) -> Result<Json<ApplicationResponse>, AppError> {
     payload.validate()?;
 
+    let application = insert_application(&state, auth_user.user_id, &payload).await?;
+
+    Ok(Json(ApplicationResponse::from(application)))
+}
+
+/// A new `waiting` application, applied for today, with the company and job
+/// URL of one of the caller's applications. Screenings and interviews stay
+/// with the original.
+pub async fn clone_application(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApplicationResponse>, AppError> {
+    let source = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
+    )
+    .bind(id)
+    .bind(auth_user.user_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let payload = CreateApplicationRequest {
+        company: source.company,
+        job_url: source.job_url,
+        applied_date: Utc::now().date_naive(),
+        salary_min: None,
+        salary_max: None,
+        currency: None,
+        status: Some(ApplicationStatus::Waiting),
+    };
+
+    let application = insert_application(&state, auth_user.user_id, &payload).await?;
+
+    Ok(Json(ApplicationResponse::from(application)))
+}
+
+/// Inserts an application for `user_id` in their cohort, queuing its webhooks
+/// in the same transaction and announcing it once committed
+async fn insert_application(
+    state: &AppState,
+    user_id: i32,
+    payload: &CreateApplicationRequest,
+) -> Result<Application, AppError> {
+    let mut tx = state.db.begin().await?;
+
     let application = sqlx::query_as::<_, Application>(
         r#"
-        INSERT INTO applications (user_id, company, job_url, applied_date)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO applications (user_id, company, company_normalized, job_url, applied_date, salary_min, salary_max, currency, status, cohort_id)
+        VALUES ($1, $2, $8, $3, $4, $5, $6, $7, $9, (SELECT cohort_id FROM users WHERE id = $1))
         RETURNING *
         "#,
     )
-    .bind(auth_user.user_id)
+    .bind(user_id)
     .bind(&payload.company)
     .bind(&payload.job_url)
     .bind(payload.applied_date)
-    .fetch_one(&state.db)
+    .bind(payload.salary_min)
+    .bind(payload.salary_max)
+    .bind(&payload.currency)
+    .bind(normalize_company(&payload.company))
+    .bind(payload.status.clone().unwrap_or(ApplicationStatus::Waiting))
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(Json(ApplicationResponse::from(application)))
+    webhooks::enqueue(
+        &mut *tx,
+        &WebhookPayload::new(WebhookEventType::ApplicationCreated, &application),
+    )
+    .await?;
+    tx.commit().await?;
+
+    state.events.publish(AppEvent::new(
+        EventType::ApplicationCreated,
+        application.user_id,
+        application.cohort_id,
+        application.id,
+    ));
+
+    Ok(application)
 }
 
 pub async fn update_application(
@@ -214,10 +1073,29 @@ pub async fn update_application(
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<i32>,
     Json(payload): Json<UpdateApplicationRequest>,
-) -> Result<Json<ApplicationResponse>, StatusCode> {
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+) -> Result<Json<ApplicationResponse>, AppError> {
+    payload.validate()?;
+
+    // Enforce the status state machine before applying the update
+    let current = if let Some(ref new_status) = payload.status {
+        let current = sqlx::query_as::<_, Application>(
+            "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
+        )
+        .bind(id)
+        .bind(auth_user.user_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+        if !current.status.can_transition_to(new_status) {
+            return Err(AppError::BadRequest(messages::text(
+                "applications.invalid_status_transition",
+            )));
+        }
+        Some(current)
+    } else {
+        None
+    };
 
     // Build the query dynamically
 
@@ -225,19 +1103,27 @@ pub async fn update_application(
         || payload.job_url.is_some()
         || payload.applied_date.is_some()
         || payload.status.is_some()
+        || payload.salary_min.is_some()
+        || payload.salary_max.is_some()
+        || payload.currency.is_some()
     {
         // For simplicity, let's update the fields that are provided
         let query = r#"
             UPDATE applications 
             SET company = COALESCE($1, company),
+                company_normalized = COALESCE($10, company_normalized),
                 job_url = COALESCE($2, job_url), 
                 applied_date = COALESCE($3, applied_date),
                 status = COALESCE($4, status),
-                updated_at = NOW()
+                salary_min = COALESCE($7, salary_min),
+                salary_max = COALESCE($8, salary_max),
+                currency = COALESCE($9, currency)
             WHERE id = $5 AND user_id = $6
             RETURNING *
         "#;
 
+        let mut tx = state.db.begin().await?;
+
         let application = sqlx::query_as::<_, Application>(query)
             .bind(&payload.company)
             .bind(&payload.job_url)
@@ -245,30 +1131,72 @@ pub async fn update_application(
             .bind(&payload.status)
             .bind(id)
             .bind(auth_user.user_id)
-            .fetch_one(&state.db)
+            .bind(payload.salary_min)
+            .bind(payload.salary_max)
+            .bind(&payload.currency)
+            .bind(payload.company.as_deref().map(normalize_company))
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|_| StatusCode::NOT_FOUND)?;
+            .map_err(|e| match e {
+                // A partial salary update can conflict with the stored range
+                sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23514") => {
+                    AppError::BadRequest(messages::text("validation.salary_range"))
+                }
+                sqlx::Error::RowNotFound => {
+                    AppError::NotFound(messages::text("applications.not_found"))
+                }
+                other => other.into(),
+            })?;
+
+        if let Some(current) = current.filter(|current| {
+            std::mem::discriminant(&current.status) != std::mem::discriminant(&application.status)
+        }) {
+            webhooks::enqueue(
+                &mut *tx,
+                &WebhookPayload::status_changed(&current, application.status.clone()),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        let event = match payload.status {
+            Some(status) => AppEvent::new(
+                EventType::StatusChanged,
+                application.user_id,
+                application.cohort_id,
+                application.id,
+            )
+            .with_status(status),
+            None => AppEvent::new(
+                EventType::ApplicationUpdated,
+                application.user_id,
+                application.cohort_id,
+                application.id,
+            ),
+        };
+        state.events.publish(event);
 
         return Ok(Json(ApplicationResponse::from(application)));
     }
 
-    return Err(StatusCode::BAD_REQUEST);
+    Err(AppError::BadRequest(messages::text(
+        "applications.no_fields_to_update",
+    )))
 }
 
 pub async fn delete_application(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<i32>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let result = sqlx::query("DELETE FROM applications WHERE id = $1 AND user_id = $2")
         .bind(id)
         .bind(auth_user.user_id)
         .execute(&state.db)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::NotFound(messages::text("applications.not_found")));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -279,94 +1207,110 @@ pub async fn upload_screening(
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<i32>,
     mut multipart: Multipart,
-) -> Result<Json<ScreeningResponse>, StatusCode> {
+) -> Result<Json<ScreeningResponse>, AppError> {
     // Check if application exists and belongs to user
-    let _application = sqlx::query_as::<_, Application>(
+    let application = sqlx::query_as::<_, Application>(
         "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
     )
     .bind(id)
     .bind(auth_user.user_id)
     .fetch_one(&state.db)
     .await
-    .map_err(|_| StatusCode::NOT_FOUND)?;
+    .map_err(|_| AppError::NotFound(messages::text("applications.not_found")))?;
 
-    let mut file_data: Option<Vec<u8>> = None;
+    let mut staged: Option<StagedUpload> = None;
     let mut original_filename: Option<String> = None;
     let mut screening_request = UpdateScreeningRequest {
         screening_date: None,
         result: None,
+        reason_code: None,
+        feedback: None,
+        score: None,
     };
 
     // Process multipart fields
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|_| StatusCode::BAD_REQUEST)?
-    {
+    let mut field_count = 0;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        field_count += 1;
         let name = field.name().unwrap_or("").to_string();
 
         match name.as_str() {
             "file" => {
-                let filename = field
-                    .file_name()
-                    .ok_or(StatusCode::BAD_REQUEST)?
-                    .to_string();
-
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-
-                // Validate file security (extension, MIME, magic bytes)
-                validate_file_security(&filename, &data)?;
-
-                file_data = Some(data.to_vec());
+                // Validated (extension, MIME, magic bytes) and staged before
+                // touching the database
+                let (filename, upload) =
+                    stage_file_field(&state, field, ALLOWED_FILE_TYPES).await?;
+                staged = Some(upload);
                 original_filename = Some(filename);
             }
             "screening_date" => {
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-                let date_str =
-                    String::from_utf8(data.to_vec()).map_err(|_| StatusCode::BAD_REQUEST)?;
+                let date_str = read_text_field(field, get_max_text_field_bytes()).await?;
                 screening_request.screening_date =
                     chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok();
             }
             "screening_status" => {
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-                let result_str =
-                    String::from_utf8(data.to_vec()).map_err(|_| StatusCode::BAD_REQUEST)?;
+                let result_str = read_text_field(field, get_max_text_field_bytes()).await?;
                 screening_request.result = match result_str.as_str() {
                     "passed" => Some(crate::models::screening::ScreeningResult::Passed),
                     "failed" => Some(crate::models::screening::ScreeningResult::Failed),
                     _ => None,
                 };
             }
+            "reason_code" => {
+                let code = read_text_field(field, get_max_text_field_bytes()).await?;
+                screening_request.reason_code = parse_reason_code(&state, &code)?;
+            }
+            "feedback" => {
+                screening_request.feedback =
+                    parse_feedback(read_text_field(field, MAX_FEEDBACK_FIELD_BYTES).await?)?;
+            }
+            "score" => {
+                let score = read_text_field(field, get_max_text_field_bytes()).await?;
+                screening_request.score = parse_score(&state, &score)?;
+            }
             _ => {}
         }
     }
 
-    // Start database transaction
-    let mut tx = state
-        .db
-        .begin()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let mut final_file_path: Option<String> = None;
-
-    // Handle file upload if present
-    if let (Some(data), Some(filename)) = (file_data, original_filename) {
-        let extension = validate_file_security(&filename, &data)?;
-        let unique_filename = format!("{}.{}", Uuid::new_v4(), extension);
-        let temp_filename = format!("{}.tmp", unique_filename);
+    if field_count == 0 {
+        return Err(AppError::BadRequest(messages::text("uploads.no_file")));
+    }
 
-        let upload_dir = PathBuf::from(&state.upload_dir);
-        let temp_path = upload_dir.join(&temp_filename);
-        let _final_path = upload_dir.join(&unique_filename);
+    let download_name = match (&original_filename, &staged) {
+        (Some(original), Some(staged)) => Some(download_filename(
+            original,
+            &application.company,
+            screening_request.screening_date,
+            "screening",
+            &staged.extension,
+        )),
+        _ => None,
+    };
 
-        // Write to temporary file first
-        fs::write(&temp_path, data)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Start database transaction
+    let mut tx = state.db.begin().await?;
 
-        final_file_path = Some(unique_filename);
-    }
+    // Lock the application so concurrent uploads replace each other's file in
+    // order, and remember the file this upload replaces
+    sqlx::query("SELECT id FROM applications WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let replaced_file = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT file_path FROM screenings WHERE application_id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .flatten();
+
+    // Store the file under a name no other upload has taken
+    let stored = match staged {
+        Some(staged) => Some(store_staged_upload(&state, staged).await?),
+        None => None,
+    };
+    let final_file_path = stored.as_ref().map(|stored| stored.key.clone());
+    let file_nonce = stored.as_ref().and_then(|stored| stored.nonce.clone());
 
     let screening_result = screening_request.result.clone();
 
@@ -375,14 +1319,21 @@ pub async fn upload_screening(
         // File was uploaded, update everything including file_path
         sqlx::query_as::<_, Screening>(
             r#"
-            INSERT INTO screenings (application_id, file_path, screening_date, result)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO screenings (
+                application_id, file_path, screening_date, result, file_nonce,
+                reason_code, feedback, original_filename, score
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             ON CONFLICT (application_id) 
             DO UPDATE SET 
                 file_path = $2,
+                file_nonce = $5,
+                original_filename = $8,
                 screening_date = COALESCE($3, screenings.screening_date),
                 result = COALESCE($4, screenings.result),
-                updated_at = NOW()
+                reason_code = COALESCE($6, screenings.reason_code),
+                feedback = COALESCE($7, screenings.feedback),
+                score = COALESCE($9, screenings.score)
             RETURNING *
             "#,
         )
@@ -390,61 +1341,106 @@ pub async fn upload_screening(
         .bind(&final_file_path)
         .bind(screening_request.screening_date)
         .bind(screening_request.result)
+        .bind(&file_nonce)
+        .bind(&screening_request.reason_code)
+        .bind(&screening_request.feedback)
+        .bind(&download_name)
+        .bind(screening_request.score)
         .fetch_one(&mut *tx)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .await?
     } else {
         // No file uploaded, only update metadata
         sqlx::query_as::<_, Screening>(
             r#"
-            INSERT INTO screenings (application_id, screening_date, result)
-            VALUES ($1, $2, $3)
+            INSERT INTO screenings (application_id, screening_date, result, reason_code, feedback, score)
+            VALUES ($1, $2, $3, $4, $5, $6)
             ON CONFLICT (application_id) 
             DO UPDATE SET 
                 screening_date = COALESCE($2, screenings.screening_date),
                 result = COALESCE($3, screenings.result),
-                updated_at = NOW()
+                reason_code = COALESCE($4, screenings.reason_code),
+                feedback = COALESCE($5, screenings.feedback),
+                score = COALESCE($6, screenings.score)
             RETURNING *
             "#,
         )
         .bind(id)
         .bind(screening_request.screening_date)
         .bind(screening_request.result)
+        .bind(&screening_request.reason_code)
+        .bind(&screening_request.feedback)
+        .bind(screening_request.score)
         .fetch_one(&mut *tx)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .await?
     };
 
     // Update application status if screening failed
+    let mut new_status = None;
     if let Some(ref result) = screening_result {
-        if matches!(result, crate::models::screening::ScreeningResult::Failed) {
-            sqlx::query("UPDATE applications SET status = 'rejected' WHERE id = $1")
+        let event = match result {
+            crate::models::screening::ScreeningResult::Passed => WebhookEventType::ScreeningPassed,
+            crate::models::screening::ScreeningResult::Failed => WebhookEventType::ScreeningFailed,
+        };
+        webhooks::enqueue(&mut *tx, &WebhookPayload::new(event, &application)).await?;
+
+        if matches!(result, crate::models::screening::ScreeningResult::Failed)
+            && application
+                .status
+                .can_transition_to(&ApplicationStatus::Rejected)
+        {
+            sqlx::query("UPDATE applications SET status = $1 WHERE id = $2")
+                .bind(ApplicationStatus::Rejected)
                 .bind(id)
                 .execute(&mut *tx)
-                .await
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                .await?;
+            new_status = Some(ApplicationStatus::Rejected);
         }
     }
 
-    // Commit transaction
-    tx.commit()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Move temp file to final location after successful commit
-    if let Some(ref unique_filename) = final_file_path {
-        let upload_dir = PathBuf::from(&state.upload_dir);
-        let temp_filename = format!("{}.tmp", unique_filename);
-        let temp_path = upload_dir.join(&temp_filename);
-        let final_path = upload_dir.join(unique_filename);
-
-        if let Err(_) = fs::rename(&temp_path, &final_path).await {
-            // If rename fails, try to clean up temp file
-            let _ = fs::remove_file(&temp_path).await;
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    // A screening booked for a future date, still without a result, moves a
+    // waiting application on to `screening_scheduled`
+    let scheduled = screening_request.screening_date.is_some()
+        && screening.result.is_none()
+        && screening
+            .screening_date
+            .is_some_and(|date| date > Utc::now().date_naive());
+    if scheduled && matches!(application.status, ApplicationStatus::Waiting) {
+        sqlx::query("UPDATE applications SET status = $1 WHERE id = $2")
+            .bind(ApplicationStatus::ScreeningScheduled)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        new_status = Some(ApplicationStatus::ScreeningScheduled);
+    }
+
+    if let Some(status) = new_status.filter(|status| {
+        std::mem::discriminant(status) != std::mem::discriminant(&application.status)
+    }) {
+        webhooks::enqueue(
+            &mut *tx,
+            &WebhookPayload::status_changed(&application, status),
+        )
+        .await?;
+    }
+
+    // Commit transaction; on any earlier error `stored` removes the file again
+    tx.commit().await?;
+    if let Some(stored) = stored {
+        stored.keep();
+    }
+
+    // The replaced recording is no longer referenced by any row
+    if let (Some(_), Some(replaced)) = (&final_file_path, &replaced_file) {
+        discard_stored_file(&state, replaced).await;
     }
 
+    state.events.publish(AppEvent::new(
+        EventType::ScreeningUpdated,
+        application.user_id,
+        application.cohort_id,
+        id,
+    ));
+
     Ok(Json(ScreeningResponse::from(screening)))
 }
 
@@ -453,93 +1449,110 @@ pub async fn upload_interview(
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<i32>,
     mut multipart: Multipart,
-) -> Result<Json<InterviewResponse>, StatusCode> {
+) -> Result<Json<InterviewResponse>, AppError> {
     // Check if application exists and belongs to user
-    let _application = sqlx::query_as::<_, Application>(
+    let application = sqlx::query_as::<_, Application>(
         "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
     )
     .bind(id)
     .bind(auth_user.user_id)
     .fetch_one(&state.db)
     .await
-    .map_err(|_| StatusCode::NOT_FOUND)?;
+    .map_err(|_| AppError::NotFound(messages::text("applications.not_found")))?;
 
-    let mut file_data: Option<Vec<u8>> = None;
+    let mut staged: Option<StagedUpload> = None;
     let mut original_filename: Option<String> = None;
     let mut interview_request = UpdateInterviewRequest {
         interview_date: None,
         result: None,
+        reason_code: None,
+        feedback: None,
+        score: None,
     };
 
     // Process multipart fields
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|_| StatusCode::BAD_REQUEST)?
-    {
+    let mut field_count = 0;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        field_count += 1;
         let name = field.name().unwrap_or("").to_string();
 
         match name.as_str() {
             "file" => {
-                let filename = field
-                    .file_name()
-                    .ok_or(StatusCode::BAD_REQUEST)?
-                    .to_string();
-
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-
-                // Validate file security (extension, MIME, magic bytes)
-                validate_file_security(&filename, &data)?;
-
-                file_data = Some(data.to_vec());
+                // Validated (extension, MIME, magic bytes) and staged before
+                // touching the database
+                let (filename, upload) =
+                    stage_file_field(&state, field, ALLOWED_FILE_TYPES).await?;
+                staged = Some(upload);
                 original_filename = Some(filename);
             }
             "interview_date" => {
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-                let date_str =
-                    String::from_utf8(data.to_vec()).map_err(|_| StatusCode::BAD_REQUEST)?;
+                let date_str = read_text_field(field, get_max_text_field_bytes()).await?;
                 interview_request.interview_date =
                     chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok();
             }
             "interview_status" => {
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-                let result_str =
-                    String::from_utf8(data.to_vec()).map_err(|_| StatusCode::BAD_REQUEST)?;
+                let result_str = read_text_field(field, get_max_text_field_bytes()).await?;
                 interview_request.result = match result_str.as_str() {
                     "passed" => Some(crate::models::interview::InterviewResult::Passed),
                     "failed" => Some(crate::models::interview::InterviewResult::Failed),
                     _ => None,
                 };
             }
+            "reason_code" => {
+                let code = read_text_field(field, get_max_text_field_bytes()).await?;
+                interview_request.reason_code = parse_reason_code(&state, &code)?;
+            }
+            "feedback" => {
+                interview_request.feedback =
+                    parse_feedback(read_text_field(field, MAX_FEEDBACK_FIELD_BYTES).await?)?;
+            }
+            "score" => {
+                let score = read_text_field(field, get_max_text_field_bytes()).await?;
+                interview_request.score = parse_score(&state, &score)?;
+            }
             _ => {}
         }
     }
 
-    // Start database transaction
-    let mut tx = state
-        .db
-        .begin()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let mut final_file_path: Option<String> = None;
-
-    // Handle file upload if present
-    if let (Some(data), Some(filename)) = (file_data, original_filename) {
-        let extension = validate_file_security(&filename, &data)?;
-        let unique_filename = format!("{}.{}", Uuid::new_v4(), extension);
-        let temp_filename = format!("{}.tmp", unique_filename);
+    if field_count == 0 {
+        return Err(AppError::BadRequest(messages::text("uploads.no_file")));
+    }
 
-        let upload_dir = PathBuf::from(&state.upload_dir);
-        let temp_path = upload_dir.join(&temp_filename);
+    let download_name = match (&original_filename, &staged) {
+        (Some(original), Some(staged)) => Some(download_filename(
+            original,
+            &application.company,
+            interview_request.interview_date,
+            "interview",
+            &staged.extension,
+        )),
+        _ => None,
+    };
 
-        // Write to temporary file first
-        fs::write(&temp_path, data)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Start database transaction
+    let mut tx = state.db.begin().await?;
 
-        final_file_path = Some(unique_filename);
-    }
+    // Lock the application so concurrent uploads replace each other's file in
+    // order, and remember the file this upload replaces
+    sqlx::query("SELECT id FROM applications WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let replaced_file = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT file_path FROM interviews WHERE application_id = $1",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .flatten();
+
+    // Store the file under a name no other upload has taken
+    let stored = match staged {
+        Some(staged) => Some(store_staged_upload(&state, staged).await?),
+        None => None,
+    };
+    let final_file_path = stored.as_ref().map(|stored| stored.key.clone());
+    let file_nonce = stored.as_ref().and_then(|stored| stored.nonce.clone());
 
     let interview_result = interview_request.result.clone();
 
@@ -548,14 +1561,21 @@ pub async fn upload_interview(
         // File was uploaded, update everything including file_path
         sqlx::query_as::<_, Interview>(
             r#"
-            INSERT INTO interviews (application_id, file_path, interview_date, result)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO interviews (
+                application_id, file_path, interview_date, result, file_nonce,
+                reason_code, feedback, original_filename, score
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             ON CONFLICT (application_id) 
             DO UPDATE SET 
                 file_path = $2,
+                file_nonce = $5,
+                original_filename = $8,
                 interview_date = COALESCE($3, interviews.interview_date),
                 result = COALESCE($4, interviews.result),
-                updated_at = NOW()
+                reason_code = COALESCE($6, interviews.reason_code),
+                feedback = COALESCE($7, interviews.feedback),
+                score = COALESCE($9, interviews.score)
             RETURNING *
             "#,
         )
@@ -563,74 +1583,274 @@ pub async fn upload_interview(
         .bind(&final_file_path)
         .bind(interview_request.interview_date)
         .bind(interview_request.result)
+        .bind(&file_nonce)
+        .bind(&interview_request.reason_code)
+        .bind(&interview_request.feedback)
+        .bind(&download_name)
+        .bind(interview_request.score)
         .fetch_one(&mut *tx)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .await?
     } else {
         // No file uploaded, only update metadata
         sqlx::query_as::<_, Interview>(
             r#"
-            INSERT INTO interviews (application_id, interview_date, result)
-            VALUES ($1, $2, $3)
+            INSERT INTO interviews (application_id, interview_date, result, reason_code, feedback, score)
+            VALUES ($1, $2, $3, $4, $5, $6)
             ON CONFLICT (application_id) 
             DO UPDATE SET 
                 interview_date = COALESCE($2, interviews.interview_date),
                 result = COALESCE($3, interviews.result),
-                updated_at = NOW()
+                reason_code = COALESCE($4, interviews.reason_code),
+                feedback = COALESCE($5, interviews.feedback),
+                score = COALESCE($6, interviews.score)
             RETURNING *
             "#,
         )
         .bind(id)
         .bind(interview_request.interview_date)
         .bind(interview_request.result)
+        .bind(&interview_request.reason_code)
+        .bind(&interview_request.feedback)
+        .bind(interview_request.score)
         .fetch_one(&mut *tx)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .await?
     };
 
     // Update application status based on interview result
     if let Some(ref result) = interview_result {
-        let new_status = match result {
-            crate::models::interview::InterviewResult::Passed => "next_stage",
-            crate::models::interview::InterviewResult::Failed => "rejected",
+        let (event, new_status) = match result {
+            crate::models::interview::InterviewResult::Passed => (
+                WebhookEventType::InterviewPassed,
+                ApplicationStatus::NextStage,
+            ),
+            crate::models::interview::InterviewResult::Failed => (
+                WebhookEventType::InterviewFailed,
+                ApplicationStatus::Rejected,
+            ),
         };
+        webhooks::enqueue(&mut *tx, &WebhookPayload::new(event, &application)).await?;
 
-        sqlx::query("UPDATE applications SET status = $1 WHERE id = $2")
-            .bind(new_status)
-            .bind(id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        // Don't downgrade applications that already reached an offer
+        if application.status.can_transition_to(&new_status) {
+            sqlx::query("UPDATE applications SET status = $1 WHERE id = $2")
+                .bind(&new_status)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+            if std::mem::discriminant(&new_status) != std::mem::discriminant(&application.status) {
+                webhooks::enqueue(
+                    &mut *tx,
+                    &WebhookPayload::status_changed(&application, new_status),
+                )
+                .await?;
+            }
+        }
     }
 
-    // Commit transaction
-    tx.commit()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Move temp file to final location after successful commit
-    if let Some(ref unique_filename) = final_file_path {
-        let upload_dir = PathBuf::from(&state.upload_dir);
-        let temp_filename = format!("{}.tmp", unique_filename);
-        let temp_path = upload_dir.join(&temp_filename);
-        let final_path = upload_dir.join(unique_filename);
-
-        if let Err(_) = fs::rename(&temp_path, &final_path).await {
-            // If rename fails, try to clean up temp file
-            let _ = fs::remove_file(&temp_path).await;
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+    // Commit transaction; on any earlier error `stored` removes the file again
+    tx.commit().await?;
+    if let Some(stored) = stored {
+        stored.keep();
     }
 
+    // The replaced recording is no longer referenced by any row
+    if let (Some(_), Some(replaced)) = (&final_file_path, &replaced_file) {
+        discard_stored_file(&state, replaced).await;
+    }
+
+    state.events.publish(AppEvent::new(
+        EventType::InterviewUpdated,
+        application.user_id,
+        application.cohort_id,
+        id,
+    ));
+
     Ok(Json(InterviewResponse::from(interview)))
 }
 
+/// Attaches a document (pdf, docx, png) to an application
+pub async fn upload_document(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Json<DocumentResponse>, AppError> {
+    let application = sqlx::query_as::<_, Application>(
+        "SELECT * FROM applications WHERE id = $1 AND user_id = $2",
+    )
+    .bind(id)
+    .bind(auth_user.user_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let mut upload: Option<(String, StagedUpload)> = None;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() == Some("file") {
+            upload = Some(stage_file_field(&state, field, ALLOWED_DOCUMENT_TYPES).await?);
+        }
+    }
+
+    let (original_filename, staged) =
+        upload.ok_or_else(|| AppError::BadRequest(messages::text("uploads.no_file")))?;
+    let size_bytes = staged.size_bytes as i64;
+
+    let stored = store_staged_upload(&state, staged).await?;
+
+    let document = sqlx::query_as::<_, Document>(
+        r#"
+        INSERT INTO documents (application_id, file_path, file_nonce, original_filename, size_bytes)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(&stored.key)
+    .bind(&stored.nonce)
+    .bind(&original_filename)
+    .bind(size_bytes)
+    .fetch_one(&state.db)
+    .await?;
+    stored.keep();
+
+    state.events.publish(AppEvent::new(
+        EventType::DocumentUploaded,
+        application.user_id,
+        application.cohort_id,
+        id,
+    ));
+
+    Ok(Json(DocumentResponse::from(document)))
+}
+
+pub async fn get_documents(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<DocumentResponse>>, AppError> {
+    sqlx::query(
+        r#"
+        SELECT id FROM applications
+        WHERE id = $1 AND (user_id = $2 OR ($3 AND ($4::int IS NULL OR cohort_id = $4)))
+        "#,
+    )
+    .bind(id)
+    .bind(auth_user.user_id)
+    .bind(auth_user.is_admin())
+    .bind(auth_user.cohort_scope())
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let documents = sqlx::query_as::<_, Document>(
+        "SELECT * FROM documents WHERE application_id = $1 ORDER BY created_at",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        documents.into_iter().map(DocumentResponse::from).collect(),
+    ))
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadValidationResponse {
+    pub accepted: bool,
+    /// Machine-readable rejection code, matching the `error` field of a real upload
+    pub error: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Dry run of the upload checks.
+///
+/// Takes a multipart body with the start of the file as `file` (the first few KB
+/// are enough for magic-byte detection) and optionally the full size in bytes as
+/// `size`. Nothing is stored.
+pub async fn validate_upload(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Json<UploadValidationResponse>, AppError> {
+    sqlx::query("SELECT id FROM applications WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(auth_user.user_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let mut sample: Option<(String, Vec<u8>)> = None;
+    let mut declared_size: Option<usize> = None;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        match field.name().unwrap_or("") {
+            "file" => {
+                let filename = field
+                    .file_name()
+                    .filter(|name| !name.trim().is_empty())
+                    .ok_or_else(|| {
+                        AppError::BadRequest(messages::text("uploads.filename_missing"))
+                    })?
+                    .to_string();
+
+                let mut data = Vec::new();
+                while data.len() < VALIDATION_SAMPLE_BYTES {
+                    match field.chunk().await.map_err(multipart_error)? {
+                        Some(chunk) => data.extend_from_slice(&chunk),
+                        None => break,
+                    }
+                }
+                data.truncate(VALIDATION_SAMPLE_BYTES);
+                sample = Some((filename, data));
+            }
+            "size" => {
+                let size = read_text_field(field, get_max_text_field_bytes()).await?;
+                declared_size = Some(size.trim().parse().map_err(|_| {
+                    AppError::BadRequest("Field 'size' must be a number of bytes".into())
+                })?);
+            }
+            _ => {}
+        }
+    }
+
+    let (filename, data) =
+        sample.ok_or_else(|| AppError::BadRequest(messages::text("uploads.no_file")))?;
+    if data.is_empty() {
+        return Err(AppError::BadRequest(messages::text("uploads.empty_file")));
+    }
+
+    let result = validate_file_size(declared_size.unwrap_or(data.len()))
+        .and_then(|_| validate_file_content(&filename, &data, ALLOWED_FILE_TYPES));
+
+    let rejection = match result {
+        Ok(_) => None,
+        Err(AppError::PayloadTooLarge(reason)) => Some(("PAYLOAD_TOO_LARGE", reason)),
+        Err(AppError::UnsupportedMediaType(reason)) => Some(("UNSUPPORTED_MEDIA_TYPE", reason)),
+        Err(AppError::BadRequest(reason)) => Some(("BAD_REQUEST", reason)),
+        Err(other) => return Err(other),
+    };
+
+    Ok(Json(match rejection {
+        None => UploadValidationResponse {
+            accepted: true,
+            error: None,
+            reason: None,
+        },
+        Some((code, reason)) => UploadValidationResponse {
+            accepted: false,
+            error: Some(code.to_string()),
+            reason: Some(reason.to_string()),
+        },
+    }))
+}
+
 pub async fn get_user_activity(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<Vec<crate::services::activity::ActivityData>>, StatusCode> {
+) -> Result<Json<Vec<crate::services::activity::ActivityData>>, AppError> {
     use crate::services::activity::{ActivityError, ActivityService};
-    use crate::utils::logger::LOGGER;
 
     LOGGER.log_request(
         "GET",
@@ -667,8 +1887,144 @@ pub async fn get_user_activity(
                 serde_json::Value::String("database".to_string()),
             );
             LOGGER.log_error(&msg, context);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "activity.fetch_failed",
+            )))
+        }
+        Err(ActivityError::PermissionDenied) => {
+            Err(AppError::Forbidden(messages::text("activity.not_allowed")))
         }
-        Err(ActivityError::PermissionDenied) => Err(StatusCode::FORBIDDEN),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::storage::LocalFileStore;
+    use std::collections::HashSet;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("otchet-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_temp_files_never_collide() {
+        let dir = scratch_dir();
+
+        let tasks: Vec<_> = (0..64u8)
+            .map(|i| {
+                let dir = dir.clone();
+                tokio::spawn(async move {
+                    let (path, mut file) = create_temp_file(&dir).await.unwrap();
+                    file.write_all(&[i; 1024]).await.unwrap();
+                    file.flush().await.unwrap();
+                    (path, i)
+                })
+            })
+            .collect();
+
+        let mut paths = HashSet::new();
+        for task in tasks {
+            let (path, i) = task.await.unwrap();
+            // Nobody else wrote into this upload's temp file
+            assert_eq!(std::fs::read(&path).unwrap(), vec![i; 1024]);
+            assert!(paths.insert(path));
+        }
+        assert_eq!(paths.len(), 64);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_stores_under_one_key_keep_the_first_file() {
+        let dir = scratch_dir();
+        let store = Arc::new(LocalFileStore::new(&dir));
+
+        let tasks: Vec<_> = (0..16u8)
+            .map(|i| {
+                let dir = dir.clone();
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let (path, mut file) = create_temp_file(&dir).await.unwrap();
+                    file.write_all(&[i; 1024]).await.unwrap();
+                    file.flush().await.unwrap();
+                    drop(file);
+                    let outcome = store.put_file("same.pdf", &path).await;
+                    (i, path, outcome)
+                })
+            })
+            .collect();
+
+        let mut winners = Vec::new();
+        for task in tasks {
+            let (i, path, outcome) = task.await.unwrap();
+            match outcome {
+                Ok(()) => winners.push(i),
+                Err(StorageError::AlreadyExists) => {
+                    // The losing upload is still staged, for the caller to retry or drop
+                    assert!(path.exists());
+                }
+                Err(e) => panic!("unexpected storage error: {}", e),
+            }
+        }
+
+        assert_eq!(winners.len(), 1);
+        assert_eq!(store.get("same.pdf").await.unwrap(), vec![winners[0]; 1024]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    const PDF_ONLY: &[(&str, &[&str])] = &[("pdf", &["application/pdf"])];
+
+    fn pdf_bytes(len: usize) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n".to_vec();
+        data.resize(len, b'x');
+        data
+    }
+
+    /// Feeds `data` in `chunk_size` pieces and returns the extension with the
+    /// number of times validation ran
+    fn validate_streamed(data: &[u8], chunk_size: usize) -> (Result<String, AppError>, usize) {
+        let runs = std::cell::Cell::new(0);
+        let mut validator = SampleValidator::new(|sample: &[u8]| {
+            runs.set(runs.get() + 1);
+            validate_file_content("report.pdf", sample, PDF_ONLY)
+        });
+        let outcome = data
+            .chunks(chunk_size)
+            .try_for_each(|chunk| validator.feed(chunk))
+            .and_then(|()| validator.finish());
+        (outcome, runs.get())
+    }
+
+    #[test]
+    fn large_upload_is_validated_once() {
+        let (extension, runs) = validate_streamed(&pdf_bytes(4 * VALIDATION_SAMPLE_BYTES), 8192);
+        assert_eq!(extension.unwrap(), "pdf");
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn upload_filling_the_sample_exactly_is_validated_once() {
+        let (extension, runs) = validate_streamed(&pdf_bytes(VALIDATION_SAMPLE_BYTES), 1000);
+        assert_eq!(extension.unwrap(), "pdf");
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn small_upload_is_validated_once_at_the_end() {
+        let (extension, runs) = validate_streamed(&pdf_bytes(2048), 512);
+        assert_eq!(extension.unwrap(), "pdf");
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn rejected_upload_is_validated_once() {
+        let data = vec![b'x'; 2 * VALIDATION_SAMPLE_BYTES];
+        let (extension, runs) = validate_streamed(&data, 8192);
+        assert!(matches!(extension, Err(AppError::UnsupportedMediaType(_))));
+        assert_eq!(runs, 1);
     }
 }
@@ -1,30 +1,37 @@
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use axum::{
     extract::{Extension, State},
+    http::StatusCode,
     response::Json,
 };
 use bcrypt::verify;
 use password_hash::{rand_core::OsRng, SaltString};
+use std::collections::HashMap;
 use std::env;
+use std::sync::OnceLock;
 use validator::Validate;
 
 use crate::{
-    middleware::auth::AuthUser,
-    models::user::{CreateUserRequest, LoginRequest, LoginResponse, User, UserResponse, UserRole},
-    utils::{errors::AppError, jwt::create_jwt},
+    handlers::cohorts::ensure_cohort_exists,
+    middleware::auth::{revoke_sessions, AuthUser},
+    models::user::{
+        ChangePasswordRequest, CreateUserRequest, LoginRequest, LoginResponse, RegisterRequest,
+        User, UserResponse, UserRole,
+    },
+    utils::{errors::AppError, jwt::create_jwt, logger::LOGGER, messages},
     AppState,
 };
 
 fn validate_password_length(password: &str) -> Result<(), AppError> {
     if password.len() < 8 {
-        return Err(AppError::BadRequest(
-            "Password must be at least 8 characters long".to_string(),
-        ));
+        return Err(AppError::BadRequest(messages::text(
+            "auth.password_too_short",
+        )));
     }
     if password.len() > 72 {
-        return Err(AppError::BadRequest(
-            "Password too long (max 72 characters for compatibility)".to_string(),
-        ));
+        return Err(AppError::BadRequest(messages::text(
+            "auth.password_too_long",
+        )));
     }
     Ok(())
 }
@@ -37,10 +44,50 @@ fn hash_password_argon2(password: &str) -> Result<String, AppError> {
 
     argon2
         .hash_password(password.as_bytes(), &salt)
-        .map_err(|_| AppError::InternalServerError("Failed to hash password".to_string()))?
+        .map_err(|_| AppError::InternalServerError("Failed to hash password".into()))?
         .to_string()
         .parse()
-        .map_err(|_| AppError::InternalServerError("Failed to format password hash".to_string()))
+        .map_err(|_| AppError::InternalServerError("Failed to format password hash".into()))
+}
+
+/// Argon2 hash checked when the login email is unknown, so both paths cost the same
+fn dummy_password_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    DUMMY_HASH.get_or_init(|| {
+        hash_password_argon2("dummy-password-for-timing")
+            .expect("hashing a fixed password cannot fail")
+    })
+}
+
+fn email_taken() -> AppError {
+    let mut errors = HashMap::new();
+    errors.insert(
+        "email".to_string(),
+        vec![messages::text("auth.email_taken")],
+    );
+    AppError::ValidationError(errors)
+}
+
+/// Friendly error for an existing email. The insert still maps the unique
+/// violation to the same error, for signups racing each other.
+async fn ensure_email_available(db: &sqlx::PgPool, email: &str) -> Result<(), AppError> {
+    let taken =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(email)
+            .fetch_one(db)
+            .await?;
+
+    if taken {
+        return Err(email_taken());
+    }
+    Ok(())
+}
+
+fn insert_user_error(error: sqlx::Error) -> AppError {
+    match &error {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => email_taken(),
+        _ => AppError::from(error),
+    }
 }
 
 async fn verify_password_and_rehash(
@@ -61,7 +108,7 @@ async fn verify_password_and_rehash(
 
     // Fallback to bcrypt
     let is_valid_bcrypt = verify(password, stored_hash)
-        .map_err(|_| AppError::InternalServerError("Failed to verify password".to_string()))?;
+        .map_err(|_| AppError::InternalServerError("Failed to verify password".into()))?;
 
     if is_valid_bcrypt {
         // Rehash with Argon2 and update in database
@@ -79,15 +126,16 @@ async fn verify_password_and_rehash(
 
 pub async fn register(
     State(state): State<AppState>,
-    Json(payload): Json<CreateUserRequest>,
+    Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<UserResponse>, AppError> {
     payload.validate()?;
+    ensure_email_available(&state.db, &payload.email).await?;
 
     let password_hash = hash_password_argon2(&payload.password)?;
 
     // Check admin code to determine role from environment variable
     let admin_code = env::var("ADMIN_CODE").map_err(|_| {
-        AppError::InternalServerError("ADMIN_CODE environment variable not set".to_string())
+        AppError::InternalServerError("ADMIN_CODE environment variable not set".into())
     })?;
 
     let role = if let Some(code) = &payload.admin_code {
@@ -95,7 +143,9 @@ pub async fn register(
             if code == &admin_code {
                 UserRole::Admin
             } else {
-                return Err(AppError::BadRequest("Invalid admin code".to_string()));
+                return Err(AppError::BadRequest(messages::text(
+                    "auth.invalid_admin_code",
+                )));
             }
         } else {
             UserRole::Student
@@ -117,7 +167,8 @@ pub async fn register(
     .bind(&payload.last_name)
     .bind(&role)
     .fetch_one(&state.db)
-    .await?;
+    .await
+    .map_err(insert_user_error)?;
 
     Ok(Json(UserResponse::from(user)))
 }
@@ -129,21 +180,29 @@ pub async fn register_admin(
 ) -> Result<Json<UserResponse>, AppError> {
     // Only existing admins can create new admins
     if !auth_user.is_admin() {
-        return Err(AppError::Forbidden(
-            "Only admins can create new admins".to_string(),
-        ));
+        return Err(AppError::Forbidden(messages::text(
+            "auth.admin_only_register",
+        )));
     }
 
     payload.validate()?;
+    let role = admin_account_role(payload.role)?;
+    ensure_email_available(&state.db, &payload.email).await?;
 
     let password_hash = hash_password_argon2(&payload.password)?;
 
-    let role = payload.role.unwrap_or(UserRole::Admin);
+    // Cohort admins can only create users inside their own cohort
+    let cohort_id = if auth_user.is_super_admin() {
+        payload.cohort_id
+    } else {
+        auth_user.cohort_id
+    };
+    ensure_cohort_exists(&state.db, cohort_id).await?;
 
     let user = sqlx::query_as::<_, User>(
         r#"
-        INSERT INTO users (email, password_hash, first_name, last_name, role)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO users (email, password_hash, first_name, last_name, role, cohort_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING *
         "#,
     )
@@ -151,45 +210,230 @@ pub async fn register_admin(
     .bind(&password_hash)
     .bind(&payload.first_name)
     .bind(&payload.last_name)
-    .bind(&role)
+    .bind(role)
+    .bind(cohort_id)
     .fetch_one(&state.db)
-    .await?;
+    .await
+    .map_err(insert_user_error)?;
 
     Ok(Json(UserResponse::from(user)))
 }
 
-pub async fn login(
-    State(state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, AppError> {
-    payload.validate()?;
+/// The role `register_admin` creates an account with. That endpoint only ever
+/// creates admins; students sign up through `register`.
+fn admin_account_role(requested: Option<UserRole>) -> Result<UserRole, AppError> {
+    match requested {
+        None | Some(UserRole::Admin) => Ok(UserRole::Admin),
+        Some(UserRole::Student) => {
+            let mut errors = HashMap::new();
+            errors.insert(
+                "role".to_string(),
+                vec![messages::text("auth.admin_register_role")],
+            );
+            Err(AppError::ValidationError(errors))
+        }
+    }
+}
 
+/// The user with this email, if `password` is theirs
+async fn authenticate(state: &AppState, email: &str, password: &str) -> Result<User, AppError> {
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&payload.email)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|_| AppError::Unauthorized("Invalid email or password".to_string()))?;
+        .bind(email)
+        .fetch_optional(&state.db)
+        .await?;
+
+    let Some(user) = user else {
+        // Burn the same Argon2 work as a real check so timing doesn't reveal unknown emails
+        if let Ok(parsed_hash) = PasswordHash::new(dummy_password_hash()) {
+            let _ = Argon2::default().verify_password(password.as_bytes(), &parsed_hash);
+        }
+        return Err(AppError::Unauthorized(messages::text(
+            "auth.invalid_credentials",
+        )));
+    };
 
     let is_valid =
-        verify_password_and_rehash(&payload.password, &user.password_hash, user.id, &state.db)
-            .await?;
+        verify_password_and_rehash(password, &user.password_hash, user.id, &state.db).await?;
 
     if !is_valid {
-        return Err(AppError::Unauthorized(
-            "Invalid email or password".to_string(),
-        ));
+        return Err(AppError::Unauthorized(messages::text(
+            "auth.invalid_credentials",
+        )));
     }
 
+    Ok(user)
+}
+
+fn login_response(state: &AppState, user: User) -> Result<LoginResponse, AppError> {
     let role_str = match user.role {
         UserRole::Student => "student",
         UserRole::Admin => "admin",
     };
 
-    let token = create_jwt(user.id, role_str, &state.jwt_secret)
-        .map_err(|_| AppError::InternalServerError("Failed to create token".to_string()))?;
+    let token = create_jwt(
+        user.id,
+        role_str,
+        user.cohort_id,
+        user.token_version,
+        &state.jwt_keys,
+    )
+    .map_err(|_| AppError::InternalServerError("Failed to create token".into()))?;
 
-    Ok(Json(LoginResponse {
+    Ok(LoginResponse {
         token,
         user: UserResponse::from(user),
+    })
+}
+
+pub async fn login(
+    State(state): State<AppState>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    payload.validate()?;
+
+    let user = authenticate(&state, &payload.email, &payload.password).await?;
+
+    // Flagged accounts get no token until the password is changed
+    if user.password_reset_required {
+        LOGGER.log_business_event(
+            "login_password_reset_required",
+            Some(user.id),
+            HashMap::new(),
+        );
+        return Err(AppError::PasswordResetRequired(messages::text(
+            "auth.password_reset_required",
+        )));
+    }
+
+    Ok(Json(login_response(&state, user)?))
+}
+
+/// Replaces the password, clears any forced reset and signs out every other
+/// session. Answers like `login`, with a token issued for the new password.
+pub async fn change_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    payload.validate()?;
+
+    let user = authenticate(&state, &payload.email, &payload.current_password).await?;
+    if payload.new_password == payload.current_password {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "new_password".to_string(),
+            vec![messages::text("auth.password_unchanged")],
+        );
+        return Err(AppError::ValidationError(errors));
+    }
+    let password_hash = hash_password_argon2(&payload.new_password)?;
+
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET password_hash = $1, password_reset_required = FALSE, token_version = token_version + 1
+        WHERE id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(&password_hash)
+    .bind(user.id)
+    .fetch_one(&state.db)
+    .await?;
+    state.token_versions.invalidate(user.id);
+
+    LOGGER.log_business_event("password_changed", Some(user.id), HashMap::new());
+
+    Ok(Json(login_response(&state, user)?))
+}
+
+/// Logs the caller out everywhere by revoking every token issued to them,
+/// including the one used for this request
+pub async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<StatusCode, AppError> {
+    revoke_sessions(&state, auth_user.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    LOGGER.log_business_event(
+        "sessions_revoked",
+        Some(auth_user.user_id),
+        [(
+            "user_id".to_string(),
+            serde_json::Value::Number(auth_user.user_id.into()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The caller's own account
+pub async fn me(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(auth_user.user_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    Ok(Json(UserResponse {
+        impersonated_by: auth_user.impersonated_by,
+        ..UserResponse::from(user)
     }))
 }
+
+/// Marks the first-run flow as done for the caller; repeating it is a no-op
+pub async fn complete_onboarding(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = sqlx::query_as::<_, User>(
+        "UPDATE users SET onboarding_completed = TRUE WHERE id = $1 RETURNING *",
+    )
+    .bind(auth_user.user_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    LOGGER.log_business_event(
+        "onboarding_completed",
+        Some(auth_user.user_id),
+        [(
+            "user_id".to_string(),
+            serde_json::Value::Number(auth_user.user_id.into()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(UserResponse::from(user)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_registration_creates_admins() {
+        assert!(matches!(admin_account_role(None), Ok(UserRole::Admin)));
+        assert!(matches!(
+            admin_account_role(Some(UserRole::Admin)),
+            Ok(UserRole::Admin)
+        ));
+    }
+
+    #[test]
+    fn admin_registration_refuses_student_role() {
+        match admin_account_role(Some(UserRole::Student)) {
+            Err(AppError::ValidationError(errors)) => assert!(errors.contains_key("role")),
+            other => panic!("expected a role validation error, got {:?}", other),
+        }
+    }
+}
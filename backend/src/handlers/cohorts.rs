@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Extension, Path, State},
+    response::Json,
+};
+use validator::Validate;
+
+use crate::{
+    middleware::auth::AuthUser,
+    models::{
+        cohort::{AssignCohortRequest, Cohort, CreateCohortRequest},
+        user::{User, UserResponse},
+    },
+    utils::{errors::AppError, messages},
+    AppState,
+};
+
+/// `None` means no cohort and always passes
+pub(crate) async fn ensure_cohort_exists(
+    db: &sqlx::PgPool,
+    cohort_id: Option<i32>,
+) -> Result<(), AppError> {
+    if let Some(cohort_id) = cohort_id {
+        let exists =
+            sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM cohorts WHERE id = $1)")
+                .bind(cohort_id)
+                .fetch_one(db)
+                .await?;
+
+        if !exists {
+            return Err(AppError::NotFound(messages::text("cohorts.unknown")));
+        }
+    }
+    Ok(())
+}
+
+pub async fn list_cohorts(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<Cohort>>, AppError> {
+    if !auth_user.is_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "cohorts.admin_only_list",
+        )));
+    }
+
+    let cohorts = sqlx::query_as::<_, Cohort>(
+        "SELECT * FROM cohorts WHERE $1::int IS NULL OR id = $1 ORDER BY name",
+    )
+    .bind(auth_user.cohort_scope())
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(cohorts))
+}
+
+pub async fn create_cohort(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateCohortRequest>,
+) -> Result<Json<Cohort>, AppError> {
+    if !auth_user.is_super_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "cohorts.super_admin_only_create",
+        )));
+    }
+
+    payload.validate()?;
+
+    let cohort = sqlx::query_as::<_, Cohort>("INSERT INTO cohorts (name) VALUES ($1) RETURNING *")
+        .bind(payload.name.trim())
+        .fetch_one(&state.db)
+        .await?;
+
+    Ok(Json(cohort))
+}
+
+pub async fn assign_user_cohort(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(user_id): Path<i32>,
+    Json(payload): Json<AssignCohortRequest>,
+) -> Result<Json<UserResponse>, AppError> {
+    if !auth_user.is_super_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "cohorts.super_admin_only_assign",
+        )));
+    }
+
+    ensure_cohort_exists(&state.db, payload.cohort_id).await?;
+
+    let mut tx = state.db.begin().await?;
+
+    // Cohort scope travels in the token, so moving a user revokes their sessions
+    let user = sqlx::query_as::<_, User>(
+        "UPDATE users
+         SET cohort_id = $1,
+             token_version = token_version
+                 + CASE WHEN cohort_id IS DISTINCT FROM $1 THEN 1 ELSE 0 END
+         WHERE id = $2
+         RETURNING *",
+    )
+    .bind(payload.cohort_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+
+    // Keep the denormalized cohort on the user's applications in sync
+    sqlx::query("UPDATE applications SET cohort_id = $1 WHERE user_id = $2")
+        .bind(payload.cohort_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    state.token_versions.invalidate(user_id);
+
+    Ok(Json(UserResponse::from(user)))
+}
@@ -0,0 +1,161 @@
+use axum::{
+    extract::{Extension, Query, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    middleware::auth::AuthUser,
+    utils::{company::normalize_company, database::escape_like, errors::AppError},
+    AppState,
+};
+
+const MAX_SUGGESTIONS: i64 = 10;
+
+/// Minimum `pg_trgm` similarity for a non-prefix match to be suggested
+const SIMILARITY_THRESHOLD: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestScope {
+    /// Companies from the caller's own applications
+    #[default]
+    Own,
+    /// Companies from every application in the caller's cohort
+    All,
+}
+
+/// Which applications a suggestion query reads
+#[derive(Debug, PartialEq)]
+struct SuggestFilter {
+    user_id: Option<i32>,
+    /// Only super-admins search across cohorts
+    every_cohort: bool,
+    /// Cohort searched otherwise; `None` means applications outside any cohort
+    cohort_id: Option<i32>,
+}
+
+impl SuggestFilter {
+    fn new(scope: SuggestScope, auth_user: &AuthUser) -> Self {
+        match scope {
+            SuggestScope::Own => Self {
+                user_id: Some(auth_user.user_id),
+                every_cohort: true,
+                cohort_id: None,
+            },
+            SuggestScope::All => Self {
+                user_id: None,
+                every_cohort: auth_user.is_super_admin(),
+                cohort_id: auth_user.cohort_id,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CompanySuggestQuery {
+    pub q: String,
+    #[serde(default)]
+    pub scope: SuggestScope,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CompanySuggestion {
+    /// The most common spelling among applications sharing the normalized name
+    pub company: String,
+    pub application_count: i64,
+}
+
+pub async fn suggest_companies(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<CompanySuggestQuery>,
+) -> Result<Json<Vec<CompanySuggestion>>, AppError> {
+    let normalized = normalize_company(&query.q);
+    if normalized.is_empty() {
+        return Ok(Json(Vec::new()));
+    }
+
+    let filter = SuggestFilter::new(query.scope, &auth_user);
+
+    // Prefix matches rank first, then by trigram similarity and popularity
+    let suggestions = sqlx::query_as::<_, CompanySuggestion>(
+        r#"
+        SELECT MODE() WITHIN GROUP (ORDER BY company) AS company,
+               COUNT(*)::bigint AS application_count
+        FROM applications
+        WHERE ($1::int IS NULL OR user_id = $1)
+        AND ($6::bool OR cohort_id IS NOT DISTINCT FROM $7)
+        AND company_normalized IS NOT NULL
+        AND (company_normalized LIKE $2 || '%' OR similarity(company_normalized, $3) >= $4)
+        GROUP BY company_normalized
+        ORDER BY (company_normalized LIKE $2 || '%') DESC,
+                 similarity(company_normalized, $3) DESC,
+                 COUNT(*) DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(filter.user_id)
+    .bind(escape_like(&normalized))
+    .bind(&normalized)
+    .bind(SIMILARITY_THRESHOLD)
+    .bind(MAX_SUGGESTIONS)
+    .bind(filter.every_cohort)
+    .bind(filter.cohort_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(suggestions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::UserRole;
+
+    fn user(role: UserRole, cohort_id: Option<i32>) -> AuthUser {
+        AuthUser {
+            user_id: 7,
+            role,
+            cohort_id,
+            token_version: 0,
+            impersonated_by: None,
+        }
+    }
+
+    #[test]
+    fn all_scope_stays_in_the_students_cohort() {
+        let filter = SuggestFilter::new(SuggestScope::All, &user(UserRole::Student, Some(3)));
+        assert_eq!(
+            filter,
+            SuggestFilter {
+                user_id: None,
+                every_cohort: false,
+                cohort_id: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn all_scope_for_unassigned_students_excludes_every_cohort() {
+        let filter = SuggestFilter::new(SuggestScope::All, &user(UserRole::Student, None));
+        assert!(!filter.every_cohort);
+        assert_eq!(filter.cohort_id, None);
+    }
+
+    #[test]
+    fn all_scope_follows_admin_cohort_scope() {
+        let cohort_admin = SuggestFilter::new(SuggestScope::All, &user(UserRole::Admin, Some(3)));
+        assert!(!cohort_admin.every_cohort);
+        assert_eq!(cohort_admin.cohort_id, Some(3));
+
+        let super_admin = SuggestFilter::new(SuggestScope::All, &user(UserRole::Admin, None));
+        assert!(super_admin.every_cohort);
+    }
+
+    #[test]
+    fn own_scope_filters_by_user() {
+        let filter = SuggestFilter::new(SuggestScope::Own, &user(UserRole::Student, Some(3)));
+        assert_eq!(filter.user_id, Some(7));
+    }
+}
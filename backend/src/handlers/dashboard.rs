@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Extension, State},
+    response::Json,
+};
+use std::collections::HashMap;
+
+use crate::{
+    handlers::admin::analytics_busy,
+    middleware::{auth::AuthUser, request_id::RequestId},
+    services::{
+        cache::CacheContext,
+        dashboard::{AdminDashboard, DashboardError, DashboardService, UserDashboard},
+    },
+    utils::{errors::AppError, logger::LOGGER, messages},
+    AppState,
+};
+
+fn dashboard_error(e: DashboardError, user_id: i32) -> AppError {
+    match e {
+        DashboardError::DatabaseError(msg) => {
+            let mut context = HashMap::new();
+            context.insert(
+                "user_id".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(user_id)),
+            );
+            context.insert(
+                "error_type".to_string(),
+                serde_json::Value::String("database".to_string()),
+            );
+            LOGGER.log_error(&msg, context);
+            AppError::InternalServerError(messages::text("dashboard.fetch_failed"))
+        }
+        DashboardError::QueryTimeout => {
+            AppError::QueryTimeout(messages::text("dashboard.query_timeout"))
+        }
+        DashboardError::Overloaded => analytics_busy(),
+    }
+}
+
+/// Status counts, the last 30 days of activity, the number of stale
+/// applications and the latest applications of the caller, in one call
+pub async fn get_dashboard(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
+) -> Result<Json<UserDashboard>, AppError> {
+    DashboardService::new(state.db.clone())
+        .get_cached_user_dashboard(
+            &state.cache,
+            Some(&CacheContext::new(auth_user.user_id, &request_id)),
+            auth_user.user_id,
+        )
+        .await
+        .map(Json)
+        .map_err(|e| dashboard_error(e, auth_user.user_id))
+}
+
+/// Totals, status counts, the last 30 days of activity and the oldest stale
+/// applications across the admin's cohorts
+pub async fn get_admin_dashboard(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
+) -> Result<Json<AdminDashboard>, AppError> {
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_dashboard_access",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text("dashboard.admin_only")));
+    }
+
+    DashboardService::new(state.db.clone())
+        .get_admin_dashboard(
+            &state.cache,
+            &state.analytics_limit,
+            Some(&CacheContext::new(auth_user.user_id, &request_id)),
+            auth_user.cohort_scope(),
+        )
+        .await
+        .map(Json)
+        .map_err(|e| dashboard_error(e, auth_user.user_id))
+}
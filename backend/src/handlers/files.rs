@@ -1,155 +1,485 @@
+use async_zip::{base::write::ZipFileWriter, Compression, ZipEntryBuilder};
 use axum::{
     body::Body,
     extract::{Extension, Path, Query, State},
     http::{header, StatusCode},
-    response::Response,
+    response::{IntoResponse, Json, Response},
 };
-use serde::Deserialize;
-use std::path::PathBuf;
-use tokio::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::io::AsyncWriteExt;
+use tokio_util::{compat::FuturesAsyncWriteCompatExt, io::ReaderStream};
 
-use crate::{middleware::auth::AuthUser, utils::jwt::verify_jwt, AppState};
-use sqlx::PgPool;
+use crate::{
+    middleware::auth::AuthUser,
+    models::file_access::{FileAccessLog, FileAccessQuery},
+    services::{
+        settings::DETECT_CONTENT_TYPE,
+        storage::StorageError,
+        transcode::{needs_preview, preview_key, Transcoder},
+    },
+    utils::{errors::AppError, jwt::verify_jwt, logger::LOGGER, messages, pagination::Pagination},
+    AppState,
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+/// Buffer between the ZIP writer task and the response body
+const ZIP_STREAM_BUFFER: usize = 64 * 1024;
+/// Lifetime of direct download links handed out by object storage backends
+const PRESIGNED_URL_TTL_SECS: u32 = 300;
+/// How long clients may reuse a downloaded recording. Stored files are named by
+/// a fresh UUID and never rewritten, so the limit only bounds how long a copy
+/// outlives its deletion.
+const MEDIA_CACHE_MAX_AGE_SECS: u32 = 30 * 24 * 60 * 60;
+/// How long clients should wait before asking for a preview that is still being made
+const PREVIEW_RETRY_AFTER_SECS: u64 = 10;
+
+/// How the browser should treat a served recording
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Disposition {
+    /// Play in place, e.g. in an `<audio>`/`<video>` tag
+    Inline,
+    #[default]
+    Attachment,
+}
+
+impl Disposition {
+    fn header_value(&self, filename: &str) -> String {
+        match self {
+            Disposition::Inline => "inline".to_string(),
+            Disposition::Attachment => {
+                // `filename` is an ASCII fallback for old clients; `filename*` (RFC 5987)
+                // carries the real name, e.g. Cyrillic
+                let fallback = filename
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect::<String>();
+                format!(
+                    "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+                    fallback,
+                    encode_rfc5987(filename)
+                )
+            }
+        }
+    }
+}
+
+/// Percent-encodes everything outside the RFC 5987 `attr-char` set
+fn encode_rfc5987(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'!'
+            | b'#'
+            | b'$'
+            | b'&'
+            | b'+'
+            | b'-'
+            | b'.'
+            | b'^'
+            | b'_'
+            | b'`'
+            | b'|'
+            | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[derive(Deserialize)]
+pub struct ServeFileQuery {
+    #[serde(default)]
+    disposition: Disposition,
+}
 
 pub async fn serve_file(
     Extension(auth_user): Extension<AuthUser>,
     State(state): State<AppState>,
     Path(filename): Path<String>,
-) -> Result<Response<Body>, StatusCode> {
+    Query(params): Query<ServeFileQuery>,
+) -> Result<Response<Body>, AppError> {
     // Validate filename to prevent path traversal
     if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(AppError::BadRequest(messages::text(
+            "files.invalid_filename",
+        )));
     }
 
-    // Check if user is admin (can access all files) or if user owns the file
+    check_file_access(&state, &auth_user, &filename).await?;
+
+    send_stored_file(
+        &state,
+        &filename,
+        params.disposition,
+        auth_user.user_id,
+        "direct",
+    )
+    .await
+}
+
+/// Admins can access files within their cohort scope, students only their own
+async fn check_file_access(
+    state: &AppState,
+    auth_user: &AuthUser,
+    filename: &str,
+) -> Result<(), AppError> {
     let can_access = if auth_user.is_admin() {
-        true
+        match auth_user.cohort_scope() {
+            None => true,
+            Some(cohort_id) => check_cohort_file_access(&state.db, filename, cohort_id).await?,
+        }
     } else {
         // For students, check if they own the file
-        check_file_ownership(&state.db, &filename, auth_user.user_id).await?
+        check_file_ownership(&state.db, filename, auth_user.user_id).await?
     };
 
     if !can_access {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text("files.not_allowed")));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreviewPendingResponse {
+    pub status: &'static str,
+    pub retry_after_secs: u64,
+}
+
+/// A browser-playable version of a recording, for inline playback.
+///
+/// Formats browsers play natively are served as they are. Others are
+/// transcoded in the background on first request, answering `202 Accepted`
+/// until the preview is ready. Without ffmpeg, or when transcoding failed,
+/// the original is served.
+pub async fn serve_preview(
+    Extension(auth_user): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Path(filename): Path<String>,
+) -> Result<Response<Body>, AppError> {
+    if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
+        return Err(AppError::BadRequest(messages::text(
+            "files.invalid_filename",
+        )));
     }
 
-    let upload_dir = PathBuf::from(&state.upload_dir);
-    let file_path = upload_dir.join(&filename);
+    check_file_access(&state, &auth_user, &filename).await?;
 
-    // Security check: ensure the path is within upload directory (canonical path check)
-    let canonical_file = file_path
-        .canonicalize()
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-    let canonical_upload_dir = upload_dir
-        .canonicalize()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let transcoder = match &state.transcoder {
+        Some(transcoder) if needs_preview(&filename) => transcoder.clone(),
+        _ => {
+            return send_stored_file(
+                &state,
+                &filename,
+                Disposition::Inline,
+                auth_user.user_id,
+                "preview",
+            )
+            .await
+        }
+    };
 
-    if !canonical_file.starts_with(&canonical_upload_dir) {
-        return Err(StatusCode::FORBIDDEN);
+    // Claim the conversion unless another request already did. Failed and
+    // abandoned (server restarted mid-run) conversions are retried after an hour.
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO file_previews (file_path) VALUES ($1)
+        ON CONFLICT (file_path) DO UPDATE
+        SET status = 'pending', last_error = NULL, updated_at = NOW()
+        WHERE file_previews.status IN ('pending', 'failed')
+          AND file_previews.updated_at < NOW() - INTERVAL '1 hour'
+        "#,
+    )
+    .bind(&filename)
+    .execute(&state.db)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if claimed {
+        let state = state.clone();
+        let filename = filename.clone();
+        tokio::spawn(async move {
+            generate_preview(&state, &transcoder, &filename).await;
+        });
     }
 
-    // Check if file exists
-    if !canonical_file.exists() {
-        return Err(StatusCode::NOT_FOUND);
+    let (status, preview_path) = sqlx::query_as::<_, (String, Option<String>)>(
+        "SELECT status, preview_path FROM file_previews WHERE file_path = $1",
+    )
+    .bind(&filename)
+    .fetch_one(&state.db)
+    .await?;
+
+    match (status.as_str(), preview_path) {
+        ("ready", Some(preview_path)) => {
+            send_stored_file(
+                &state,
+                &preview_path,
+                Disposition::Inline,
+                auth_user.user_id,
+                "preview",
+            )
+            .await
+        }
+        ("pending", _) => Ok((
+            StatusCode::ACCEPTED,
+            [(header::RETRY_AFTER, PREVIEW_RETRY_AFTER_SECS.to_string())],
+            Json(PreviewPendingResponse {
+                status: "processing",
+                retry_after_secs: PREVIEW_RETRY_AFTER_SECS,
+            }),
+        )
+            .into_response()),
+        _ => {
+            send_stored_file(
+                &state,
+                &filename,
+                Disposition::Inline,
+                auth_user.user_id,
+                "preview",
+            )
+            .await
+        }
     }
+}
 
-    // Read file
-    let file_content = fs::read(&canonical_file)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+/// Transcodes `filename` and records the outcome in `file_previews`
+async fn generate_preview(state: &AppState, transcoder: &Transcoder, filename: &str) {
+    let result = build_preview(state, transcoder, filename).await;
 
-    // Determine content type based on file extension
-    let content_type = match canonical_file.extension().and_then(|ext| ext.to_str()) {
-        Some("mp4") => "video/mp4",
-        Some("webm") => "video/webm",
-        Some("mp3") => "audio/mpeg",
-        Some("wav") => "audio/wav",
-        Some("ogg") => "audio/ogg",
-        Some("m4a") => "audio/mp4",
-        Some("aac") => "audio/aac",
-        Some("mov") => "video/quicktime",
-        Some("avi") => "video/x-msvideo",
-        Some("mkv") => "video/x-matroska",
-        _ => "application/octet-stream",
+    let update = match &result {
+        Ok((preview_path, nonce)) => {
+            sqlx::query(
+                r#"
+            UPDATE file_previews
+            SET status = 'ready', preview_path = $2, preview_nonce = $3, updated_at = NOW()
+            WHERE file_path = $1
+            "#,
+            )
+            .bind(filename)
+            .bind(preview_path)
+            .bind(nonce)
+            .execute(&state.db)
+            .await
+        }
+        Err(e) => {
+            LOGGER.log_error(
+                &format!("Failed to transcode preview: {:#}", e),
+                [(
+                    "filename".to_string(),
+                    serde_json::Value::String(filename.to_string()),
+                )]
+                .iter()
+                .cloned()
+                .collect(),
+            );
+            sqlx::query(
+                r#"
+                UPDATE file_previews
+                SET status = 'failed', last_error = $2, updated_at = NOW()
+                WHERE file_path = $1
+                "#,
+            )
+            .bind(filename)
+            .bind(format!("{:#}", e))
+            .execute(&state.db)
+            .await
+        }
     };
 
-    // Create safe filename for Content-Disposition
-    let safe_filename = filename
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '-' || *c == '_')
-        .collect::<String>();
+    if let Err(e) = update {
+        tracing::warn!("Failed to record preview status for {}: {}", filename, e);
+    }
+}
 
-    Ok(Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, content_type)
-        .header(header::CONTENT_LENGTH, file_content.len())
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", safe_filename),
-        )
-        .header(header::CACHE_CONTROL, "private, no-cache")
-        .body(Body::from(file_content))
-        .unwrap())
+/// Stores the preview next to the original, encrypted the same way, and
+/// returns its key and nonce
+async fn build_preview(
+    state: &AppState,
+    transcoder: &Transcoder,
+    filename: &str,
+) -> anyhow::Result<(String, Option<Vec<u8>>)> {
+    let (nonce, _) = stored_file_info(&state.db, filename)
+        .await
+        .map_err(|_| anyhow::anyhow!("Failed to look up {}", filename))?;
+    let data = state.files.get(filename).await?;
+    let data = decrypt_if_needed(state, data, nonce.as_deref())
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt {}", filename))?;
+
+    let extension = std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("bin");
+    let preview = transcoder.to_preview(&data, extension).await?;
+
+    let (preview, preview_nonce) = match &state.file_cipher {
+        Some(cipher) => {
+            let (ciphertext, nonce) = cipher
+                .encrypt(&preview)
+                .map_err(|_| anyhow::anyhow!("Failed to encrypt preview"))?;
+            (ciphertext, Some(nonce))
+        }
+        None => (preview, None),
+    };
+
+    let key = preview_key(filename);
+    state.files.put(&key, preview).await?;
+    Ok((key, preview_nonce))
 }
 
 #[derive(Deserialize)]
 pub struct FileQuery {
     token: String,
+    #[serde(default)]
+    disposition: Disposition,
 }
 
 pub async fn serve_file_with_token(
     State(state): State<AppState>,
     Path(filename): Path<String>,
     Query(params): Query<FileQuery>,
-) -> Result<Response<Body>, StatusCode> {
+) -> Result<Response<Body>, AppError> {
     // Validate filename to prevent path traversal
     if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(AppError::BadRequest(messages::text(
+            "files.invalid_filename",
+        )));
     }
 
     // Verify token
-    let claims =
-        verify_jwt(&params.token, &state.jwt_secret).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let claims = verify_jwt(&params.token, &state.jwt_keys)
+        .map_err(|_| AppError::Unauthorized(messages::text("auth.invalid_token")))?;
+
+    // Sessions revoked through /admin/users/:id/revoke-sessions can't download either
+    let current_version = state
+        .token_versions
+        .current_version(&state.db, claims.sub)
+        .await?;
+    if current_version != Some(claims.token_version) {
+        return Err(AppError::Unauthorized(messages::text("auth.invalid_token")));
+    }
 
-    // Check if user is admin (can access all files) or if user owns the file
+    // Only requests through the auth middleware are audited for impersonation
+    if claims.impersonated_by.is_some() {
+        return Err(AppError::Forbidden(messages::text(
+            "auth.impersonation_not_allowed",
+        )));
+    }
+
+    // Admins can access files within their cohort scope, students only their own
     let can_access = if claims.role == "admin" {
-        true
+        match claims.cohort_id {
+            None => true,
+            Some(cohort_id) => check_cohort_file_access(&state.db, &filename, cohort_id).await?,
+        }
     } else {
         // For students, check if they own the file
         check_file_ownership(&state.db, &filename, claims.sub).await?
     };
 
     if !can_access {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text("files.not_allowed")));
     }
 
-    let upload_dir = PathBuf::from(&state.upload_dir);
-    let file_path = upload_dir.join(&filename);
+    send_stored_file(&state, &filename, params.disposition, claims.sub, "token").await
+}
 
-    // Security check: ensure the path is within upload directory (canonical path check)
-    let canonical_file = file_path
-        .canonicalize()
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-    let canonical_upload_dir = upload_dir
-        .canonicalize()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+/// Responds with a stored recording, or redirects to the backend when it can serve it directly
+async fn send_stored_file(
+    state: &AppState,
+    filename: &str,
+    disposition: Disposition,
+    accessed_by: i32,
+    via: &str,
+) -> Result<Response<Body>, AppError> {
+    let (nonce, download_name) = stored_file_info(&state.db, filename).await?;
+    let content_disposition =
+        disposition.header_value(download_name.as_deref().unwrap_or(filename));
 
-    if !canonical_file.starts_with(&canonical_upload_dir) {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    let detect = state
+        .settings
+        .read()
+        .unwrap()
+        .get::<bool>(DETECT_CONTENT_TYPE)
+        .unwrap_or(true);
+
+    // Encrypted files have to pass through here to be decrypted, and detecting
+    // the content type needs the bytes too
+    let presigned_url = match nonce {
+        Some(_) => None,
+        None if detect => None,
+        None => state
+            .files
+            .presigned_url(
+                filename,
+                PRESIGNED_URL_TTL_SECS,
+                &content_disposition,
+                extension_content_type(filename),
+            )
+            .map_err(storage_error)?,
+    };
 
-    // Check if file exists
-    if !canonical_file.exists() {
-        return Err(StatusCode::NOT_FOUND);
+    if let Some(url) = presigned_url {
+        // Bytes go straight from the bucket to the client, so the size isn't known here
+        record_file_access(&state.db, filename, accessed_by, via, 0).await;
+        return Ok(Response::builder()
+            .status(StatusCode::TEMPORARY_REDIRECT)
+            .header(header::LOCATION, url)
+            .header(header::CACHE_CONTROL, "private, no-cache")
+            .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+            .body(Body::empty())
+            .unwrap());
     }
 
-    // Read file
-    let file_content = fs::read(&canonical_file)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let file_content = state.files.get(filename).await.map_err(storage_error)?;
+    let file_content = decrypt_if_needed(state, file_content, nonce.as_deref())?;
+
+    let content_type = if detect {
+        detected_content_type(filename, &file_content)
+    } else {
+        extension_content_type(filename)
+    };
+
+    // A preview URL serves the original until the transcoded version is ready
+    let cache_control = if via == "preview" {
+        "private, no-cache".to_string()
+    } else {
+        format!("private, max-age={}, immutable", MEDIA_CACHE_MAX_AGE_SECS)
+    };
 
-    // Determine content type based on file extension
-    let content_type = match canonical_file.extension().and_then(|ext| ext.to_str()) {
+    record_file_access(&state.db, filename, accessed_by, via, file_content.len()).await;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, file_content.len())
+        .header(header::CONTENT_DISPOSITION, content_disposition)
+        .header(header::CACHE_CONTROL, cache_control)
+        // Direct downloads are authorized by this header, so a cached copy
+        // belongs to whoever sent it
+        .header(header::VARY, "Authorization")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from(file_content))
+        .unwrap())
+}
+
+/// Content type for a stored file's extension
+fn extension_content_type(filename: &str) -> &'static str {
+    match std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+    {
         Some("mp4") => "video/mp4",
         Some("webm") => "video/webm",
         Some("mp3") => "audio/mpeg",
@@ -160,49 +490,517 @@ pub async fn serve_file_with_token(
         Some("mov") => "video/quicktime",
         Some("avi") => "video/x-msvideo",
         Some("mkv") => "video/x-matroska",
+        Some("pdf") => "application/pdf",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("png") => "image/png",
         _ => "application/octet-stream",
+    }
+}
+
+/// Content type of the file's leading bytes when they contradict its extension,
+/// otherwise the extension's.
+///
+/// Uploads are checked against their extension, so the two only disagree when
+/// something slipped past that check; the bytes win then, since they are what
+/// the client would render.
+fn detected_content_type(filename: &str, data: &[u8]) -> &'static str {
+    let extension = std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let Some(kind) = infer::get(data).filter(|kind| kind.extension() != extension) else {
+        return extension_content_type(filename);
     };
 
-    // Create safe filename for Content-Disposition
-    let safe_filename = filename
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '-' || *c == '_')
-        .collect::<String>();
+    tracing::warn!(
+        "Serving {} as {}, detected from its content",
+        filename,
+        kind.mime_type()
+    );
+    kind.mime_type()
+}
 
-    Ok(Response::builder()
+/// Nonce of an encrypted file (`None` for plaintext) and the name it is downloaded under
+async fn stored_file_info(
+    db: &PgPool,
+    filename: &str,
+) -> Result<(Option<Vec<u8>>, Option<String>), AppError> {
+    let info = sqlx::query_as::<_, (Option<Vec<u8>>, Option<String>)>(
+        r#"
+        SELECT file_nonce, original_filename FROM screenings WHERE file_path = $1
+        UNION ALL
+        SELECT file_nonce, original_filename FROM interviews WHERE file_path = $1
+        UNION ALL
+        SELECT file_nonce, original_filename FROM documents WHERE file_path = $1
+        UNION ALL
+        SELECT preview_nonce, NULL FROM file_previews WHERE preview_path = $1
+        LIMIT 1
+        "#,
+    )
+    .bind(filename)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(info.unwrap_or((None, None)))
+}
+
+fn decrypt_if_needed(
+    state: &AppState,
+    data: Vec<u8>,
+    nonce: Option<&[u8]>,
+) -> Result<Vec<u8>, AppError> {
+    let Some(nonce) = nonce else {
+        return Ok(data);
+    };
+
+    let Some(cipher) = &state.file_cipher else {
+        LOGGER.log_error(
+            "Encrypted file requested but FILE_ENCRYPTION_KEY is not set",
+            HashMap::new(),
+        );
+        return Err(AppError::InternalServerError(messages::text(
+            "files.read_failed",
+        )));
+    };
+
+    cipher
+        .decrypt(&data, nonce)
+        .map_err(|_| AppError::InternalServerError(messages::text("files.read_failed")))
+}
+
+fn storage_error(error: StorageError) -> AppError {
+    match error {
+        StorageError::NotFound => AppError::NotFound(messages::text("files.not_found")),
+        StorageError::InvalidKey => AppError::Forbidden(messages::text("files.invalid_filename")),
+        StorageError::AlreadyExists
+        | StorageError::StorageFull(_)
+        | StorageError::IoError(_)
+        | StorageError::BackendError(_) => {
+            AppError::InternalServerError(messages::text("files.read_failed"))
+        }
+    }
+}
+
+/// Stream a ZIP of the screening and interview recordings of an application
+pub async fn download_all_recordings(
+    Extension(auth_user): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Response<Body>, AppError> {
+    let application =
+        sqlx::query("SELECT user_id, cohort_id, company FROM applications WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound(messages::text("applications.not_found")))?;
+
+    let owner_id: i32 = application.get("user_id");
+    let cohort_id: Option<i32> = application.get("cohort_id");
+    let company: String = application.get("company");
+
+    // Same rules as serve_file: admins within their cohort scope, students only their own
+    let can_access = if auth_user.is_admin() {
+        match auth_user.cohort_scope() {
+            None => true,
+            Some(scope) => cohort_id == Some(scope),
+        }
+    } else {
+        owner_id == auth_user.user_id
+    };
+
+    if !can_access {
+        return Err(AppError::Forbidden(messages::text("files.not_allowed")));
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT 'screening' AS kind, file_path, file_nonce FROM screenings
+        WHERE application_id = $1 AND file_path IS NOT NULL
+        UNION ALL
+        SELECT 'interview' AS kind, file_path, file_nonce FROM interviews
+        WHERE application_id = $1 AND file_path IS NOT NULL
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let safe_company = sanitize_filename(&company);
+    let mut entries = Vec::new();
+    for row in rows {
+        let kind: String = row.get("kind");
+        let entry_stem = format!("{}_{}", safe_company, kind);
+        if let Some(entry) = zip_source(&state, entry_stem, &row).await {
+            entries.push(entry);
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(AppError::NotFound(messages::text("files.no_recordings")));
+    }
+
+    Ok(stream_zip(
+        &state,
+        entries,
+        auth_user.user_id,
+        &format!("{}_recordings.zip", safe_company),
+        ("application_id", id),
+    ))
+}
+
+/// Stream a ZIP of every screening and interview recording of one student,
+/// one folder per application
+pub async fn download_user_recordings(
+    Extension(auth_user): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Path(user_id): Path<i32>,
+) -> Result<Response<Body>, AppError> {
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_recordings_export",
+            Some(auth_user.user_id),
+            [(
+                "target_user_id".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(user_id)),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "files.admin_only_export",
+        )));
+    }
+
+    // Students outside a cohort admin's scope look the same as missing ones
+    let student = sqlx::query(
+        "SELECT email FROM users WHERE id = $1 AND ($2::int IS NULL OR cohort_id = $2)",
+    )
+    .bind(user_id)
+    .bind(auth_user.cohort_scope())
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(messages::text("users.not_found")))?;
+    let email: String = student.get("email");
+
+    let rows = sqlx::query(
+        r#"
+        SELECT a.id AS application_id, a.company, 'screening' AS kind, s.file_path, s.file_nonce
+        FROM applications a
+        JOIN screenings s ON s.application_id = a.id
+        WHERE a.user_id = $1 AND s.file_path IS NOT NULL
+        UNION ALL
+        SELECT a.id AS application_id, a.company, 'interview' AS kind, i.file_path, i.file_nonce
+        FROM applications a
+        JOIN interviews i ON i.application_id = a.id
+        WHERE a.user_id = $1 AND i.file_path IS NOT NULL
+        ORDER BY application_id, kind DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let application_id: i32 = row.get("application_id");
+        let company: String = row.get("company");
+        let kind: String = row.get("kind");
+        // The id keeps two applications to the same company apart
+        let entry_stem = format!(
+            "{}_{}/{}",
+            sanitize_filename(&company),
+            application_id,
+            kind
+        );
+        if let Some(entry) = zip_source(&state, entry_stem, &row).await {
+            entries.push(entry);
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(AppError::NotFound(messages::text(
+            "files.no_user_recordings",
+        )));
+    }
+
+    LOGGER.log_business_event(
+        "recordings_exported",
+        Some(auth_user.user_id),
+        [
+            (
+                "target_user_id".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(user_id)),
+            ),
+            (
+                "files".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(entries.len())),
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(stream_zip(
+        &state,
+        entries,
+        auth_user.user_id,
+        &format!("{}_recordings.zip", sanitize_filename(&email)),
+        ("target_user_id", user_id),
+    ))
+}
+
+/// Archive entry for a recording row with `file_path` and `file_nonce` columns.
+///
+/// `None` when the file is missing or its name is unsafe, so one bad record
+/// doesn't break the whole archive.
+async fn zip_source(state: &AppState, entry_stem: String, row: &PgRow) -> Option<ZipSource> {
+    let filename: String = row.get("file_path");
+    let nonce: Option<Vec<u8>> = row.get("file_nonce");
+
+    if !state.files.exists(&filename).await.unwrap_or(false) {
+        return None;
+    }
+
+    let entry_name = match std::path::Path::new(&filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+    {
+        Some(ext) => format!("{}.{}", entry_stem, ext),
+        None => entry_stem,
+    };
+    Some(ZipSource {
+        entry_name,
+        filename,
+        nonce,
+    })
+}
+
+/// Responds with a ZIP of `entries`, written by a background task as the client reads it
+fn stream_zip(
+    state: &AppState,
+    entries: Vec<ZipSource>,
+    user_id: i32,
+    archive_name: &str,
+    log_context: (&str, i32),
+) -> Response<Body> {
+    // The archive is written into one end of a pipe while the response streams the other,
+    // so plain recordings are never held in memory as a whole. Encrypted ones are
+    // sealed as one block and have to be read in full to be decrypted.
+    let (reader, writer) = tokio::io::duplex(ZIP_STREAM_BUFFER);
+    let state = state.clone();
+    let (context_key, context_id) = (log_context.0.to_string(), log_context.1);
+    tokio::spawn(async move {
+        if let Err(e) = write_zip(writer, entries, &state, user_id).await {
+            LOGGER.log_error(
+                &format!("Failed to stream recordings archive: {}", e),
+                [(
+                    context_key,
+                    serde_json::Value::Number(serde_json::Number::from(context_id)),
+                )]
+                .iter()
+                .cloned()
+                .collect(),
+            );
+        }
+    });
+
+    Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, content_type)
-        .header(header::CONTENT_LENGTH, file_content.len())
+        .header(header::CONTENT_TYPE, "application/zip")
         .header(
             header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", safe_filename),
+            format!("attachment; filename=\"{}\"", archive_name),
         )
         .header(header::CACHE_CONTROL, "private, no-cache")
-        .body(Body::from(file_content))
-        .unwrap())
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from_stream(ReaderStream::new(reader)))
+        .unwrap()
+}
+
+struct ZipSource {
+    entry_name: String,
+    filename: String,
+    nonce: Option<Vec<u8>>,
+}
+
+async fn write_zip(
+    writer: tokio::io::DuplexStream,
+    entries: Vec<ZipSource>,
+    state: &AppState,
+    user_id: i32,
+) -> anyhow::Result<()> {
+    let mut zip = ZipFileWriter::with_tokio(writer);
+
+    for ZipSource {
+        entry_name,
+        filename,
+        nonce,
+    } in entries
+    {
+        // Recordings are already compressed; storing them avoids burning CPU for nothing
+        let builder = ZipEntryBuilder::new(entry_name.into(), Compression::Stored);
+        let mut entry_writer = zip.write_entry_stream(builder).await?.compat_write();
+
+        let bytes_written = match nonce {
+            None => {
+                let mut reader = state.files.open(&filename).await?;
+                tokio::io::copy(&mut reader, &mut entry_writer).await? as usize
+            }
+            Some(nonce) => {
+                let data = state.files.get(&filename).await?;
+                let data = decrypt_if_needed(state, data, Some(&nonce))
+                    .map_err(|_| anyhow::anyhow!("Failed to decrypt {}", filename))?;
+                entry_writer.write_all(&data).await?;
+                data.len()
+            }
+        };
+        entry_writer.into_inner().close().await?;
+
+        record_file_access(&state.db, &filename, user_id, "archive", bytes_written).await;
+    }
+
+    zip.close().await?;
+    Ok(())
+}
+
+/// ASCII-only so the name is always a valid header value
+fn sanitize_filename(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if sanitized.chars().all(|c| c == '_') {
+        "application".to_string()
+    } else {
+        sanitized
+    }
 }
 
-async fn check_file_ownership(
+/// Best effort: a failed insert is logged but never blocks the download
+async fn record_file_access(
     db: &PgPool,
     filename: &str,
     user_id: i32,
-) -> Result<bool, StatusCode> {
-    // Check if the file belongs to any screening or interview record owned by the user
+    via: &str,
+    bytes_served: usize,
+) {
+    let result = sqlx::query(
+        "INSERT INTO file_access_log (filename, accessed_by, via, bytes_served) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(filename)
+    .bind(user_id)
+    .bind(via)
+    .bind(bytes_served as i64)
+    .execute(db)
+    .await;
+
+    if let Err(e) = result {
+        LOGGER.log_error(
+            &format!("Failed to record file access: {}", e),
+            [(
+                "filename".to_string(),
+                serde_json::Value::String(filename.to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+    }
+}
+
+/// Download history for recordings and documents, newest first
+pub async fn get_file_access_log(
+    Extension(auth_user): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Query(query): Query<FileAccessQuery>,
+    pagination: Pagination,
+) -> Result<Json<Vec<FileAccessLog>>, AppError> {
+    if !auth_user.is_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "files.admin_only_access_log",
+        )));
+    }
+
+    // Cohort admins only see downloads of files that belong to their cohort
+    let entries = sqlx::query_as::<_, FileAccessLog>(
+        r#"
+        SELECT l.* FROM file_access_log l
+        WHERE ($1::text IS NULL OR l.filename = $1)
+        AND ($2::int IS NULL OR l.accessed_by = $2)
+        AND ($3::int IS NULL OR EXISTS (
+            SELECT 1 FROM applications a
+            LEFT JOIN screenings s ON a.id = s.application_id
+            LEFT JOIN interviews i ON a.id = i.application_id
+            LEFT JOIN documents d ON a.id = d.application_id
+            WHERE a.cohort_id = $3
+            AND (s.file_path = l.filename OR i.file_path = l.filename OR d.file_path = l.filename)
+        ))
+        ORDER BY l.accessed_at DESC, l.id DESC
+        LIMIT $4 OFFSET $5
+        "#,
+    )
+    .bind(&query.filename)
+    .bind(query.user_id)
+    .bind(auth_user.cohort_scope())
+    .bind(pagination.limit)
+    .bind(pagination.offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(entries))
+}
+
+async fn check_file_ownership(db: &PgPool, filename: &str, user_id: i32) -> Result<bool, AppError> {
+    // Check if the file belongs to any screening, interview or document owned by the user
     let result = sqlx::query_scalar::<_, i64>(
         r#"
         SELECT COUNT(*)
         FROM applications a
         LEFT JOIN screenings s ON a.id = s.application_id
         LEFT JOIN interviews i ON a.id = i.application_id
+        LEFT JOIN documents d ON a.id = d.application_id
         WHERE a.user_id = $1 
-        AND (s.file_path = $2 OR i.file_path = $2)
+        AND (s.file_path = $2 OR i.file_path = $2 OR d.file_path = $2)
         "#,
     )
     .bind(user_id)
     .bind(filename)
     .fetch_one(db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
+
+    Ok(result > 0)
+}
+
+async fn check_cohort_file_access(
+    db: &PgPool,
+    filename: &str,
+    cohort_id: i32,
+) -> Result<bool, AppError> {
+    // Check if the file belongs to any screening, interview or document within the cohort
+    let result = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM applications a
+        LEFT JOIN screenings s ON a.id = s.application_id
+        LEFT JOIN interviews i ON a.id = i.application_id
+        LEFT JOIN documents d ON a.id = d.application_id
+        WHERE a.cohort_id = $1
+        AND (s.file_path = $2 OR i.file_path = $2 OR d.file_path = $2)
+        "#,
+    )
+    .bind(cohort_id)
+    .bind(filename)
+    .fetch_one(db)
+    .await?;
 
     Ok(result > 0)
 }
@@ -0,0 +1,81 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+
+use crate::{services::storage::available_space, utils::logger::LOGGER, AppState};
+
+/// Free space the upload directory needs for the instance to take traffic
+fn min_free_disk_mb() -> u64 {
+    env::var("MIN_FREE_DISK_MB")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(1024)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub database: bool,
+    /// `None` when the free space couldn't be determined
+    pub upload_dir_free_mb: Option<u64>,
+    pub min_free_disk_mb: u64,
+}
+
+/// Readiness probe: 200 when the database answers and the upload directory
+/// has at least `MIN_FREE_DISK_MB` free, 503 otherwise.
+///
+/// Unlike `/health`, which only says the process is up, this catches a disk
+/// filling up before uploads start failing.
+pub async fn ready(State(state): State<AppState>) -> Response {
+    let database = sqlx::query("SELECT 1").execute(&state.db).await.is_ok();
+
+    let min_free_disk_mb = min_free_disk_mb();
+    let upload_dir_free_mb = match available_space(&state.upload_dir) {
+        Ok(bytes) => Some(bytes / 1024 / 1024),
+        Err(e) => {
+            tracing::warn!("Failed to read free space of {}: {}", state.upload_dir, e);
+            None
+        }
+    };
+
+    let disk_ok = upload_dir_free_mb.is_none_or(|free| free >= min_free_disk_mb);
+    if !disk_ok {
+        let mut context = HashMap::new();
+        context.insert(
+            "error_type".to_string(),
+            serde_json::Value::String("storage_low".to_string()),
+        );
+        context.insert(
+            "upload_dir".to_string(),
+            serde_json::Value::String(state.upload_dir.clone()),
+        );
+        context.insert(
+            "free_mb".to_string(),
+            serde_json::Value::Number(serde_json::Number::from(upload_dir_free_mb.unwrap_or(0))),
+        );
+        LOGGER.log_error("Upload directory is running out of space", context);
+    }
+
+    let ready = database && disk_ok;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            database,
+            upload_dir_free_mb,
+            min_free_disk_mb,
+        }),
+    )
+        .into_response()
+}
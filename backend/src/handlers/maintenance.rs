@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Extension, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    middleware::auth::AuthUser,
+    services::maintenance::{backfill_batch, remaining_after, BackfillField},
+    utils::{errors::AppError, logger::LOGGER, messages},
+    AppState,
+};
+
+const DEFAULT_BACKFILL_BATCH_SIZE: i64 = 500;
+
+/// Batches run per request when `max_batches` isn't given; keeps one call
+/// well inside the request timeout
+const DEFAULT_BACKFILL_MAX_BATCHES: u32 = 20;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BackfillRequest {
+    pub field: BackfillField,
+    /// Continue after this application id, as returned in `next_after_id`;
+    /// starts from the beginning when omitted
+    pub after_id: Option<i32>,
+    #[validate(range(min = 1, max = 5000))]
+    pub batch_size: Option<i64>,
+    #[validate(range(min = 1, max = 100))]
+    pub max_batches: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackfillResponse {
+    pub field: BackfillField,
+    pub batches: u32,
+    pub scanned: i64,
+    pub updated: i64,
+    /// Send as `after_id` to pick up where this call stopped
+    pub next_after_id: Option<i32>,
+    /// Applications past `next_after_id` not scanned yet
+    pub remaining: i64,
+    pub done: bool,
+}
+
+/// Recomputes a derived column for existing applications in id-ordered batches.
+///
+/// A call stops after `max_batches`; call again with the returned
+/// `next_after_id` until `done`. Only rows whose value differs are written, so
+/// repeating or restarting a backfill is safe.
+pub async fn backfill(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<BackfillRequest>,
+) -> Result<Json<BackfillResponse>, AppError> {
+    // Backfills touch every cohort's rows
+    if !auth_user.is_super_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_backfill",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "maintenance.super_admin_only_backfill",
+        )));
+    }
+
+    payload.validate()?;
+
+    let batch_size = payload.batch_size.unwrap_or(DEFAULT_BACKFILL_BATCH_SIZE);
+    let max_batches = payload.max_batches.unwrap_or(DEFAULT_BACKFILL_MAX_BATCHES);
+
+    let mut cursor = payload.after_id.unwrap_or(0);
+    let mut response = BackfillResponse {
+        field: payload.field,
+        batches: 0,
+        scanned: 0,
+        updated: 0,
+        next_after_id: payload.after_id,
+        remaining: 0,
+        done: false,
+    };
+
+    while response.batches < max_batches {
+        let batch = backfill_batch(&state.db, payload.field, cursor, batch_size).await?;
+        response.batches += 1;
+        response.scanned += batch.scanned;
+        response.updated += batch.updated;
+
+        if let Some(last_id) = batch.last_id {
+            cursor = last_id;
+            response.next_after_id = Some(last_id);
+        }
+        if batch.scanned < batch_size {
+            response.done = true;
+            break;
+        }
+    }
+
+    if !response.done {
+        response.remaining = remaining_after(&state.db, cursor).await?;
+        response.done = response.remaining == 0;
+    }
+
+    LOGGER.log_business_event(
+        "backfill_run",
+        Some(auth_user.user_id),
+        [
+            (
+                "field".to_string(),
+                serde_json::to_value(payload.field).unwrap_or_default(),
+            ),
+            (
+                "updated".to_string(),
+                serde_json::Value::Number(response.updated.into()),
+            ),
+            ("done".to_string(), serde_json::Value::Bool(response.done)),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(response))
+}
@@ -1,25 +1,60 @@
 use axum::{
-    extract::{Extension, Query, State},
-    http::StatusCode,
+    extract::{Extension, Path, Query, State},
     response::Json,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::{
-    middleware::auth::AuthUser,
-    services::cache::{CacheService, CacheStats},
-    services::metrics::{MetricsError, MetricsService, TimeBasedMetrics},
-    utils::logger::LOGGER,
+    middleware::{auth::AuthUser, request_id::RequestId},
+    services::cache::{
+        CacheContext, CacheEntryDetails, CacheError, CacheSnapshotEntry, CacheStats,
+    },
+    services::metrics::{
+        Granularity, MetricsError, MetricsService, MetricsTimeseries, PrivacyTier,
+        StudentBenchmark, TimeBasedMetrics, TimeseriesMetric, MAX_METRICS_DAYS,
+    },
+    utils::{errors::AppError, logger::LOGGER, messages},
     AppState,
 };
 
+/// Longest `cache_duration` a client can ask for, in minutes
+const MAX_CACHE_DURATION_MINUTES: i32 = 24 * 60;
+
 #[derive(Debug, Deserialize)]
 pub struct MetricsQuery {
     pub days: Option<i32>,
+    /// Minutes
     pub cache_duration: Option<i32>,
 }
 
+/// `value`, or `default` when absent; a 400 naming `name` when outside `min..=max`.
+///
+/// These flow into interval arithmetic in SQL, where a negative or huge value
+/// silently yields nonsense or a scan of every row.
+pub(crate) fn bounded_param(
+    name: &str,
+    value: Option<i32>,
+    default: i32,
+    min: i32,
+    max: i32,
+) -> Result<i32, AppError> {
+    let value = value.unwrap_or(default);
+    if (min..=max).contains(&value) {
+        return Ok(value);
+    }
+
+    let mut errors = HashMap::new();
+    errors.insert(
+        name.to_string(),
+        vec![messages::formatted(
+            &format!("metrics.{}_out_of_range", name),
+            format!("{} must be between {} and {}", name, min, max),
+        )],
+    );
+    Err(AppError::ValidationError(errors))
+}
+
 #[derive(Debug, Serialize)]
 pub struct MetricsResponse {
     pub metrics: TimeBasedMetrics,
@@ -34,12 +69,13 @@ pub struct CacheStatsResponse {
     pub entries_cleaned: usize,
 }
 
-/// Get anonymized time-based metrics
+/// Get time-based metrics, anonymized unless the caller is a super-admin
 pub async fn get_anonymous_metrics(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
     Query(query): Query<MetricsQuery>,
-) -> Result<Json<MetricsResponse>, StatusCode> {
+) -> Result<Json<MetricsResponse>, AppError> {
     // Only admins can access metrics
     if !auth_user.is_admin() {
         LOGGER.log_business_event(
@@ -53,19 +89,39 @@ pub async fn get_anonymous_metrics(
             .cloned()
             .collect(),
         );
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text("metrics.admin_only")));
     }
 
-    let days_back = query.days.unwrap_or(30);
-    let cache_duration = query.cache_duration.unwrap_or(60); // 1 hour default
+    let days_back = bounded_param("days", query.days, 30, 1, MAX_METRICS_DAYS)?;
+    let cache_duration = bounded_param(
+        "cache_duration",
+        query.cache_duration,
+        60,
+        1,
+        MAX_CACHE_DURATION_MINUTES,
+    )?;
+
+    // Super-admins see every group and the company names; other admins get
+    // the anonymized report
+    let tier = if auth_user.is_super_admin() {
+        PrivacyTier::Detailed
+    } else {
+        PrivacyTier::Aggregate
+    };
 
     LOGGER.log_request("GET", "/admin/metrics", Some(auth_user.user_id), 200);
 
     let start_time = std::time::Instant::now();
-    let metrics_service = MetricsService::new(state.db.clone());
+    let metrics_service = MetricsService::new(state.db.clone(), auth_user.cohort_scope());
 
     match metrics_service
-        .get_cached_metrics(days_back, cache_duration)
+        .get_cached_metrics(
+            &state.cache,
+            Some(&CacheContext::new(auth_user.user_id, &request_id)),
+            days_back,
+            tier,
+            cache_duration,
+        )
         .await
     {
         Ok(metrics) => {
@@ -83,6 +139,10 @@ pub async fn get_anonymous_metrics(
                         "generation_time_ms".to_string(),
                         serde_json::Value::Number(serde_json::Number::from(generation_time)),
                     ),
+                    (
+                        "privacy_tier".to_string(),
+                        serde_json::to_value(tier).unwrap_or_default(),
+                    ),
                 ]
                 .iter()
                 .cloned()
@@ -106,7 +166,9 @@ pub async fn get_anonymous_metrics(
                 serde_json::Value::Number(serde_json::Number::from(days_back)),
             );
             LOGGER.log_error(&msg, context);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.generate_failed",
+            )))
         }
         Err(MetricsError::CalculationError(msg)) => {
             let mut context = HashMap::new();
@@ -115,8 +177,111 @@ pub async fn get_anonymous_metrics(
                 serde_json::Value::Number(serde_json::Number::from(auth_user.user_id)),
             );
             LOGGER.log_error(&msg, context);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.generate_failed",
+            )))
+        }
+        Err(MetricsError::QueryTimeout) => Err(AppError::QueryTimeout(messages::text(
+            "metrics.query_timeout",
+        ))),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeseriesQuery {
+    #[serde(default)]
+    pub metric: TimeseriesMetric,
+    #[serde(default)]
+    pub granularity: Granularity,
+    pub days: Option<i32>,
+}
+
+/// One metric bucketed by day, week or month, for charting trends
+pub async fn get_metrics_timeseries(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<MetricsTimeseries>, AppError> {
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_metrics_access",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text("metrics.admin_only")));
+    }
+
+    let days_back = bounded_param("days", query.days, 90, 1, MAX_METRICS_DAYS)?;
+    let metrics_service = MetricsService::new(state.db.clone(), auth_user.cohort_scope());
+
+    match metrics_service
+        .get_cached_timeseries(
+            &state.cache,
+            Some(&CacheContext::new(auth_user.user_id, &request_id)),
+            query.metric,
+            query.granularity,
+            days_back,
+        )
+        .await
+    {
+        Ok(timeseries) => Ok(Json(timeseries)),
+        Err(MetricsError::DatabaseError(msg)) | Err(MetricsError::CalculationError(msg)) => {
+            let mut context = HashMap::new();
+            context.insert(
+                "user_id".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(auth_user.user_id)),
+            );
+            LOGGER.log_error(&msg, context);
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.generate_failed",
+            )))
         }
+        Err(MetricsError::QueryTimeout) => Err(AppError::QueryTimeout(messages::text(
+            "metrics.query_timeout",
+        ))),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BenchmarkQuery {
+    pub days: Option<i32>,
+}
+
+/// The caller's own numbers against the anonymized rest of their cohort
+pub async fn get_student_benchmark(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<BenchmarkQuery>,
+) -> Result<Json<StudentBenchmark>, AppError> {
+    let days_back = bounded_param("days", query.days, 90, 1, MAX_METRICS_DAYS)?;
+    let metrics_service = MetricsService::new(state.db.clone(), auth_user.cohort_id);
+
+    match metrics_service
+        .student_benchmark(auth_user.user_id, days_back)
+        .await
+    {
+        Ok(benchmark) => Ok(Json(benchmark)),
+        Err(MetricsError::DatabaseError(msg)) | Err(MetricsError::CalculationError(msg)) => {
+            let mut context = HashMap::new();
+            context.insert(
+                "user_id".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(auth_user.user_id)),
+            );
+            LOGGER.log_error(&msg, context);
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.generate_failed",
+            )))
+        }
+        Err(MetricsError::QueryTimeout) => Err(AppError::QueryTimeout(messages::text(
+            "metrics.query_timeout",
+        ))),
     }
 }
 
@@ -124,7 +289,7 @@ pub async fn get_anonymous_metrics(
 pub async fn get_cache_stats(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<CacheStatsResponse>, StatusCode> {
+) -> Result<Json<CacheStatsResponse>, AppError> {
     // Only admins can access cache stats
     if !auth_user.is_admin() {
         LOGGER.log_business_event(
@@ -138,16 +303,15 @@ pub async fn get_cache_stats(
             .cloned()
             .collect(),
         );
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text(
+            "metrics.admin_only_cache_stats",
+        )));
     }
 
     LOGGER.log_request("GET", "/admin/cache-stats", Some(auth_user.user_id), 200);
 
-    // Create cache service with reasonable memory limits
-    let cache_service = CacheService::new(state.db.clone(), 1000);
-
     let (stats_result, cleanup_result) =
-        tokio::join!(cache_service.get_stats(), cache_service.cleanup_expired());
+        tokio::join!(state.cache.get_stats(), state.cache.cleanup_expired());
 
     match (stats_result, cleanup_result) {
         (Ok(cache_stats), Ok(entries_cleaned)) => {
@@ -163,6 +327,10 @@ pub async fn get_cache_stats(
                         "entries_cleaned".to_string(),
                         serde_json::Value::Number(serde_json::Number::from(entries_cleaned)),
                     ),
+                    (
+                        "evictions".to_string(),
+                        serde_json::Value::Number(serde_json::Number::from(cache_stats.evictions)),
+                    ),
                 ]
                 .iter()
                 .cloned()
@@ -177,11 +345,203 @@ pub async fn get_cache_stats(
         }
         _ => {
             LOGGER.log_error("Failed to get cache statistics", HashMap::new());
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.cache_stats_failed",
+            )))
         }
     }
 }
 
+/// Inspect a single cache entry (memory and persistent layers)
+pub async fn inspect_cache_entry(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(key): Path<String>,
+) -> Result<Json<CacheEntryDetails>, AppError> {
+    // Only admins can inspect cache entries
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_cache_access",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "metrics.admin_only_cache_inspect",
+        )));
+    }
+
+    LOGGER.log_request("GET", "/admin/cache/:key", Some(auth_user.user_id), 200);
+
+    match state.cache.inspect(&key).await {
+        Ok(details) => Ok(Json(details)),
+        Err(CacheError::NotFound) => Err(AppError::NotFound(messages::text(
+            "metrics.cache_entry_not_found",
+        ))),
+        Err(_) => {
+            let mut context = HashMap::new();
+            context.insert("cache_key".to_string(), serde_json::Value::String(key));
+            LOGGER.log_error("Failed to inspect cache entry", context);
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.cache_inspect_failed",
+            )))
+        }
+    }
+}
+
+/// Longest lifetime an imported cache entry can be given
+const MAX_IMPORT_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct CacheExportResponse {
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub entries: Vec<CacheSnapshotEntry>,
+}
+
+/// Accepts the body of `CacheExportResponse` as is
+#[derive(Debug, Deserialize)]
+pub struct CacheImportRequest {
+    pub entries: Vec<CacheSnapshotEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheImportResponse {
+    pub imported_count: usize,
+}
+
+/// Snapshot the unexpired entries of the persistent cache for backup.
+///
+/// Super-admins only: the cache holds every cohort's analytics.
+pub async fn export_cache(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<CacheExportResponse>, AppError> {
+    if !auth_user.is_super_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_cache_access",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "metrics.super_admin_only_cache_export",
+        )));
+    }
+
+    LOGGER.log_request("GET", "/admin/cache/export", Some(auth_user.user_id), 200);
+
+    let entries = state.cache.export().await.map_err(|_| {
+        LOGGER.log_error("Failed to export cache", HashMap::new());
+        AppError::InternalServerError(messages::text("metrics.cache_export_failed"))
+    })?;
+
+    LOGGER.log_business_event(
+        "cache_exported",
+        Some(auth_user.user_id),
+        [(
+            "entry_count".to_string(),
+            serde_json::Value::Number(serde_json::Number::from(entries.len())),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(CacheExportResponse {
+        exported_at: chrono::Utc::now(),
+        entries,
+    }))
+}
+
+/// Restore entries from a cache export, each with a fresh expiry.
+///
+/// Super-admins only: imported values are served as computed data to every cohort.
+pub async fn import_cache(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CacheImportRequest>,
+) -> Result<Json<CacheImportResponse>, AppError> {
+    if !auth_user.is_super_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_cache_import",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "metrics.super_admin_only_cache_import",
+        )));
+    }
+
+    let mut errors = HashMap::new();
+    if request
+        .entries
+        .iter()
+        .any(|entry| entry.key.trim().is_empty())
+    {
+        errors.insert(
+            "key".to_string(),
+            vec![messages::text("metrics.cache_key_empty")],
+        );
+    }
+    if request
+        .entries
+        .iter()
+        .any(|entry| !(1..=MAX_IMPORT_TTL_SECONDS).contains(&entry.ttl_seconds))
+    {
+        errors.insert(
+            "ttl_seconds".to_string(),
+            vec![messages::formatted(
+                "metrics.cache_ttl_out_of_range",
+                format!(
+                    "ttl_seconds must be between 1 and {}",
+                    MAX_IMPORT_TTL_SECONDS
+                ),
+            )],
+        );
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(errors));
+    }
+
+    LOGGER.log_request("POST", "/admin/cache/import", Some(auth_user.user_id), 200);
+
+    let imported_count = state.cache.import(&request.entries).await.map_err(|_| {
+        LOGGER.log_error("Failed to import cache", HashMap::new());
+        AppError::InternalServerError(messages::text("metrics.cache_import_failed"))
+    })?;
+
+    LOGGER.log_business_event(
+        "cache_imported",
+        Some(auth_user.user_id),
+        [(
+            "imported_count".to_string(),
+            serde_json::Value::Number(serde_json::Number::from(imported_count)),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(CacheImportResponse { imported_count }))
+}
+
 /// Invalidate cache entries by pattern
 #[derive(Debug, Deserialize)]
 pub struct InvalidateRequest {
@@ -197,8 +557,9 @@ pub struct InvalidateResponse {
 pub async fn invalidate_cache(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    Extension(request_id): Extension<RequestId>,
     Json(request): Json<InvalidateRequest>,
-) -> Result<Json<InvalidateResponse>, StatusCode> {
+) -> Result<Json<InvalidateResponse>, AppError> {
     // Only admins can invalidate cache
     if !auth_user.is_admin() {
         LOGGER.log_business_event(
@@ -218,7 +579,9 @@ pub async fn invalidate_cache(
             .cloned()
             .collect(),
         );
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text(
+            "metrics.admin_only_cache_invalidate",
+        )));
     }
 
     LOGGER.log_request(
@@ -228,9 +591,14 @@ pub async fn invalidate_cache(
         200,
     );
 
-    let cache_service = CacheService::new(state.db.clone(), 1000);
-
-    match cache_service.invalidate_pattern(&request.pattern).await {
+    match state
+        .cache
+        .invalidate_pattern(
+            &request.pattern,
+            Some(&CacheContext::new(auth_user.user_id, &request_id)),
+        )
+        .await
+    {
         Ok(invalidated_count) => {
             LOGGER.log_business_event(
                 "cache_invalidated",
@@ -257,7 +625,9 @@ pub async fn invalidate_cache(
         }
         Err(_) => {
             LOGGER.log_error("Failed to invalidate cache", HashMap::new());
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.cache_invalidate_failed",
+            )))
         }
     }
 }
@@ -272,18 +642,18 @@ pub struct WarmCacheResponse {
 pub async fn warm_cache(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<WarmCacheResponse>, StatusCode> {
+) -> Result<Json<WarmCacheResponse>, AppError> {
     // Only admins can warm cache
     if !auth_user.is_admin() {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text(
+            "metrics.admin_only_cache_warm",
+        )));
     }
 
     LOGGER.log_request("POST", "/admin/cache-warm", Some(auth_user.user_id), 200);
 
     let start_time = std::time::Instant::now();
-    let cache_service = CacheService::new(state.db.clone(), 1000);
-
-    match cache_service.warm_cache().await {
+    match state.cache.warm_cache().await {
         Ok(_) => {
             let warming_time = start_time.elapsed().as_millis() as u64;
 
@@ -306,7 +676,9 @@ pub async fn warm_cache(
         }
         Err(_) => {
             LOGGER.log_error("Failed to warm cache", HashMap::new());
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(AppError::InternalServerError(messages::text(
+                "metrics.cache_warm_failed",
+            )))
         }
     }
 }
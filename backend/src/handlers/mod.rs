@@ -1,6 +1,15 @@
 pub mod admin;
 pub mod applications;
 pub mod auth;
+pub mod cohorts;
+pub mod companies;
+pub mod dashboard;
 pub mod files;
+pub mod health;
+pub mod maintenance;
 pub mod metrics;
 pub mod notifications;
+pub mod realtime;
+pub mod scheduler;
+pub mod settings;
+pub mod webhooks;
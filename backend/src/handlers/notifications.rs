@@ -1,15 +1,34 @@
 use axum::{
     extract::{Extension, Query, State},
-    http::StatusCode,
     response::Json,
 };
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use validator::Validate;
 
-use crate::{middleware::auth::AuthUser, services::notification::NotificationService, AppState};
+use crate::{
+    handlers::metrics::bounded_param,
+    middleware::auth::AuthUser,
+    models::{
+        application::{ApplicationResponse, ApplicationStatus},
+        notification::NotificationChannel,
+    },
+    services::{
+        notification::{next_reminder_run, NotificationService, StaleApplication},
+        settings::NOTIFICATIONS_ENABLED,
+    },
+    utils::{errors::AppError, messages},
+    AppState,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct NotificationQuery {
+    /// Overrides the per-status thresholds in `stale_thresholds`
     pub days: Option<i32>,
+    /// Report who would be notified without sending anything
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -17,46 +36,160 @@ pub struct NotificationResponse {
     pub message: String,
     pub processed_users: usize,
     pub total_stale_applications: usize,
+    pub dry_run: bool,
+    /// Per-user breakdown, only filled in for dry runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipients: Option<Vec<NotificationRecipient>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationRecipient {
+    pub user_id: i32,
+    pub email: String,
+    pub applications: Vec<ApplicationResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StalePreviewQuery {
+    /// Overrides the per-status thresholds in `stale_thresholds`
+    pub days: Option<i32>,
+}
+
+/// Why an application counts as stale
+#[derive(Debug, Serialize)]
+pub struct StaleReason {
+    pub status: ApplicationStatus,
+    pub days_since_activity: i32,
+    /// Threshold that was exceeded
+    pub threshold_days: i32,
+    /// Whether `threshold_days` came from the `days` override rather than `stale_thresholds`
+    pub threshold_overridden: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaleApplicationPreview {
+    #[serde(flatten)]
+    pub application: ApplicationResponse,
+    pub reason: StaleReason,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaleUserPreview {
+    pub user_id: i32,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub stale_count: usize,
+    pub applications: Vec<StaleApplicationPreview>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StalePreviewResponse {
+    pub total_users: usize,
+    pub total_applications: usize,
+    pub users: Vec<StaleUserPreview>,
+}
+
+/// Largest `days` override accepted, two years
+const MAX_STALE_DAYS: i32 = 730;
+
+/// The `days` override, a 400 when outside `1..=MAX_STALE_DAYS`
+fn stale_days(days: Option<i32>) -> Result<Option<i32>, AppError> {
+    days.map(|days| bounded_param("days", Some(days), days, 1, MAX_STALE_DAYS))
+        .transpose()
+}
+
+/// Look-ahead of `/notifications/status` when the request doesn't set one
+const DEFAULT_STATUS_WITHIN_DAYS: i32 = 3;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct NotificationStatusQuery {
+    /// Count applications that go stale within this many days
+    #[validate(range(min = 1, max = 30))]
+    pub within_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationStatusResponse {
+    /// Already past their threshold; the next reminder will list them
+    pub stale_count: usize,
+    /// Not stale yet, but will be within `within_days` without new activity
+    pub nearly_stale_count: usize,
+    pub within_days: i32,
+    /// `None` when reminders are switched off
+    #[serde(with = "crate::utils::time::rfc3339::option")]
+    pub next_reminder_at: Option<DateTime<Utc>>,
+}
+
+fn notification_error(error: anyhow::Error) -> AppError {
+    tracing::error!("Notification processing failed: {:?}", error);
+    AppError::InternalServerError(messages::text("notifications.process_failed"))
+}
+
+fn stale_description(days: Option<i32>) -> String {
+    match days {
+        Some(days) => format!("applications older than {} days", days),
+        None => "applications past their status thresholds".to_string(),
+    }
 }
 
 pub async fn trigger_notifications(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<NotificationQuery>,
-) -> Result<Json<NotificationResponse>, StatusCode> {
+) -> Result<Json<NotificationResponse>, AppError> {
     // Only admins can trigger notifications
     if !auth_user.is_admin() {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(AppError::Forbidden(messages::text(
+            "notifications.admin_only_trigger",
+        )));
     }
 
     let notification_service = NotificationService::new(state.db.clone());
-    let days = query.days.unwrap_or(7);
+    let days = stale_days(query.days)?;
 
-    // Get stale applications for counting
-    let stale_applications = notification_service
-        .find_stale_applications(days)
+    let grouped = notification_service
+        .stale_applications_by_user(days, auth_user.cohort_scope())
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(notification_error)?;
 
-    // Group by user to count unique users
-    let mut unique_users = std::collections::HashSet::new();
-    for app in &stale_applications {
-        unique_users.insert(app.user_id);
+    let processed_users = grouped.len();
+    let total_stale_applications = grouped.iter().map(|(_, apps)| apps.len()).sum();
+
+    if query.dry_run {
+        let recipients = grouped
+            .into_iter()
+            .map(|(user, applications)| NotificationRecipient {
+                user_id: user.id,
+                email: user.email,
+                applications: applications
+                    .into_iter()
+                    .map(ApplicationResponse::from)
+                    .collect(),
+            })
+            .collect();
+
+        return Ok(Json(NotificationResponse {
+            message: format!("Dry run: nothing sent for {}", stale_description(days)),
+            processed_users,
+            total_stale_applications,
+            dry_run: true,
+            recipients: Some(recipients),
+        }));
     }
 
-    // Process notifications
+    // Delivery happens in the outbox worker
     notification_service
-        .process_stale_notifications_with_days(days)
+        .enqueue_notifications(&grouped)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(notification_error)?;
 
     Ok(Json(NotificationResponse {
-        message: format!(
-            "Notifications processed for applications older than {} days",
-            days
-        ),
-        processed_users: unique_users.len(),
-        total_stale_applications: stale_applications.len(),
+        message: format!("Notifications queued for {}", stale_description(days)),
+        processed_users,
+        total_stale_applications,
+        dry_run: false,
+        recipients: None,
     }))
 }
 
@@ -64,29 +197,219 @@ pub async fn get_stale_applications(
     State(state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<NotificationQuery>,
-) -> Result<Json<Vec<crate::models::application::ApplicationResponse>>, StatusCode> {
+) -> Result<Json<Vec<ApplicationResponse>>, AppError> {
     // Allow both admins and students to view their own stale applications
     let notification_service = NotificationService::new(state.db.clone());
-    let days = query.days.unwrap_or(7);
+    let days = stale_days(query.days)?;
 
     let stale_applications = if auth_user.is_admin() {
-        // Admin can see all stale applications
+        // Admins see stale applications within their cohort scope
         notification_service
-            .find_stale_applications(days)
+            .find_stale_applications(days, auth_user.cohort_scope())
             .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .map_err(notification_error)?
     } else {
         // Students can only see their own stale applications
         notification_service
             .find_user_stale_applications(auth_user.user_id, days)
             .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .map_err(notification_error)?
     };
 
-    let responses: Vec<crate::models::application::ApplicationResponse> = stale_applications
+    let responses: Vec<ApplicationResponse> = stale_applications
         .into_iter()
-        .map(crate::models::application::ApplicationResponse::from)
+        .map(ApplicationResponse::from)
         .collect();
 
     Ok(Json(responses))
 }
+
+/// How many of the caller's applications are stale or about to be, and when
+/// the next reminder goes out
+pub async fn get_notification_status(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<NotificationStatusQuery>,
+) -> Result<Json<NotificationStatusResponse>, AppError> {
+    query.validate()?;
+    let within_days = query.within_days.unwrap_or(DEFAULT_STATUS_WITHIN_DAYS);
+
+    let notification_service = NotificationService::new(state.db.clone());
+    // Both checks measure from the same instant so no application is counted twice
+    let now = Utc::now();
+    let (stale, upcoming) = tokio::try_join!(
+        notification_service.find_user_stale_applications_at(auth_user.user_id, None, now),
+        notification_service.find_user_stale_applications_at(
+            auth_user.user_id,
+            None,
+            now + Duration::days(within_days as i64),
+        ),
+    )
+    .map_err(notification_error)?;
+
+    let stale_ids: HashSet<i32> = stale.iter().map(|application| application.id).collect();
+    let nearly_stale_count = upcoming
+        .iter()
+        .filter(|application| !stale_ids.contains(&application.id))
+        .count();
+
+    let enabled = state
+        .settings
+        .read()
+        .unwrap()
+        .get::<bool>(NOTIFICATIONS_ENABLED)
+        .unwrap_or(true);
+
+    Ok(Json(NotificationStatusResponse {
+        stale_count: stale.len(),
+        nearly_stale_count,
+        within_days,
+        next_reminder_at: if enabled { next_reminder_run() } else { None },
+    }))
+}
+
+/// Who the next notification run would remind and why, for review before triggering it
+pub async fn preview_stale_notifications(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<StalePreviewQuery>,
+) -> Result<Json<StalePreviewResponse>, AppError> {
+    if !auth_user.is_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "notifications.admin_only_preview",
+        )));
+    }
+
+    let days = stale_days(query.days)?;
+    let notification_service = NotificationService::new(state.db.clone());
+    let stale = notification_service
+        .find_stale_applications_with_reasons(days, auth_user.cohort_scope())
+        .await
+        .map_err(notification_error)?;
+
+    let total_applications = stale.len();
+
+    // Rows arrive ordered by owner, so each user's applications are contiguous
+    let mut users: Vec<StaleUserPreview> = Vec::new();
+    for row in stale {
+        let StaleApplication {
+            application,
+            owner_email,
+            owner_first_name,
+            owner_last_name,
+            days_since_activity,
+            threshold_days,
+        } = row;
+
+        let preview = StaleApplicationPreview {
+            reason: StaleReason {
+                status: application.status.clone(),
+                days_since_activity,
+                threshold_days,
+                threshold_overridden: days.is_some(),
+            },
+            application: ApplicationResponse::from(application),
+        };
+
+        match users.last_mut() {
+            Some(user) if user.user_id == preview.application.user_id => {
+                user.applications.push(preview);
+            }
+            _ => users.push(StaleUserPreview {
+                user_id: preview.application.user_id,
+                email: owner_email,
+                first_name: owner_first_name,
+                last_name: owner_last_name,
+                stale_count: 0,
+                applications: vec![preview],
+            }),
+        }
+    }
+    for user in &mut users {
+        user.stale_count = user.applications.len();
+    }
+
+    Ok(Json(StalePreviewResponse {
+        total_users: users.len(),
+        total_applications,
+        users,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct TestNotificationRequest {
+    /// Defaults to the calling admin's own address
+    #[validate(email)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestNotificationResponse {
+    pub delivered: bool,
+    pub recipient: String,
+    pub channel: NotificationChannel,
+    pub error: Option<String>,
+}
+
+/// Sends a sample notification to check the delivery configuration
+pub async fn test_notification(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    payload: Option<Json<TestNotificationRequest>>,
+) -> Result<Json<TestNotificationResponse>, AppError> {
+    if !auth_user.is_admin() {
+        return Err(AppError::Forbidden(messages::text(
+            "notifications.admin_only_test",
+        )));
+    }
+
+    let Json(payload) = payload.unwrap_or_default();
+    payload.validate()?;
+
+    let recipient = match payload.email {
+        Some(email) => email,
+        None => {
+            sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = $1")
+                .bind(auth_user.user_id)
+                .fetch_one(&state.db)
+                .await?
+        }
+    };
+
+    let notification_service = NotificationService::new(state.db.clone());
+    let result = notification_service
+        .send_test_notification(&recipient)
+        .await;
+
+    Ok(Json(TestNotificationResponse {
+        delivered: result.is_ok(),
+        recipient,
+        channel: notification_service.channel(),
+        error: result.err().map(|e| e.to_string()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_days_override_is_optional() {
+        assert_eq!(stale_days(None).unwrap(), None);
+        assert_eq!(stale_days(Some(14)).unwrap(), Some(14));
+        assert_eq!(
+            stale_days(Some(MAX_STALE_DAYS)).unwrap(),
+            Some(MAX_STALE_DAYS)
+        );
+    }
+
+    #[test]
+    fn stale_days_out_of_range_is_rejected() {
+        for days in [0, -5, MAX_STALE_DAYS + 1, i32::MAX] {
+            assert!(matches!(
+                stale_days(Some(days)),
+                Err(AppError::ValidationError(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use axum::{
+    extract::{
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Extension, Query, State,
+    },
+    response::Response,
+};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    middleware::{auth::session_still_valid, client_ip::ClientIp},
+    models::user::UserRole,
+    services::events::AppEvent,
+    utils::{errors::AppError, jwt::verify_jwt, logger::LOGGER, messages},
+    AppState,
+};
+
+/// Interval between server pings
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// Close the socket when the client has been silent for this long
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(90);
+/// Close code sent when the session behind the socket is no longer valid
+const CLOSE_POLICY_VIOLATION: u16 = 1008;
+
+#[derive(Deserialize)]
+pub struct WsQuery {
+    token: String,
+    /// `cohort` subscribes admins to the cohort-wide feed
+    feed: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Subscription {
+    User(i32),
+    /// Admin feed restricted to a cohort; `None` is the unrestricted feed
+    Cohort(Option<i32>),
+}
+
+impl Subscription {
+    fn accepts(&self, event: &AppEvent) -> bool {
+        match self {
+            Subscription::User(user_id) => event.user_id == *user_id,
+            Subscription::Cohort(cohort_scope) => event.in_cohort_scope(*cohort_scope),
+        }
+    }
+}
+
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    client_ip: Option<Extension<ClientIp>>,
+    Query(params): Query<WsQuery>,
+) -> Result<Response, AppError> {
+    // Browsers can't set headers on WebSocket requests, so the token comes in the query
+    let claims = verify_jwt(&params.token, &state.jwt_keys)
+        .map_err(|_| AppError::Unauthorized(messages::text("auth.invalid_token")))?;
+
+    // Impersonated requests are audited one by one in the auth middleware,
+    // which a long-lived socket would bypass
+    if claims.impersonated_by.is_some() {
+        return Err(AppError::Forbidden(messages::text(
+            "auth.impersonation_not_allowed",
+        )));
+    }
+
+    // A revoked token must not open a long-lived connection either
+    let current_version = state
+        .token_versions
+        .current_version(&state.db, claims.sub)
+        .await?;
+    if current_version != Some(claims.token_version) {
+        return Err(AppError::Unauthorized(messages::text("auth.invalid_token")));
+    }
+
+    let subscription = match params.feed.as_deref() {
+        None | Some("user") => Subscription::User(claims.sub),
+        Some("cohort") => {
+            // The admin routes' role and network checks, which this route sits outside of
+            let role = state.role_cache.current_role(&state.db, claims.sub).await?;
+            if claims.role != "admin" || !matches!(role, Some(UserRole::Admin)) {
+                return Err(AppError::Forbidden(messages::text(
+                    "realtime.admin_only_cohort_feed",
+                )));
+            }
+            if let Some(allowlist) = &state.admin_ip_allowlist {
+                let client_ip = client_ip.map(|Extension(ClientIp(ip))| ip);
+                if !client_ip.is_some_and(|ip| allowlist.allows(ip)) {
+                    return Err(AppError::Forbidden(messages::text(
+                        "realtime.admin_ip_not_allowed",
+                    )));
+                }
+            }
+            Subscription::Cohort(claims.cohort_id)
+        }
+        Some(_) => {
+            return Err(AppError::BadRequest(messages::text(
+                "realtime.unknown_feed",
+            )))
+        }
+    };
+
+    let user_id = claims.sub;
+    let token_version = claims.token_version;
+    Ok(ws.on_upgrade(move |socket| {
+        handle_socket(socket, state, user_id, token_version, subscription)
+    }))
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    state: AppState,
+    user_id: i32,
+    token_version: i32,
+    subscription: Subscription,
+) {
+    let mut events = match subscription {
+        Subscription::User(id) => state.events.subscribe_user(id),
+        Subscription::Cohort(_) => state.events.subscribe_all(),
+    };
+
+    LOGGER.log_business_event("websocket_connected", Some(user_id), Default::default());
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut last_seen = Instant::now();
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => last_seen = Instant::now(),
+            },
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if !subscription.accepts(&event) {
+                        continue;
+                    }
+                    let Ok(payload) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(payload)).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client missed some events; keep streaming the newer ones
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > CLIENT_TIMEOUT {
+                    break;
+                }
+                // Sessions revoked or admins demoted since the upgrade lose the socket
+                let require_admin = matches!(subscription, Subscription::Cohort(_));
+                let valid = session_still_valid(&state, user_id, token_version, require_admin).await;
+                if !matches!(valid, Ok(true)) {
+                    let _ = socket
+                        .send(Message::Close(Some(CloseFrame {
+                            code: CLOSE_POLICY_VIOLATION,
+                            reason: "session revoked".into(),
+                        })))
+                        .await;
+                    break;
+                }
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Drop the receiver first so the user channel can be released
+    drop(events);
+    if let Subscription::User(id) = subscription {
+        state.events.release_user(id);
+    }
+
+    LOGGER.log_business_event("websocket_disconnected", Some(user_id), Default::default());
+}
@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Extension, State},
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    middleware::auth::AuthUser,
+    services::scheduler::{job_statuses, missed_run_grace, JobStatus, RunOutcome},
+    utils::{errors::AppError, logger::LOGGER, messages},
+    AppState,
+};
+
+#[derive(Debug, Serialize)]
+pub struct SchedulerStatusResponse {
+    /// No job missed a run and none failed its last one
+    pub healthy: bool,
+    pub jobs: Vec<JobStatus>,
+    pub missed_run_grace_minutes: i64,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Last run and outcome of every background job.
+///
+/// A job is `missed` when its next run is overdue by more than
+/// `SCHEDULER_MISSED_RUN_GRACE_MINUTES`, e.g. because the scheduler task died.
+/// Each missed job is also logged as a `scheduler_run_missed` event to alert on.
+pub async fn get_scheduler_status(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<SchedulerStatusResponse>, AppError> {
+    if !auth_user.is_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_scheduler_status",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text("scheduler.admin_only")));
+    }
+
+    let now = Utc::now();
+    let grace = missed_run_grace();
+    let jobs = job_statuses(&state.db, now, grace).await?;
+
+    for job in jobs.iter().filter(|job| job.missed) {
+        LOGGER.log_business_event(
+            "scheduler_run_missed",
+            Some(auth_user.user_id),
+            [
+                (
+                    "job".to_string(),
+                    serde_json::Value::String(job.job.clone()),
+                ),
+                (
+                    "next_due_at".to_string(),
+                    job.next_due_at
+                        .map(|due| serde_json::Value::String(due.to_rfc3339()))
+                        .unwrap_or(serde_json::Value::Null),
+                ),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+    }
+
+    let healthy = jobs
+        .iter()
+        .all(|job| !job.missed && job.last_outcome != Some(RunOutcome::Failed));
+
+    Ok(Json(SchedulerStatusResponse {
+        healthy,
+        jobs,
+        missed_run_grace_minutes: grace.num_minutes(),
+        checked_at: now,
+    }))
+}
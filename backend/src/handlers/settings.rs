@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Extension, State},
+    response::Json,
+};
+use std::collections::HashMap;
+
+use crate::{
+    middleware::auth::AuthUser,
+    models::setting::{SettingsResponse, UpdateSettingsRequest},
+    services::settings::{is_valid_key, save_settings, value_errors, Settings},
+    utils::{errors::AppError, logger::LOGGER, messages},
+    AppState,
+};
+
+pub async fn get_settings(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<SettingsResponse>, AppError> {
+    if !auth_user.is_admin() {
+        return Err(AppError::Forbidden(messages::text("settings.admin_only")));
+    }
+
+    let settings = state.settings.read().unwrap().all();
+    Ok(Json(SettingsResponse { settings }))
+}
+
+pub async fn update_settings(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<UpdateSettingsRequest>,
+) -> Result<Json<SettingsResponse>, AppError> {
+    // Settings apply to every cohort, so only super-admins may change them
+    if !auth_user.is_super_admin() {
+        LOGGER.log_business_event(
+            "unauthorized_settings_update",
+            Some(auth_user.user_id),
+            [(
+                "role".to_string(),
+                serde_json::Value::String(auth_user.role_str().to_string()),
+            )]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(AppError::Forbidden(messages::text(
+            "settings.super_admin_only_update",
+        )));
+    }
+
+    let problems: Vec<String> = payload
+        .settings
+        .iter()
+        .flat_map(|(key, value)| {
+            if is_valid_key(key) {
+                value_errors(key, value)
+            } else {
+                vec![format!("Invalid setting key '{}'", key)]
+            }
+        })
+        .collect();
+    if !problems.is_empty() {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "settings".to_string(),
+            problems.into_iter().map(Into::into).collect(),
+        );
+        return Err(AppError::ValidationError(errors));
+    }
+
+    let changed = save_settings(&state.db, &payload.settings, auth_user.user_id).await?;
+
+    // Reload rather than patch so the snapshot matches what was committed
+    let settings = Settings::load(&state.db).await?;
+    let response = SettingsResponse {
+        settings: settings.all(),
+    };
+    *state.settings.write().unwrap() = settings;
+
+    LOGGER.log_business_event(
+        "settings_updated",
+        Some(auth_user.user_id),
+        [
+            (
+                "keys".to_string(),
+                serde_json::Value::Array(
+                    payload
+                        .settings
+                        .keys()
+                        .cloned()
+                        .map(serde_json::Value::String)
+                        .collect(),
+                ),
+            ),
+            (
+                "changed".to_string(),
+                serde_json::Value::Number(serde_json::Number::from(changed)),
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(response))
+}
@@ -0,0 +1,213 @@
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::{
+    middleware::auth::AuthUser,
+    models::webhook::{
+        CreateWebhookRequest, CreatedWebhookResponse, Webhook, WebhookDeliveriesQuery,
+        WebhookDelivery,
+    },
+    services::webhooks::{is_valid_webhook_url, resolve_public_target, to_hex},
+    utils::{errors::AppError, logger::LOGGER, messages, pagination::Pagination},
+    AppState,
+};
+
+/// Random bytes in a generated signing secret
+const SECRET_BYTES: usize = 32;
+
+/// Everything but the secret
+const WEBHOOK_COLUMNS: &str = "id, cohort_id, url, event_types, active, created_by, created_at";
+
+/// Webhooks send data out of the system, so only super-admins manage them
+fn require_super_admin(auth_user: &AuthUser, action: &str) -> Result<(), AppError> {
+    if auth_user.is_super_admin() {
+        return Ok(());
+    }
+
+    LOGGER.log_business_event(
+        "unauthorized_webhook_access",
+        Some(auth_user.user_id),
+        [(
+            "action".to_string(),
+            serde_json::Value::String(action.to_string()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+    Err(AppError::Forbidden(messages::text(
+        "webhooks.super_admin_only",
+    )))
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<Webhook>>, AppError> {
+    require_super_admin(&auth_user, "list")?;
+
+    let webhooks = sqlx::query_as::<_, Webhook>(&format!(
+        "SELECT {} FROM webhooks ORDER BY id",
+        WEBHOOK_COLUMNS
+    ))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(webhooks))
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<Json<CreatedWebhookResponse>, AppError> {
+    require_super_admin(&auth_user, "create")?;
+
+    payload.validate()?;
+    if !is_valid_webhook_url(&payload.url) {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "url".to_string(),
+            vec![messages::text("webhooks.invalid_url")],
+        );
+        return Err(AppError::ValidationError(errors));
+    }
+    if let Err(reason) = resolve_public_target(&payload.url).await {
+        tracing::warn!("Rejected webhook URL {}: {}", payload.url, reason);
+        let mut errors = HashMap::new();
+        errors.insert(
+            "url".to_string(),
+            vec![messages::text("webhooks.private_url")],
+        );
+        return Err(AppError::ValidationError(errors));
+    }
+
+    if let Some(cohort_id) = payload.cohort_id {
+        let exists =
+            sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM cohorts WHERE id = $1)")
+                .bind(cohort_id)
+                .fetch_one(&state.db)
+                .await?;
+        if !exists {
+            return Err(AppError::BadRequest(messages::text("cohorts.unknown")));
+        }
+    }
+
+    let mut secret = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut secret);
+    let secret = to_hex(&secret);
+
+    let mut event_types: Vec<&str> = payload.event_types.iter().map(|e| e.as_str()).collect();
+    event_types.sort_unstable();
+    event_types.dedup();
+
+    let webhook = sqlx::query_as::<_, Webhook>(&format!(
+        r#"
+        INSERT INTO webhooks (cohort_id, url, secret, event_types, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {}
+        "#,
+        WEBHOOK_COLUMNS
+    ))
+    .bind(payload.cohort_id)
+    .bind(&payload.url)
+    .bind(&secret)
+    .bind(&event_types)
+    .bind(auth_user.user_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    LOGGER.log_business_event(
+        "webhook_created",
+        Some(auth_user.user_id),
+        [
+            (
+                "webhook_id".to_string(),
+                serde_json::Value::Number(webhook.id.into()),
+            ),
+            (
+                "url".to_string(),
+                serde_json::Value::String(webhook.url.clone()),
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(Json(CreatedWebhookResponse { webhook, secret }))
+}
+
+/// Removes the webhook along with its delivery log
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    require_super_admin(&auth_user, "delete")?;
+
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(messages::text("webhooks.not_found")));
+    }
+
+    LOGGER.log_business_event(
+        "webhook_deleted",
+        Some(auth_user.user_id),
+        [(
+            "webhook_id".to_string(),
+            serde_json::Value::Number(id.into()),
+        )]
+        .iter()
+        .cloned()
+        .collect(),
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delivery log of one webhook, newest first
+pub async fn get_webhook_deliveries(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<i32>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+    pagination: Pagination,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    require_super_admin(&auth_user, "deliveries")?;
+
+    let exists =
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM webhooks WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&state.db)
+            .await?;
+    if !exists {
+        return Err(AppError::NotFound(messages::text("webhooks.not_found")));
+    }
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(id)
+    .bind(&query.status)
+    .bind(pagination.limit)
+    .bind(pagination.offset)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(deliveries))
+}
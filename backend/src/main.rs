@@ -7,26 +7,68 @@ mod utils;
 use axum::{
     extract::DefaultBodyLimit,
     http::{HeaderValue, Method},
-    middleware::from_fn_with_state,
+    middleware::{from_fn, from_fn_with_state},
     routing::{get, post},
     Router,
 };
 use sqlx::PgPool;
 use std::env;
-use tower_http::cors::CorsLayer;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    handlers::{admin, applications, auth, files, metrics, notifications},
-    middleware::auth::auth_middleware,
-    utils::database::create_pool,
+    handlers::{
+        admin, applications, auth, cohorts, companies, dashboard, files, health, maintenance,
+        metrics, notifications, realtime, scheduler, settings, webhooks,
+    },
+    middleware::{
+        api_version::deprecated_alias_middleware,
+        auth::{auth_middleware, verify_role_middleware, RoleCache, TokenVersionCache},
+        cache_control::no_store_by_default_middleware,
+        client_ip::{client_ip_middleware, ClientIpResolver},
+        ip_allowlist::{admin_ip_allowlist_middleware, AdminIpAllowlist},
+        locale::localize_errors_middleware,
+        rate_limit::{rate_limit_middleware, RateLimiter},
+        request_id::request_id_middleware,
+        slow_request::{slow_request_middleware, SlowRequestThreshold},
+        timeout::{timeout_middleware, RequestTimeouts},
+    },
+    services::{
+        cache::CacheService,
+        events::EventBus,
+        settings::Settings,
+        storage::{file_store_from_env, FileStore},
+        transcode::Transcoder,
+        webhooks::WebhookDispatcher,
+    },
+    utils::{
+        concurrency::ConcurrencyLimit, database::create_pool, encryption::FileCipher, jwt::JwtKeys,
+    },
 };
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-    pub jwt_secret: String,
+    pub jwt_keys: Arc<JwtKeys>,
+    /// Staging directory for uploads before they move into `files`
     pub upload_dir: String,
+    pub files: Arc<dyn FileStore>,
+    pub file_cipher: Option<Arc<FileCipher>>,
+    /// Set when `FFMPEG_PATH` is configured; previews are disabled otherwise
+    pub transcoder: Option<Arc<Transcoder>>,
+    pub events: Arc<EventBus>,
+    pub role_cache: Arc<RoleCache>,
+    pub token_versions: Arc<TokenVersionCache>,
+    /// `None` when `ADMIN_IP_ALLOWLIST` is unset
+    pub admin_ip_allowlist: Option<Arc<AdminIpAllowlist>>,
+    /// Shared so the in-memory layer survives across requests
+    pub cache: Arc<CacheService>,
+    /// Runtime settings, reloaded whenever an admin changes them
+    pub settings: Arc<RwLock<Settings>>,
+    /// Bounds concurrent comprehensive-analytics computations
+    pub analytics_limit: Arc<ConcurrencyLimit>,
 }
 
 #[tokio::main]
@@ -42,7 +84,7 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+    let jwt_keys = Arc::new(JwtKeys::from_env()?);
     let upload_dir = env::var("UPLOAD_DIR").unwrap_or_else(|_| "./storage/uploads".to_string());
 
     // Create upload directory if it doesn't exist
@@ -54,38 +96,197 @@ async fn main() -> anyhow::Result<()> {
 
     sqlx::migrate!("./migrations").run(&db).await?;
 
+    let settings = Settings::load(&db).await?;
+
     let state = AppState {
+        cache: Arc::new(CacheService::from_env(db.clone())),
         db,
-        jwt_secret,
+        jwt_keys,
+        files: file_store_from_env(&upload_dir)?,
+        file_cipher: FileCipher::from_env()?.map(Arc::new),
+        transcoder: Transcoder::from_env(&upload_dir).map(Arc::new),
         upload_dir,
+        events: Arc::new(EventBus::new()),
+        role_cache: Arc::new(RoleCache::from_env()),
+        token_versions: Arc::new(TokenVersionCache::from_env()),
+        admin_ip_allowlist: AdminIpAllowlist::from_env()?.map(Arc::new),
+        settings: Arc::new(RwLock::new(settings)),
+        analytics_limit: Arc::new(ConcurrencyLimit::from_env("ANALYTICS")),
     };
 
     let cors_origin = env::var("CORS_ALLOWED_ORIGIN")
         .unwrap_or_else(|_| "http://localhost:3000".to_string());
     
-    let cors = if cors_origin == "*" {
-        // Allow any origin for production flexibility
-        CorsLayer::new()
-            .allow_origin(axum::http::header::HeaderValue::from_static("*"))
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([
-                axum::http::header::CONTENT_TYPE,
-                axum::http::header::AUTHORIZATION,
-            ])
+    // The layer answers preflights itself; listing OPTIONS only advertises it
+    let cors_methods = env::var("CORS_ALLOWED_METHODS")
+        .unwrap_or_else(|_| "GET,POST,PUT,PATCH,DELETE,HEAD,OPTIONS".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|method| !method.is_empty())
+        .map(|method| Method::from_bytes(method.to_ascii_uppercase().as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let cors_credentials = env::var("CORS_ALLOW_CREDENTIALS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+
+    // Browsers reject credentialed responses for a wildcard origin, so fail here
+    // rather than on every request
+    if cors_origin == "*" && cors_credentials {
+        anyhow::bail!(
+            "CORS_ALLOW_CREDENTIALS cannot be combined with CORS_ALLOWED_ORIGIN=*; set a specific origin"
+        );
+    }
+
+    let cors = CorsLayer::new()
+        .allow_origin(if cors_origin == "*" {
+            // Allow any origin for production flexibility
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::exact(cors_origin.parse::<HeaderValue>()?)
+        })
+        .allow_methods(cors_methods)
+        .allow_headers([
+            axum::http::header::CONTENT_TYPE,
+            axum::http::header::AUTHORIZATION,
+        ])
+        .allow_credentials(cors_credentials);
+
+    // Token download links are embedded as media elsewhere, so they get their own
+    // origins (defaulting to the API's) and the headers range requests need
+    let media_cors_origins =
+        env::var("MEDIA_CORS_ALLOWED_ORIGINS").unwrap_or_else(|_| cors_origin.clone());
+    let media_cors_origin = if media_cors_origins.trim() == "*" {
+        AllowOrigin::any()
     } else {
-        // Specific origin
-        CorsLayer::new()
-            .allow_origin(cors_origin.parse::<HeaderValue>()?)
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-            .allow_headers([
-                axum::http::header::CONTENT_TYPE,
-                axum::http::header::AUTHORIZATION,
-            ])
+        AllowOrigin::list(
+            media_cors_origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(|origin| origin.parse::<HeaderValue>())
+                .collect::<Result<Vec<_>, _>>()?,
+        )
     };
+    let media_cors = CorsLayer::new()
+        .allow_origin(media_cors_origin)
+        .allow_methods([Method::GET, Method::HEAD])
+        .allow_headers([axum::http::header::RANGE])
+        .expose_headers([
+            axum::http::header::ACCEPT_RANGES,
+            axum::http::header::CONTENT_DISPOSITION,
+            axum::http::header::CONTENT_LENGTH,
+            axum::http::header::CONTENT_RANGE,
+        ]);
+
+    let rate_limiter = Arc::new(RateLimiter::from_env());
+    rate_limiter.spawn_sweeper();
+
+    let mut admin_routes = Router::new()
+        .route("/admin/analytics", get(admin::get_analytics))
+        .route("/admin/analytics/refresh", post(admin::refresh_analytics))
+        .route("/admin/dashboard", get(dashboard::get_admin_dashboard))
+        .route("/admin/maintenance/backfill", post(maintenance::backfill))
+        .route("/admin/students", get(admin::get_all_students))
+        .route("/admin/applications", get(admin::get_all_applications))
+        .route("/admin/activity", get(admin::get_admin_activity))
+        .route("/admin/activity/stream", get(admin::stream_admin_activity))
+        .route(
+            "/admin/users/:user_id/activity",
+            get(admin::get_user_activity_admin),
+        )
+        .route("/admin/cohorts", get(cohorts::list_cohorts))
+        .route("/admin/cohorts", post(cohorts::create_cohort))
+        .route(
+            "/admin/users/:user_id/cohort",
+            axum::routing::put(cohorts::assign_user_cohort),
+        )
+        .route("/admin/metrics", get(metrics::get_anonymous_metrics))
+        .route(
+            "/admin/metrics/timeseries",
+            get(metrics::get_metrics_timeseries),
+        )
+        .route("/admin/cache-stats", get(metrics::get_cache_stats))
+        .route("/admin/cache-invalidate", post(metrics::invalidate_cache))
+        .route("/admin/cache-warm", post(metrics::warm_cache))
+        .route("/admin/cache/export", get(metrics::export_cache))
+        .route("/admin/cache/import", post(metrics::import_cache))
+        .route("/admin/cache/:key", get(metrics::inspect_cache_entry))
+        .route("/admin/file-access", get(files::get_file_access_log))
+        .route(
+            "/admin/notifications/trigger",
+            post(notifications::trigger_notifications),
+        )
+        .route(
+            "/admin/notifications/preview",
+            get(notifications::preview_stale_notifications),
+        )
+        .route(
+            "/admin/notifications/test",
+            post(notifications::test_notification),
+        )
+        .route("/admin/register", post(auth::register_admin))
+        .route(
+            "/admin/password-migration-status",
+            get(admin::get_password_migration_status),
+        )
+        .route(
+            "/admin/password-migration/flag-legacy",
+            post(admin::flag_legacy_passwords),
+        )
+        .route(
+            "/admin/users/:id/revoke-sessions",
+            post(admin::revoke_user_sessions),
+        )
+        .route("/admin/impersonate/:user_id", post(admin::impersonate_user))
+        .route(
+            "/admin/applications/:id/reassign",
+            post(admin::reassign_application),
+        )
+        .route(
+            "/admin/users/:id/recordings.zip",
+            get(files::download_user_recordings),
+        )
+        .route(
+            "/admin/scheduler/status",
+            get(scheduler::get_scheduler_status),
+        )
+        .route("/admin/settings", get(settings::get_settings))
+        .route(
+            "/admin/settings",
+            axum::routing::put(settings::update_settings),
+        )
+        .route(
+            "/admin/webhooks",
+            get(webhooks::list_webhooks).post(webhooks::create_webhook),
+        )
+        .route(
+            "/admin/webhooks/:id",
+            axum::routing::delete(webhooks::delete_webhook),
+        )
+        .route(
+            "/admin/webhooks/:id/deliveries",
+            get(webhooks::get_webhook_deliveries),
+        )
+        .layer(from_fn_with_state(state.clone(), verify_role_middleware));
+
+    let client_ip_resolver = Arc::new(ClientIpResolver::from_env()?);
+    if let Some(allowlist) = state.admin_ip_allowlist.clone() {
+        admin_routes =
+            admin_routes.layer(from_fn_with_state(allowlist, admin_ip_allowlist_middleware));
+    }
 
     let protected_routes = Router::new()
         .route("/applications", get(applications::get_applications))
         .route("/applications", post(applications::create_application))
+        .route("/applications/count", get(applications::count_applications))
+        .route(
+            "/applications/status-transitions",
+            get(applications::get_status_transitions),
+        )
+        .route(
+            "/applications/batch",
+            post(applications::get_applications_batch),
+        )
         .route("/applications/:id", get(applications::get_application))
         .route(
             "/applications/:id",
@@ -95,6 +296,14 @@ async fn main() -> anyhow::Result<()> {
             "/applications/:id",
             axum::routing::delete(applications::delete_application),
         )
+        .route(
+            "/applications/:id/clone",
+            post(applications::clone_application),
+        )
+        .route(
+            "/applications/:id/timeline",
+            get(applications::get_application_timeline),
+        )
         .route(
             "/applications/:id/screening",
             post(applications::upload_screening),
@@ -104,40 +313,92 @@ async fn main() -> anyhow::Result<()> {
             post(applications::upload_interview),
         )
         .route(
-            "/applications/activity",
-            get(applications::get_user_activity),
+            "/applications/:id/documents",
+            get(applications::get_documents).post(applications::upload_document),
         )
-        .route("/admin/analytics", get(admin::get_analytics))
-        .route("/admin/students", get(admin::get_all_students))
-        .route("/admin/applications", get(admin::get_all_applications))
-        .route("/admin/activity", get(admin::get_admin_activity))
         .route(
-            "/admin/users/:user_id/activity",
-            get(admin::get_user_activity_admin),
+            "/applications/:id/validate-upload",
+            post(applications::validate_upload),
         )
-        .route("/admin/metrics", get(metrics::get_anonymous_metrics))
-        .route("/admin/cache-stats", get(metrics::get_cache_stats))
-        .route("/admin/cache-invalidate", post(metrics::invalidate_cache))
-        .route("/admin/cache-warm", post(metrics::warm_cache))
         .route(
-            "/admin/notifications/trigger",
-            post(notifications::trigger_notifications),
+            "/applications/:id/download-all",
+            get(files::download_all_recordings),
+        )
+        .route(
+            "/applications/activity",
+            get(applications::get_user_activity),
         )
-        .route("/admin/register", post(auth::register_admin))
+        .route(
+            "/applications/benchmark",
+            get(metrics::get_student_benchmark),
+        )
+        .route("/companies/suggest", get(companies::suggest_companies))
+        .route("/dashboard", get(dashboard::get_dashboard))
+        .route("/auth/revoke-all-sessions", post(auth::revoke_all_sessions))
+        .route("/auth/me", get(auth::me))
+        .route("/auth/onboarding/complete", post(auth::complete_onboarding))
+        .merge(admin_routes)
         .route(
             "/notifications/stale",
             get(notifications::get_stale_applications),
         )
+        .route(
+            "/notifications/status",
+            get(notifications::get_notification_status),
+        )
         .route("/files/:filename", get(files::serve_file))
+        .route("/files/:filename/preview", get(files::serve_preview))
+        .layer(from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit_middleware,
+        ))
         .layer(from_fn_with_state(state.clone(), auth_middleware));
 
-    let app = Router::new()
-        .route("/health", get(|| async { "OK" }))
+    let v1_routes = Router::new()
         .route("/auth/register", post(auth::register))
         .route("/auth/login", post(auth::login))
-        .route("/download/:filename", get(files::serve_file_with_token))
-        .merge(protected_routes)
-        .layer(cors)
+        .route("/auth/change-password", post(auth::change_password))
+        .route("/ws", get(realtime::ws_handler))
+        .merge(protected_routes);
+
+    // Breaking response shapes (the list envelope) live under /v2
+    let v2_routes = Router::new()
+        .route("/applications", get(applications::list_applications))
+        .layer(from_fn_with_state(rate_limiter, rate_limit_middleware))
+        .layer(from_fn_with_state(state.clone(), auth_middleware));
+
+    // Kept out of the API's CORS layer, which would otherwise answer their preflights
+    let media_routes =
+        Router::new().route("/download/:filename", get(files::serve_file_with_token));
+
+    let api = Router::new()
+        .route("/health", get(|| async { "OK" }))
+        .route("/ready", get(health::ready))
+        .nest("/v1", v1_routes.clone())
+        .nest("/v2", v2_routes)
+        // Unprefixed aliases of /v1, kept through the deprecation window
+        .merge(v1_routes.layer(from_fn(deprecated_alias_middleware)))
+        .layer(cors);
+
+    let media = Router::new()
+        .nest("/v1", media_routes.clone())
+        .merge(media_routes.layer(from_fn(deprecated_alias_middleware)))
+        .layer(media_cors);
+
+    let app = api
+        .merge(media)
+        .layer(from_fn(no_store_by_default_middleware))
+        .layer(from_fn(localize_errors_middleware))
+        .layer(from_fn(request_id_middleware))
+        .layer(from_fn_with_state(client_ip_resolver, client_ip_middleware))
+        .layer(from_fn_with_state(
+            RequestTimeouts::from_env(),
+            timeout_middleware,
+        ))
+        .layer(from_fn_with_state(
+            SlowRequestThreshold::from_env(),
+            slow_request_middleware,
+        ))
         .layer(DefaultBodyLimit::max(
             env::var("MAX_REQUEST_BODY_MB")
                 .unwrap_or_else(|_| "500".to_string())
@@ -149,34 +410,183 @@ async fn main() -> anyhow::Result<()> {
         .with_state(state.clone());
 
     // Start background notification scheduler
+    let scheduler_db = state.db.clone();
     let notification_db = state.db.clone();
+    let notification_settings = state.settings.clone();
+    let startup_settings = state.settings.clone();
+    let outbox_db = state.db.clone();
+    let analytics_db = state.db.clone();
+    let webhook_db = state.db.clone();
+    let webhook_dispatcher = Arc::new(WebhookDispatcher::new(state.db.clone()));
+    let analytics_cache = state.cache.clone();
     tokio::spawn(async move {
-        use crate::services::notification::NotificationService;
+        use crate::services::analytics::AnalyticsService;
+        use crate::services::notification::{
+            NotificationService, OUTBOX_BATCH_SIZE, STALE_REMINDER_SCHEDULE,
+        };
+        use crate::services::scheduler::{
+            register_jobs, run_recorded, run_stale_reminders, RunOutcome, StartupNotifications,
+            ANALYTICS_REFRESH_JOB, ANALYTICS_REFRESH_SCHEDULE, NOTIFICATION_OUTBOX_JOB,
+            NOTIFICATION_OUTBOX_SCHEDULE, STALE_REMINDERS_JOB, STARTUP_NOTIFICATIONS_DELAY_SECS,
+            WEBHOOK_DELIVERIES_JOB, WEBHOOK_DELIVERIES_SCHEDULE,
+        };
+        use crate::services::settings::NOTIFICATIONS_ENABLED;
+        use crate::services::webhooks::WEBHOOK_BATCH_SIZE;
         use tokio_cron_scheduler::{Job, JobScheduler};
 
         let sched = JobScheduler::new()
             .await
             .expect("Failed to create scheduler");
 
+        // Without the rows the status endpoint reports every job as missed
+        if let Err(e) = register_jobs(&scheduler_db).await {
+            tracing::error!("Failed to register scheduled jobs: {}", e);
+        }
+
         // Run notifications daily at 9 AM
-        let job = Job::new_async("0 0 9 * * *", move |_uuid, _l| {
+        let job = Job::new_async(STALE_REMINDER_SCHEDULE, move |_uuid, _l| {
             let db = notification_db.clone();
+            let enabled = notification_settings
+                .read()
+                .unwrap()
+                .get::<bool>(NOTIFICATIONS_ENABLED)
+                .unwrap_or(true);
             Box::pin(async move {
-                let notification_service = NotificationService::new(db);
-                if let Err(e) = notification_service.process_stale_notifications().await {
-                    tracing::error!("Failed to process notifications: {}", e);
-                } else {
-                    tracing::info!("Daily notifications processed successfully");
-                }
+                run_recorded(
+                    &db,
+                    STALE_REMINDERS_JOB,
+                    run_stale_reminders(db.clone(), enabled, false),
+                )
+                .await;
             })
         })
         .expect("Failed to create notification job");
 
         sched.add(job).await.expect("Failed to add job");
+
+        // Deliver queued notifications every minute
+        let outbox_job = Job::new_async(NOTIFICATION_OUTBOX_SCHEDULE, move |_uuid, _l| {
+            let db = outbox_db.clone();
+            Box::pin(async move {
+                let run_db = db.clone();
+                run_recorded(&run_db, NOTIFICATION_OUTBOX_JOB, async move {
+                    let notification_service = NotificationService::new(db);
+                    match notification_service.drain_outbox(OUTBOX_BATCH_SIZE).await {
+                        Ok(summary) if summary.sent + summary.retrying + summary.failed > 0 => {
+                            tracing::info!(
+                                "Notification outbox drained: {} sent, {} retrying, {} failed",
+                                summary.sent,
+                                summary.retrying,
+                                summary.failed
+                            );
+                            Ok(RunOutcome::Success)
+                        }
+                        Ok(_) => Ok(RunOutcome::Skipped),
+                        Err(e) => {
+                            tracing::error!("Failed to drain notification outbox: {}", e);
+                            Err(e.to_string())
+                        }
+                    }
+                })
+                .await;
+            })
+        })
+        .expect("Failed to create notification outbox job");
+
+        sched
+            .add(outbox_job)
+            .await
+            .expect("Failed to add notification outbox job");
+
+        // Send queued webhook deliveries every minute
+        let webhook_job = Job::new_async(WEBHOOK_DELIVERIES_SCHEDULE, move |_uuid, _l| {
+            let dispatcher = webhook_dispatcher.clone();
+            let db = webhook_db.clone();
+            Box::pin(async move {
+                run_recorded(&db, WEBHOOK_DELIVERIES_JOB, async move {
+                    match dispatcher.drain(WEBHOOK_BATCH_SIZE).await {
+                        Ok(summary)
+                            if summary.delivered + summary.retrying + summary.failed > 0 =>
+                        {
+                            tracing::info!(
+                                "Webhook deliveries drained: {} delivered, {} retrying, {} failed",
+                                summary.delivered,
+                                summary.retrying,
+                                summary.failed
+                            );
+                            Ok(RunOutcome::Success)
+                        }
+                        Ok(_) => Ok(RunOutcome::Skipped),
+                        Err(e) => {
+                            tracing::error!("Failed to drain webhook deliveries: {}", e);
+                            Err(e.to_string())
+                        }
+                    }
+                })
+                .await;
+            })
+        })
+        .expect("Failed to create webhook job");
+
+        sched
+            .add(webhook_job)
+            .await
+            .expect("Failed to add webhook job");
+
+        // Refresh the analytics materialized views every 15 minutes
+        let refresh_job = Job::new_async(ANALYTICS_REFRESH_SCHEDULE, move |_uuid, _l| {
+            let db = analytics_db.clone();
+            let cache = analytics_cache.clone();
+            Box::pin(async move {
+                let run_db = db.clone();
+                run_recorded(&run_db, ANALYTICS_REFRESH_JOB, async move {
+                    match AnalyticsService::refresh_materialized_views(&db, &cache, None).await {
+                        Ok(()) => Ok(RunOutcome::Success),
+                        Err(e) => {
+                            tracing::error!("Failed to refresh analytics views: {:?}", e);
+                            Err(format!("{:?}", e))
+                        }
+                    }
+                })
+                .await;
+            })
+        })
+        .expect("Failed to create analytics refresh job");
+
+        sched
+            .add(refresh_job)
+            .await
+            .expect("Failed to add analytics refresh job");
         sched.start().await.expect("Failed to start scheduler");
 
         tracing::info!("Notification scheduler started - running daily at 9 AM");
 
+        // Exercise the daily job once without waiting for 9 AM, e.g. during setup
+        let startup_notifications = StartupNotifications::from_env();
+        if startup_notifications != StartupNotifications::Off {
+            tokio::time::sleep(tokio::time::Duration::from_secs(
+                STARTUP_NOTIFICATIONS_DELAY_SECS,
+            ))
+            .await;
+
+            let enabled = startup_settings
+                .read()
+                .unwrap()
+                .get::<bool>(NOTIFICATIONS_ENABLED)
+                .unwrap_or(true);
+            let dry_run = startup_notifications == StartupNotifications::DryRun;
+            tracing::info!(
+                "Running daily notifications on startup{}",
+                if dry_run { " (dry run)" } else { "" }
+            );
+            run_recorded(
+                &scheduler_db,
+                STALE_REMINDERS_JOB,
+                run_stale_reminders(scheduler_db.clone(), enabled, dry_run),
+            )
+            .await;
+        }
+
         // Keep the scheduler running
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
@@ -186,7 +596,11 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await?;
     tracing::info!("Server running on http://0.0.0.0:8000");
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
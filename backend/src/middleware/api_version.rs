@@ -0,0 +1,38 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+static DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+
+/// Route prefixes of the versioned API
+const VERSION_PREFIXES: &[&str] = &["/v1", "/v2"];
+
+/// The path without its version prefix, for middleware that decides by path
+pub fn unversioned_path(path: &str) -> &str {
+    VERSION_PREFIXES
+        .iter()
+        .find_map(|prefix| {
+            path.strip_prefix(prefix)
+                .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .unwrap_or(path)
+}
+
+/// Marks responses of the unprefixed routes, which are deprecated aliases of `/v1`
+pub async fn deprecated_alias_middleware(request: Request, next: Next) -> Response {
+    let successor = HeaderValue::from_str(&format!(
+        "</v1{}>; rel=\"successor-version\"",
+        request.uri().path()
+    ));
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION_HEADER.clone(), HeaderValue::from_static("true"));
+    if let Ok(successor) = successor {
+        headers.insert(header::LINK, successor);
+    }
+    response
+}
@@ -1,15 +1,30 @@
-use crate::{models::user::UserRole, utils::jwt::verify_jwt, AppState};
+use crate::{
+    models::user::UserRole,
+    utils::{errors::AppError, jwt::verify_jwt, logger::LOGGER, messages},
+    AppState,
+};
 use axum::{
     extract::{Request, State},
-    http::StatusCode,
+    http::{Method, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
+use jsonwebtoken::errors::ErrorKind;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct AuthUser {
     pub user_id: i32,
     pub role: UserRole,
+    pub cohort_id: Option<i32>,
+    /// `users.token_version` the token was issued under
+    pub token_version: i32,
+    /// The admin behind a read-only impersonation token
+    pub impersonated_by: Option<i32>,
 }
 
 impl AuthUser {
@@ -17,6 +32,20 @@ impl AuthUser {
         matches!(self.role, UserRole::Admin)
     }
 
+    /// Admins not bound to a cohort can see every cohort
+    pub fn is_super_admin(&self) -> bool {
+        self.is_admin() && self.cohort_id.is_none()
+    }
+
+    /// Cohort filter for admin queries: `None` means unrestricted
+    pub fn cohort_scope(&self) -> Option<i32> {
+        if self.is_super_admin() {
+            None
+        } else {
+            self.cohort_id
+        }
+    }
+
     pub fn is_student(&self) -> bool {
         matches!(self.role, UserRole::Student)
     }
@@ -29,36 +58,378 @@ impl AuthUser {
     }
 }
 
+/// Short-lived cache of roles read back from the database.
+///
+/// Enabled with `VERIFY_ADMIN_ROLE=true`; `ROLE_CACHE_TTL_SECS` bounds how long a
+/// demoted admin can keep using an old token.
+pub struct RoleCache {
+    enabled: bool,
+    ttl: Duration,
+    entries: Mutex<HashMap<i32, (Option<UserRole>, Instant)>>,
+}
+
+impl RoleCache {
+    pub fn from_env() -> Self {
+        let enabled = env::var("VERIFY_ADMIN_ROLE")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        let ttl_secs = env::var("ROLE_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        Self {
+            enabled,
+            ttl: Duration::from_secs(ttl_secs),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Current role of the user, or `None` if the user no longer exists
+    pub async fn current_role(
+        &self,
+        db: &sqlx::PgPool,
+        user_id: i32,
+    ) -> Result<Option<UserRole>, sqlx::Error> {
+        if let Some((role, cached_at)) = self.entries.lock().unwrap().get(&user_id) {
+            if cached_at.elapsed() < self.ttl {
+                return Ok(role.clone());
+            }
+        }
+
+        let role = sqlx::query_scalar::<_, UserRole>("SELECT role FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?;
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, cached_at)| cached_at.elapsed() < self.ttl);
+        entries.insert(user_id, (role.clone(), Instant::now()));
+
+        Ok(role)
+    }
+}
+
+/// Optional cache of `users.token_version`.
+///
+/// Off by default (`TOKEN_VERSION_CACHE_TTL_SECS=0`): every request reads the
+/// version, a primary-key lookup, so revocation takes effect at once on every
+/// instance. With a TTL, revocations on this instance still invalidate the entry
+/// immediately, but other instances keep accepting revoked tokens for up to
+/// the TTL.
+pub struct TokenVersionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<i32, (Option<i32>, Instant)>>,
+}
+
+impl TokenVersionCache {
+    pub fn from_env() -> Self {
+        let ttl_secs = env::var("TOKEN_VERSION_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        Self {
+            ttl: Duration::from_secs(ttl_secs),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Current token version of the user, or `None` if the user no longer exists
+    pub async fn current_version(
+        &self,
+        db: &sqlx::PgPool,
+        user_id: i32,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        if let Some((version, cached_at)) = self.entries.lock().unwrap().get(&user_id) {
+            if cached_at.elapsed() < self.ttl {
+                return Ok(*version);
+            }
+        }
+
+        let version = sqlx::query_scalar::<_, i32>("SELECT token_version FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(db)
+            .await?;
+
+        if self.ttl.is_zero() {
+            return Ok(version);
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, cached_at)| cached_at.elapsed() < self.ttl);
+        entries.insert(user_id, (version, Instant::now()));
+
+        Ok(version)
+    }
+
+    pub fn invalidate(&self, user_id: i32) {
+        self.entries.lock().unwrap().remove(&user_id);
+    }
+}
+
+/// Revokes every token issued to the user so far, returning the new version
+pub async fn revoke_sessions(state: &AppState, user_id: i32) -> Result<Option<i32>, sqlx::Error> {
+    let version = sqlx::query_scalar::<_, i32>(
+        "UPDATE users SET token_version = token_version + 1 WHERE id = $1 RETURNING token_version",
+    )
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    state.token_versions.invalidate(user_id);
+    Ok(version)
+}
+
+/// Whether a long-lived connection opened with a token may keep streaming.
+///
+/// False once the token is revoked or, with `require_admin`, once the user is no
+/// longer an admin. The role comes from `RoleCache` whether or not
+/// `VERIFY_ADMIN_ROLE` is set, since these connections outlive any token check.
+pub async fn session_still_valid(
+    state: &AppState,
+    user_id: i32,
+    token_version: i32,
+    require_admin: bool,
+) -> Result<bool, sqlx::Error> {
+    let version = state
+        .token_versions
+        .current_version(&state.db, user_id)
+        .await?;
+    if version != Some(token_version) {
+        return Ok(false);
+    }
+
+    if require_admin {
+        let role = state.role_cache.current_role(&state.db, user_id).await?;
+        return Ok(matches!(role, Some(UserRole::Admin)));
+    }
+
+    Ok(true)
+}
+
+/// Short hash identifying a token in logs without exposing it
+fn token_fingerprint(token: &str) -> String {
+    format!("{:x}", md5::compute(token))[..12].to_string()
+}
+
+fn reject(reason: &str, path: &str, token: Option<&str>, user_id: Option<i32>) -> StatusCode {
+    let mut context: HashMap<String, serde_json::Value> = [
+        (
+            "reason".to_string(),
+            serde_json::Value::String(reason.to_string()),
+        ),
+        (
+            "path".to_string(),
+            serde_json::Value::String(path.to_string()),
+        ),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    if let Some(token) = token {
+        context.insert(
+            "token_fingerprint".to_string(),
+            serde_json::Value::String(token_fingerprint(token)),
+        );
+    }
+
+    LOGGER.log_business_event("auth_rejected", user_id, context);
+    StatusCode::UNAUTHORIZED
+}
+
+/// Records a request made under an impersonation token in `audit_log`
+async fn audit_impersonated_request(
+    db: &sqlx::PgPool,
+    admin_id: i32,
+    user_id: i32,
+    method: &Method,
+    path: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log (table_name, operation, new_data, user_id)
+         VALUES ('impersonation', 'REQUEST', $1, $2)",
+    )
+    .bind(serde_json::json!({
+        "impersonated_user_id": user_id,
+        "method": method.as_str(),
+        "path": path,
+    }))
+    .bind(admin_id)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let auth_header = request
-        .headers()
-        .get("Authorization")
-        .and_then(|header| header.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let path = request.uri().path().to_string();
 
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
+    let auth_header = match request.headers().get("Authorization") {
+        Some(header) => header
+            .to_str()
+            .map_err(|_| reject("malformed_header", &path, None, None))?,
+        None => return Err(reject("missing_header", &path, None, None)),
+    };
 
-    let token = &auth_header[7..]; // Remove "Bearer " prefix
+    let Some(token) = auth_header.strip_prefix("Bearer ") else {
+        return Err(reject("not_bearer", &path, None, None));
+    };
 
-    let claims = verify_jwt(token, &state.jwt_secret).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let claims = verify_jwt(token, &state.jwt_keys).map_err(|e| {
+        let reason = match e
+            .downcast_ref::<jsonwebtoken::errors::Error>()
+            .map(|e| e.kind())
+        {
+            Some(ErrorKind::ExpiredSignature) => "expired",
+            Some(ErrorKind::InvalidSignature) => "invalid_signature",
+            Some(ErrorKind::InvalidToken | ErrorKind::Base64(_) | ErrorKind::Json(_)) => {
+                "malformed_token"
+            }
+            Some(_) => "invalid_token",
+            None => "unknown_key",
+        };
+        reject(reason, &path, Some(token), None)
+    })?;
 
+    // Deny by default: only known roles get through
     let role = match claims.role.as_str() {
         "admin" => UserRole::Admin,
         "student" => UserRole::Student,
-        _ => return Err(StatusCode::UNAUTHORIZED),
+        _ => return Err(reject("unknown_role", &path, Some(token), Some(claims.sub))),
     };
 
+    let current_version = state
+        .token_versions
+        .current_version(&state.db, claims.sub)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    match current_version {
+        None => return Err(reject("unknown_user", &path, Some(token), Some(claims.sub))),
+        Some(version) if version != claims.token_version => {
+            return Err(reject("revoked", &path, Some(token), Some(claims.sub)));
+        }
+        Some(_) => {}
+    }
+
+    if let Some(admin_id) = claims.impersonated_by {
+        // The impersonating admin must still be an admin, with the sessions the
+        // token was issued under, and still have the student in their cohort scope
+        let impersonator = sqlx::query(
+            "SELECT admin.role::text AS role, admin.token_version,
+                    (admin.cohort_id IS NULL OR admin.cohort_id = student.cohort_id) AS in_scope
+             FROM users admin, users student
+             WHERE admin.id = $1 AND student.id = $2",
+        )
+        .bind(admin_id)
+        .bind(claims.sub)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let rejection = match &impersonator {
+            None => Some("unknown_impersonator"),
+            Some(row) if row.get::<String, _>("role") != "admin" => Some("impersonator_not_admin"),
+            Some(row)
+                if Some(row.get::<i32, _>("token_version")) != claims.impersonator_version =>
+            {
+                Some("impersonator_revoked")
+            }
+            Some(row) if !row.get::<bool, _>("in_scope") => Some("impersonation_out_of_scope"),
+            Some(_) => None,
+        };
+        if let Some(reason) = rejection {
+            return Err(reject(reason, &path, Some(token), Some(admin_id)));
+        }
+
+        let method = request.method().clone();
+        let read_only = method == Method::GET || method == Method::HEAD;
+
+        LOGGER.log_business_event(
+            if read_only {
+                "impersonated_request"
+            } else {
+                "impersonated_write_rejected"
+            },
+            Some(admin_id),
+            [
+                (
+                    "impersonated_user_id".to_string(),
+                    serde_json::Value::Number(claims.sub.into()),
+                ),
+                (
+                    "method".to_string(),
+                    serde_json::Value::String(method.to_string()),
+                ),
+                ("path".to_string(), serde_json::Value::String(path.clone())),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+
+        // Nothing is served unless the audit row is written
+        audit_impersonated_request(&state.db, admin_id, claims.sub, &method, &path)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if !read_only {
+            return Ok(
+                AppError::Forbidden(messages::text("auth.impersonation_read_only")).into_response(),
+            );
+        }
+    }
+
     let auth_user = AuthUser {
         user_id: claims.sub,
         role,
+        cohort_id: claims.cohort_id,
+        token_version: claims.token_version,
+        impersonated_by: claims.impersonated_by,
     };
 
-    request.extensions_mut().insert(auth_user);
+    request.extensions_mut().insert(auth_user.clone());
+    let mut response = next.run(request).await;
+
+    // Lets outer middleware (e.g. slow request logging) attribute the request
+    response.extensions_mut().insert(auth_user);
+    Ok(response)
+}
+
+/// Re-checks the admin role against the database for admin routes.
+///
+/// Must run after `auth_middleware`; a no-op unless `VERIFY_ADMIN_ROLE` is set.
+pub async fn verify_role_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if !state.role_cache.enabled {
+        return Ok(next.run(request).await);
+    }
+
+    let auth_user = request
+        .extensions()
+        .get::<AuthUser>()
+        .cloned()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if auth_user.is_admin() {
+        let current_role = state
+            .role_cache
+            .current_role(&state.db, auth_user.user_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if !matches!(current_role, Some(UserRole::Admin)) {
+            let path = request.uri().path().to_string();
+            return Err(reject("stale_role", &path, None, Some(auth_user.user_id)));
+        }
+    }
+
     Ok(next.run(request).await)
 }
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use super::ip_allowlist::IpNetwork;
+
+/// The resolved address of the client that made the request.
+///
+/// Inserted into request extensions by `client_ip_middleware`; handlers and
+/// other middleware should use this instead of reading proxy headers themselves.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+/// Resolves the client IP behind reverse proxies without trusting spoofed headers.
+///
+/// `TRUSTED_PROXIES` lists the proxy networks (comma-separated CIDRs) and
+/// `CLIENT_IP_HEADER` the header they set (`X-Forwarded-For` by default, or e.g.
+/// `X-Real-IP` / `CF-Connecting-IP`). The header is only honoured when the
+/// connection itself comes from a trusted proxy.
+#[derive(Debug)]
+pub struct ClientIpResolver {
+    trusted_proxies: Vec<IpNetwork>,
+    header: String,
+}
+
+impl ClientIpResolver {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(IpNetwork::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?;
+
+        let header = env::var("CLIENT_IP_HEADER")
+            .ok()
+            .filter(|header| !header.is_empty())
+            .unwrap_or_else(|| "X-Forwarded-For".to_string());
+
+        Ok(Self {
+            trusted_proxies,
+            header,
+        })
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|network| network.contains(ip))
+    }
+
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let Some(value) = headers
+            .get(self.header.as_str())
+            .and_then(|value| value.to_str().ok())
+        else {
+            return peer;
+        };
+
+        // Walk the chain from the nearest hop; the first address that isn't one
+        // of our proxies is the client. Anything further left is client-supplied.
+        let mut client = peer;
+        for hop in value.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        client
+    }
+}
+
+pub async fn client_ip_middleware(
+    State(resolver): State<Arc<ClientIpResolver>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    if let Some(peer) = peer {
+        let client_ip = resolver.resolve(peer, request.headers());
+        request.extensions_mut().insert(ClientIp(client_ip));
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use std::env;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use super::client_ip::ClientIp;
+use crate::utils::logger::LOGGER;
+
+/// An IP network in CIDR notation, e.g. `10.0.0.0/8` or `2001:db8::/32`
+#[derive(Debug, Clone, Copy)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// Parses `addr/prefix`; a bare address is a single-host network
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid IP address in '{}'", value))?;
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max_prefix)
+                .ok_or_else(|| format!("Invalid prefix length in '{}'", value))?,
+            None => max_prefix,
+        };
+
+        Ok(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            // IPv4 clients can show up as IPv4-mapped IPv6 addresses on dual-stack sockets
+            (IpAddr::V4(_), IpAddr::V6(ip)) => ip
+                .to_ipv4_mapped()
+                .is_some_and(|ip| self.contains(IpAddr::V4(ip))),
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+}
+
+/// Networks allowed to reach the admin routes.
+///
+/// Configured with `ADMIN_IP_ALLOWLIST` (comma-separated CIDRs). The client IP
+/// comes from `ClientIp`, so deployments behind a proxy configure
+/// `TRUSTED_PROXIES` rather than anything allowlist-specific.
+#[derive(Debug)]
+pub struct AdminIpAllowlist {
+    networks: Vec<IpNetwork>,
+}
+
+impl AdminIpAllowlist {
+    /// Returns `None` when no allowlist is configured
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(raw) = env::var("ADMIN_IP_ALLOWLIST") else {
+            return Ok(None);
+        };
+
+        let networks = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(IpNetwork::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?;
+
+        if networks.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { networks }))
+    }
+
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+}
+
+pub async fn admin_ip_allowlist_middleware(
+    State(allowlist): State<Arc<AdminIpAllowlist>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let client_ip = request
+        .extensions()
+        .get::<ClientIp>()
+        .map(|ClientIp(ip)| *ip);
+
+    if !client_ip.is_some_and(|ip| allowlist.allows(ip)) {
+        LOGGER.log_business_event(
+            "admin_ip_rejected",
+            None,
+            [
+                (
+                    "path".to_string(),
+                    serde_json::Value::String(request.uri().path().to_string()),
+                ),
+                (
+                    "client_ip".to_string(),
+                    client_ip
+                        .map(|ip| serde_json::Value::String(ip.to_string()))
+                        .unwrap_or(serde_json::Value::Null),
+                ),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,57 @@
+use axum::{body::Body, extract::Request, http::header, middleware::Next, response::Response};
+
+use crate::utils::{
+    errors::ErrorResponse,
+    messages::{ErrorText, Locale},
+};
+
+/// Rewrites `AppError` bodies into the locale requested via `Accept-Language`.
+///
+/// Only the human-readable `message` and validation details change; the
+/// `error` code stays the same in every language. Messages are translated by
+/// their catalog key, and ones outside the catalog stay in English.
+/// `Content-Language` is only set when every message was translated, so it
+/// never claims a language the body isn't fully in.
+pub async fn localize_errors_middleware(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Locale::from_accept_language)
+        .unwrap_or_default();
+
+    let mut response = next.run(request).await;
+    if locale == Locale::En {
+        return response;
+    }
+
+    let Some(mut error) = response.extensions_mut().remove::<ErrorResponse>() else {
+        return response;
+    };
+
+    let mut fully_translated = true;
+    let mut translate = |text: &mut ErrorText| match text.translate(locale) {
+        Some(translated) => *text = translated,
+        None => fully_translated = false,
+    };
+    translate(&mut error.message);
+    if let Some(details) = error.details.as_mut() {
+        for messages in details.values_mut() {
+            messages.iter_mut().for_each(&mut translate);
+        }
+    }
+
+    let Ok(body) = serde_json::to_vec(&error) else {
+        return response;
+    };
+
+    response.headers_mut().remove(header::CONTENT_LENGTH);
+    if fully_translated {
+        response.headers_mut().insert(
+            header::CONTENT_LANGUAGE,
+            header::HeaderValue::from_static(locale.tag()),
+        );
+    }
+    *response.body_mut() = Body::from(body);
+    response
+}
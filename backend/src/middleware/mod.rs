@@ -1 +1,10 @@
+pub mod api_version;
 pub mod auth;
+pub mod cache_control;
+pub mod client_ip;
+pub mod ip_allowlist;
+pub mod locale;
+pub mod rate_limit;
+pub mod request_id;
+pub mod slow_request;
+pub mod timeout;
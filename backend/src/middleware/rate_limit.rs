@@ -0,0 +1,236 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::api_version::unversioned_path;
+use crate::{
+    middleware::auth::AuthUser,
+    utils::{errors::AppError, logger::LOGGER, messages},
+};
+
+/// How often idle buckets are swept from memory
+const SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Endpoint groups with separate budgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RouteClass {
+    Read,
+    Write,
+    Upload,
+    Analytics,
+}
+
+impl RouteClass {
+    fn of(request: &Request) -> Self {
+        let path = unversioned_path(request.uri().path());
+        if path.starts_with("/admin/analytics") || path.starts_with("/admin/metrics") {
+            RouteClass::Analytics
+        } else if request.method() == Method::POST
+            && (path.ends_with("/screening")
+                || path.ends_with("/interview")
+                || path.ends_with("/documents"))
+        {
+            RouteClass::Upload
+        } else if request.method() == Method::GET {
+            RouteClass::Read
+        } else {
+            RouteClass::Write
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Read => "read",
+            RouteClass::Write => "write",
+            RouteClass::Upload => "upload",
+            RouteClass::Analytics => "analytics",
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Per-user token buckets, one per route class.
+///
+/// Budgets are requests per minute from `RATE_LIMIT_READ_PER_MIN`,
+/// `RATE_LIMIT_WRITE_PER_MIN`, `RATE_LIMIT_UPLOAD_PER_MIN` and
+/// `RATE_LIMIT_ANALYTICS_PER_MIN`; admins get `RATE_LIMIT_ADMIN_MULTIPLIER`
+/// times as much. A bucket holds one minute's worth of requests as burst.
+pub struct RateLimiter {
+    limits: HashMap<RouteClass, f64>,
+    admin_multiplier: f64,
+    buckets: Mutex<HashMap<(i32, RouteClass), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn from_env() -> Self {
+        let per_minute = |name: &str, default: f64| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+                .unwrap_or(default)
+        };
+
+        let limits = [
+            (
+                RouteClass::Read,
+                per_minute("RATE_LIMIT_READ_PER_MIN", 120.0),
+            ),
+            (
+                RouteClass::Write,
+                per_minute("RATE_LIMIT_WRITE_PER_MIN", 60.0),
+            ),
+            (
+                RouteClass::Upload,
+                per_minute("RATE_LIMIT_UPLOAD_PER_MIN", 10.0),
+            ),
+            (
+                RouteClass::Analytics,
+                per_minute("RATE_LIMIT_ANALYTICS_PER_MIN", 20.0),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        Self {
+            limits,
+            admin_multiplier: per_minute("RATE_LIMIT_ADMIN_MULTIPLIER", 5.0),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token, or returns how long until one is available
+    fn try_acquire(&self, user_id: i32, is_admin: bool, class: RouteClass) -> Result<(), Duration> {
+        let mut capacity = self.limits[&class];
+        if is_admin {
+            capacity *= self.admin_multiplier;
+        }
+        let refill_per_sec = capacity / 60.0;
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry((user_id, class)).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: Instant::now(),
+        });
+
+        let elapsed = bucket.updated_at.elapsed().as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated_at = Instant::now();
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / refill_per_sec;
+            Err(Duration::from_secs_f64(wait))
+        }
+    }
+
+    /// Drops buckets idle long enough to have refilled completely
+    fn sweep(&self) -> usize {
+        let mut buckets = self.buckets.lock().unwrap();
+        let before = buckets.len();
+        buckets.retain(|_, bucket| bucket.updated_at.elapsed() < Duration::from_secs(60));
+        before - buckets.len()
+    }
+
+    /// Periodically sweeps idle buckets so the map doesn't grow without bound
+    pub fn spawn_sweeper(self: &Arc<Self>) {
+        let limiter = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let removed = limiter.sweep();
+                if removed > 0 {
+                    tracing::debug!("Swept {} idle rate limit buckets", removed);
+                }
+            }
+        });
+    }
+}
+
+/// Must run after `auth_middleware`; unauthenticated requests pass through
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth_user) = request.extensions().get::<AuthUser>().cloned() else {
+        return next.run(request).await;
+    };
+
+    let class = RouteClass::of(&request);
+    if let Err(wait) = limiter.try_acquire(auth_user.user_id, auth_user.is_admin(), class) {
+        let retry_after_secs = wait.as_secs() + 1;
+        LOGGER.log_business_event(
+            "rate_limit_exceeded",
+            Some(auth_user.user_id),
+            [
+                (
+                    "route_class".to_string(),
+                    serde_json::Value::String(class.as_str().to_string()),
+                ),
+                (
+                    "path".to_string(),
+                    serde_json::Value::String(request.uri().path().to_string()),
+                ),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        return AppError::TooManyRequests {
+            message: messages::text("too_many_requests"),
+            retry_after_secs,
+        }
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn class(method: Method, path: &str) -> RouteClass {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::empty())
+            .unwrap();
+        RouteClass::of(&request)
+    }
+
+    #[test]
+    fn file_uploads_use_the_upload_budget() {
+        for path in [
+            "/v1/applications/1/screening",
+            "/v1/applications/1/interview",
+            "/v1/applications/1/documents",
+        ] {
+            assert_eq!(class(Method::POST, path), RouteClass::Upload, "{}", path);
+        }
+    }
+
+    #[test]
+    fn listing_documents_is_a_read() {
+        assert_eq!(
+            class(Method::GET, "/v1/applications/1/documents"),
+            RouteClass::Read
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::env;
+use std::time::{Duration, Instant};
+
+use super::auth::AuthUser;
+use crate::utils::logger::LOGGER;
+
+/// Requests slower than `SLOW_REQUEST_THRESHOLD_MS` (default 2000) are logged
+/// as `slow_request` warnings. `0` disables the check.
+#[derive(Debug, Clone, Copy)]
+pub struct SlowRequestThreshold(Option<Duration>);
+
+impl SlowRequestThreshold {
+    pub fn from_env() -> Self {
+        let millis = env::var("SLOW_REQUEST_THRESHOLD_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(2000);
+
+        Self((millis > 0).then(|| Duration::from_millis(millis)))
+    }
+}
+
+pub async fn slow_request_middleware(
+    State(SlowRequestThreshold(threshold)): State<SlowRequestThreshold>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(threshold) = threshold else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let elapsed = started.elapsed();
+    if elapsed >= threshold {
+        // auth_middleware copies the user onto the response for us
+        let user_id = response
+            .extensions()
+            .get::<AuthUser>()
+            .map(|user| user.user_id);
+
+        LOGGER.log_slow_request(
+            &method,
+            &path,
+            user_id,
+            response.status().as_u16(),
+            elapsed.as_millis(),
+        );
+    }
+
+    response
+}
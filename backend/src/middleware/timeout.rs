@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::env;
+use std::time::Duration;
+
+use super::api_version::unversioned_path;
+use crate::utils::{errors::AppError, logger::LOGGER};
+
+/// Per-request deadlines.
+///
+/// `REQUEST_TIMEOUT_SECS` applies to ordinary requests; uploads (recordings and
+/// documents) and downloads get `TRANSFER_TIMEOUT_SECS` since large files
+/// legitimately take longer.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestTimeouts {
+    default: Duration,
+    transfer: Duration,
+}
+
+impl RequestTimeouts {
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: u64| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            default: Duration::from_secs(secs("REQUEST_TIMEOUT_SECS", 30)),
+            transfer: Duration::from_secs(secs("TRANSFER_TIMEOUT_SECS", 600)),
+        }
+    }
+
+    fn for_request(&self, request: &Request) -> Duration {
+        let path = unversioned_path(request.uri().path());
+        let is_upload = path.ends_with("/screening")
+            || path.ends_with("/interview")
+            || path.ends_with("/validate-upload")
+            || (request.method() == Method::POST && path.ends_with("/documents"));
+        let is_download = path.starts_with("/files/")
+            || path.starts_with("/download/")
+            || path.ends_with("/download-all");
+
+        if is_upload || is_download {
+            self.transfer
+        } else {
+            self.default
+        }
+    }
+}
+
+pub async fn timeout_middleware(
+    State(timeouts): State<RequestTimeouts>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let limit = timeouts.for_request(&request);
+
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            LOGGER.log_request(&method, &path, None, 408);
+            AppError::RequestTimeout(
+                format!(
+                    "Request did not complete within {} seconds",
+                    limit.as_secs()
+                )
+                .into(),
+            )
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn timeouts() -> RequestTimeouts {
+        RequestTimeouts {
+            default: Duration::from_secs(30),
+            transfer: Duration::from_secs(600),
+        }
+    }
+
+    fn request(method: Method, path: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn uploads_get_the_transfer_deadline() {
+        let t = timeouts();
+        for path in [
+            "/applications/1/screening",
+            "/applications/1/interview",
+            "/applications/1/documents",
+            "/applications/1/validate-upload",
+        ] {
+            assert_eq!(
+                t.for_request(&request(Method::POST, path)),
+                t.transfer,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn listing_documents_keeps_the_default_deadline() {
+        let t = timeouts();
+        let listing = request(Method::GET, "/applications/1/documents");
+        assert_eq!(t.for_request(&listing), t.default);
+    }
+}
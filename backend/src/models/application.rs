@@ -1,7 +1,11 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use validator::Validate;
+use std::collections::BTreeMap;
+use validator::{Validate, ValidationError};
+
+use crate::utils::currency::validate_currency;
+use crate::utils::messages::{self, ErrorText};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Application {
@@ -13,8 +17,17 @@ pub struct Application {
     #[serde(rename = "application_date")]
     pub applied_date: NaiveDate,
     pub status: ApplicationStatus,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
+    pub salary_min: Option<i32>,
+    pub salary_max: Option<i32>,
+    pub currency: Option<String>,
+    pub cohort_id: Option<i32>,
+    /// Latest change to the application or its screening, interview or documents
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub last_activity_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -22,12 +35,151 @@ pub struct Application {
 #[serde(rename_all = "snake_case")]
 pub enum ApplicationStatus {
     Waiting,
+    /// Still waiting, with a screening date recorded but no result yet
+    ScreeningScheduled,
     Rejected,
     NextStage,
     Ignored,
+    Offer,
+    Accepted,
+}
+
+impl ApplicationStatus {
+    pub const ALL: [ApplicationStatus; 7] = [
+        ApplicationStatus::Waiting,
+        ApplicationStatus::ScreeningScheduled,
+        ApplicationStatus::NextStage,
+        ApplicationStatus::Offer,
+        ApplicationStatus::Accepted,
+        ApplicationStatus::Rejected,
+        ApplicationStatus::Ignored,
+    ];
+
+    /// Same spelling as the serialized and database value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApplicationStatus::Waiting => "waiting",
+            ApplicationStatus::ScreeningScheduled => "screening_scheduled",
+            ApplicationStatus::Rejected => "rejected",
+            ApplicationStatus::NextStage => "next_stage",
+            ApplicationStatus::Ignored => "ignored",
+            ApplicationStatus::Offer => "offer",
+            ApplicationStatus::Accepted => "accepted",
+        }
+    }
+
+    /// Statuses this one may move to, not counting staying put
+    pub fn next_statuses(&self) -> Vec<ApplicationStatus> {
+        ApplicationStatus::ALL
+            .into_iter()
+            .filter(|next| {
+                std::mem::discriminant(self) != std::mem::discriminant(next)
+                    && self.can_transition_to(next)
+            })
+            .collect()
+    }
+
+    /// Whether an application in this status may move to `next`.
+    ///
+    /// Staying in the same status is always allowed. `Accepted` is terminal,
+    /// and closed applications (`Rejected`, `Ignored`) can only be reopened.
+    pub fn can_transition_to(&self, next: &ApplicationStatus) -> bool {
+        use ApplicationStatus::*;
+
+        if std::mem::discriminant(self) == std::mem::discriminant(next) {
+            return true;
+        }
+
+        match self {
+            Waiting | ScreeningScheduled | NextStage => matches!(
+                next,
+                Waiting | ScreeningScheduled | NextStage | Rejected | Ignored | Offer
+            ),
+            Offer => matches!(next, Accepted | Rejected | Ignored),
+            Rejected | Ignored => matches!(next, Waiting),
+            Accepted => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusTransitionsResponse {
+    /// Status of a new application when the request doesn't set one
+    pub initial: ApplicationStatus,
+    /// Statuses each status may move to
+    pub transitions: BTreeMap<&'static str, Vec<ApplicationStatus>>,
+}
+
+/// A status change recovered from the applications audit log
+#[derive(Debug, FromRow)]
+pub struct StatusChange {
+    pub at: DateTime<Utc>,
+    /// `None` when the earlier status predates the audit log
+    pub from_status: Option<ApplicationStatus>,
+    pub to_status: ApplicationStatus,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    Created {
+        status: ApplicationStatus,
+    },
+    StatusChanged {
+        from: Option<ApplicationStatus>,
+        to: ApplicationStatus,
+    },
+    ScreeningUploaded,
+    ScreeningResult {
+        result: crate::models::screening::ScreeningResult,
+        reason_code: Option<String>,
+    },
+    InterviewUploaded,
+    InterviewResult {
+        result: crate::models::interview::InterviewResult,
+        reason_code: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineEvent {
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: TimelineEventKind,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApplicationTimelineResponse {
+    pub application_id: i32,
+    /// Oldest first
+    pub events: Vec<TimelineEvent>,
+}
+
+fn validate_salary_range(
+    salary_min: Option<i32>,
+    salary_max: Option<i32>,
+) -> Result<(), ValidationError> {
+    match (salary_min, salary_max) {
+        (Some(min), Some(max)) if min > max => {
+            let mut error = ValidationError::new("salary_range");
+            error.message = Some("salary_min must not exceed salary_max".into());
+            Err(error)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_create_salary(request: &CreateApplicationRequest) -> Result<(), ValidationError> {
+    validate_salary_range(request.salary_min, request.salary_max)
+}
+
+fn validate_update_salary(request: &UpdateApplicationRequest) -> Result<(), ValidationError> {
+    validate_salary_range(request.salary_min, request.salary_max)
 }
 
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_create_salary"))]
 pub struct CreateApplicationRequest {
     #[validate(length(min = 1))]
     #[serde(rename = "company_name")]
@@ -36,9 +188,18 @@ pub struct CreateApplicationRequest {
     pub job_url: Option<String>,
     #[serde(rename = "application_date")]
     pub applied_date: NaiveDate,
+    #[validate(range(min = 0))]
+    pub salary_min: Option<i32>,
+    #[validate(range(min = 0))]
+    pub salary_max: Option<i32>,
+    #[validate(custom = "validate_currency")]
+    pub currency: Option<String>,
+    /// Lets imported history keep its outcome; new applications start as `waiting`
+    pub status: Option<ApplicationStatus>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_update_salary"))]
 pub struct UpdateApplicationRequest {
     #[serde(rename = "company_name")]
     pub company: Option<String>,
@@ -47,9 +208,96 @@ pub struct UpdateApplicationRequest {
     #[serde(rename = "application_date")]
     pub applied_date: Option<NaiveDate>,
     pub status: Option<ApplicationStatus>,
+    #[validate(range(min = 0))]
+    pub salary_min: Option<i32>,
+    #[validate(range(min = 0))]
+    pub salary_max: Option<i32>,
+    #[validate(custom = "validate_currency")]
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplicationsQuery {
+    /// Only return applications changed after this instant (delta sync)
+    pub since: Option<DateTime<Utc>>,
+    /// Comma-separated sub-resources to attach, see `Includes`
+    pub include: Option<String>,
+}
+
+/// Which sub-resources a list of applications comes with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Includes {
+    pub screening: bool,
+    pub interview: bool,
+}
+
+impl Includes {
+    pub const ALL: Includes = Includes {
+        screening: true,
+        interview: true,
+    };
+
+    /// Parses `include=screening,interview`. Absent means everything, as
+    /// before the parameter existed; an empty value means nothing.
+    pub fn parse(include: Option<&str>) -> Result<Self, ErrorText> {
+        let Some(include) = include else {
+            return Ok(Self::ALL);
+        };
+
+        let mut includes = Includes {
+            screening: false,
+            interview: false,
+        };
+        for part in include
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            match part {
+                "screening" => includes.screening = true,
+                "interview" => includes.interview = true,
+                _ => return Err(messages::text("validation.include")),
+            }
+        }
+        Ok(includes)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplicationsCountQuery {
+    pub status: Option<ApplicationStatus>,
 }
 
 #[derive(Debug, Serialize)]
+pub struct ApplicationsCountResponse {
+    pub count: i64,
+}
+
+/// An application deleted since the client's last sync
+#[derive(Debug, Serialize, FromRow)]
+pub struct ApplicationTombstone {
+    #[sqlx(rename = "application_id")]
+    pub id: i32,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApplicationSyncResponse {
+    pub applications: Vec<ApplicationResponse>,
+    pub deleted: Vec<ApplicationTombstone>,
+    /// Pass as `since` on the next sync. It trails the server clock by a
+    /// safety window, so the next sync may return some applications again.
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub server_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchApplicationsRequest {
+    pub ids: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ApplicationResponse {
     pub id: i32,
     pub user_id: i32,
@@ -59,8 +307,15 @@ pub struct ApplicationResponse {
     #[serde(rename = "application_date")]
     pub applied_date: NaiveDate,
     pub status: ApplicationStatus,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub last_activity_at: DateTime<Utc>,
+    pub salary_min: Option<i32>,
+    pub salary_max: Option<i32>,
+    pub currency: Option<String>,
     pub screening: Option<crate::models::screening::ScreeningResponse>,
     pub interview: Option<crate::models::interview::InterviewResponse>,
 }
@@ -76,6 +331,10 @@ impl From<Application> for ApplicationResponse {
             status: app.status,
             created_at: app.created_at,
             updated_at: app.updated_at,
+            last_activity_at: app.last_activity_at,
+            salary_min: app.salary_min,
+            salary_max: app.salary_max,
+            currency: app.currency,
             screening: None,
             interview: None,
         }
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Cohort {
+    pub id: i32,
+    pub name: String,
+    #[serde(default, with = "crate::utils::time::rfc3339::option")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCohortRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignCohortRequest {
+    pub cohort_id: Option<i32>,
+}
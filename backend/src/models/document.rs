@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Document {
+    pub id: i32,
+    pub application_id: i32,
+    pub file_path: String,
+    pub original_filename: String,
+    pub size_bytes: i64,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocumentResponse {
+    pub id: i32,
+    pub application_id: i32,
+    pub file_path: String,
+    pub original_filename: String,
+    pub size_bytes: i64,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Document> for DocumentResponse {
+    fn from(document: Document) -> Self {
+        Self {
+            id: document.id,
+            application_id: document.application_id,
+            file_path: document.file_path,
+            original_filename: document.original_filename,
+            size_bytes: document.size_bytes,
+            created_at: document.created_at,
+        }
+    }
+}
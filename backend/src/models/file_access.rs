@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FileAccessLog {
+    pub id: i64,
+    pub filename: String,
+    pub accessed_by: Option<i32>,
+    /// `direct` (Authorization header), `token` (download link), `archive` (download-all)
+    /// or `preview` (inline playback)
+    pub via: String,
+    pub bytes_served: i64,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub accessed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileAccessQuery {
+    pub filename: Option<String>,
+    pub user_id: Option<i32>,
+}
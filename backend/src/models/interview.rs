@@ -7,9 +7,17 @@ pub struct Interview {
     pub id: i32,
     pub application_id: i32,
     pub file_path: Option<String>,
+    /// Name the recording is downloaded under
+    pub original_filename: Option<String>,
     pub interview_date: Option<NaiveDate>,
     pub result: Option<InterviewResult>,
+    pub reason_code: Option<String>,
+    pub feedback: Option<String>,
+    /// Graded outcome on the `outcome.score_scale` scale, independent of `result`
+    pub score: Option<i16>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -26,16 +34,27 @@ pub struct UpdateInterviewRequest {
     pub interview_date: Option<NaiveDate>,
     #[serde(rename = "interview_status")]
     pub result: Option<InterviewResult>,
+    pub reason_code: Option<String>,
+    pub feedback: Option<String>,
+    pub score: Option<i16>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct InterviewResponse {
     pub id: i32,
     pub application_id: i32,
     pub file_path: Option<String>,
+    /// Name the recording is downloaded under
+    pub original_filename: Option<String>,
     pub interview_date: Option<NaiveDate>,
     pub result: Option<InterviewResult>,
+    pub reason_code: Option<String>,
+    pub feedback: Option<String>,
+    /// Graded outcome on the `outcome.score_scale` scale, independent of `result`
+    pub score: Option<i16>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -45,8 +64,12 @@ impl From<Interview> for InterviewResponse {
             id: interview.id,
             application_id: interview.application_id,
             file_path: interview.file_path,
+            original_filename: interview.original_filename,
             interview_date: interview.interview_date,
             result: interview.result,
+            reason_code: interview.reason_code,
+            feedback: interview.feedback,
+            score: interview.score,
             created_at: interview.created_at,
             updated_at: interview.updated_at,
         }
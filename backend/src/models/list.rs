@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Envelope for list endpoints, so metadata can grow without breaking clients
+#[derive(Debug, Serialize)]
+pub struct ListResponse<T> {
+    pub data: Vec<T>,
+    pub meta: ListMeta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListMeta {
+    /// Items matching the request, across all pages
+    pub total: i64,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub generated_at: DateTime<Utc>,
+}
+
+impl<T> ListResponse<T> {
+    pub fn new(data: Vec<T>, total: i64) -> Self {
+        Self {
+            data,
+            meta: ListMeta {
+                total,
+                generated_at: Utc::now(),
+            },
+        }
+    }
+}
@@ -1,4 +1,11 @@
 pub mod application;
+pub mod cohort;
+pub mod document;
+pub mod file_access;
 pub mod interview;
+pub mod list;
+pub mod notification;
 pub mod screening;
+pub mod setting;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Where a notification is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "notification_channel", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationChannel {
+    /// Written to the application log instead of being sent anywhere
+    Log,
+    Email,
+    Webhook,
+    Slack,
+}
+
+/// State of a row in `notification_outbox`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "notification_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Waiting for its first attempt or a retry
+    Pending,
+    Sent,
+    /// Gave up after the last allowed attempt
+    Failed,
+}
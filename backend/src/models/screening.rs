@@ -7,9 +7,17 @@ pub struct Screening {
     pub id: i32,
     pub application_id: i32,
     pub file_path: Option<String>,
+    /// Name the recording is downloaded under
+    pub original_filename: Option<String>,
     pub screening_date: Option<NaiveDate>,
     pub result: Option<ScreeningResult>,
+    pub reason_code: Option<String>,
+    pub feedback: Option<String>,
+    /// Graded outcome on the `outcome.score_scale` scale, independent of `result`
+    pub score: Option<i16>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -26,16 +34,27 @@ pub struct UpdateScreeningRequest {
     pub screening_date: Option<NaiveDate>,
     #[serde(rename = "screening_status")]
     pub result: Option<ScreeningResult>,
+    pub reason_code: Option<String>,
+    pub feedback: Option<String>,
+    pub score: Option<i16>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ScreeningResponse {
     pub id: i32,
     pub application_id: i32,
     pub file_path: Option<String>,
+    /// Name the recording is downloaded under
+    pub original_filename: Option<String>,
     pub screening_date: Option<NaiveDate>,
     pub result: Option<ScreeningResult>,
+    pub reason_code: Option<String>,
+    pub feedback: Option<String>,
+    /// Graded outcome on the `outcome.score_scale` scale, independent of `result`
+    pub score: Option<i16>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -45,8 +64,12 @@ impl From<Screening> for ScreeningResponse {
             id: screening.id,
             application_id: screening.application_id,
             file_path: screening.file_path,
+            original_filename: screening.original_filename,
             screening_date: screening.screening_date,
             result: screening.result,
+            reason_code: screening.reason_code,
+            feedback: screening.feedback,
+            score: screening.score,
             created_at: screening.created_at,
             updated_at: screening.updated_at,
         }
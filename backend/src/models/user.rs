@@ -11,8 +11,14 @@ pub struct User {
     pub first_name: String,
     pub last_name: String,
     pub role: UserRole,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub updated_at: DateTime<Utc>,
+    pub cohort_id: Option<i32>,
+    pub token_version: i32,
+    pub password_reset_required: bool,
+    pub onboarding_completed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -23,6 +29,22 @@ pub enum UserRole {
     Admin,
 }
 
+/// Public sign-up. There is no cohort here: new accounts start unassigned
+/// until an admin places them with `/admin/users/:user_id/cohort`.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RegisterRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 6))]
+    pub password: String,
+    #[validate(length(min = 1))]
+    pub first_name: String,
+    #[validate(length(min = 1))]
+    pub last_name: String,
+    pub admin_code: Option<String>,
+}
+
+/// An account created by an existing admin
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateUserRequest {
     #[validate(email)]
@@ -34,7 +56,17 @@ pub struct CreateUserRequest {
     #[validate(length(min = 1))]
     pub last_name: String,
     pub role: Option<UserRole>,
-    pub admin_code: Option<String>,
+    pub cohort_id: Option<i32>,
+}
+
+/// Signs in with the current password and replaces it. Works without a token,
+/// so users whose login is refused with `PASSWORD_RESET_REQUIRED` can use it.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(email)]
+    pub email: String,
+    pub current_password: String,
+    pub new_password: String,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -51,7 +83,39 @@ pub struct UserResponse {
     pub first_name: String,
     pub last_name: String,
     pub role: UserRole,
+    pub cohort_id: Option<i32>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub created_at: DateTime<Utc>,
+    /// The client shows the first-run flow while this is false
+    pub onboarding_completed: bool,
+    /// Set on `/auth/me` under a read-only impersonation token, so the client
+    /// can show whose view this is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonated_by: Option<i32>,
+}
+
+/// A student in the admin list, with how many applications they have tracked
+#[derive(Debug, Serialize)]
+pub struct StudentResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub application_count: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct StudentRow {
+    #[sqlx(flatten)]
+    pub user: User,
+    pub application_count: i64,
+}
+
+impl From<StudentRow> for StudentResponse {
+    fn from(row: StudentRow) -> Self {
+        Self {
+            user: UserResponse::from(row.user),
+            application_count: row.application_count,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -68,7 +132,10 @@ impl From<User> for UserResponse {
             first_name: user.first_name,
             last_name: user.last_name,
             role: user.role,
+            cohort_id: user.cohort_id,
             created_at: user.created_at,
+            onboarding_completed: user.onboarding_completed,
+            impersonated_by: None,
         }
     }
 }
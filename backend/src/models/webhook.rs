@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::services::webhooks::WebhookEventType;
+
+/// A webhook without its signing secret, which is only returned on creation
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Webhook {
+    pub id: i32,
+    /// `None` for webhooks receiving events from every cohort
+    pub cohort_id: Option<i32>,
+    pub url: String,
+    /// Empty means every event
+    pub event_types: Vec<String>,
+    pub active: bool,
+    pub created_by: Option<i32>,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWebhookRequest {
+    #[validate(url, length(max = 2048))]
+    pub url: String,
+    pub cohort_id: Option<i32>,
+    #[serde(default)]
+    pub event_types: Vec<WebhookEventType>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// Key for verifying the `X-Webhook-Signature` header; store it now
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// `pending`, `delivered` or `failed`
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339::option")]
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesQuery {
+    pub status: Option<String>,
+}
@@ -1,11 +1,13 @@
+use crate::utils::database::with_retry;
 use crate::utils::logger::LOGGER;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 use std::time::Instant;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ActivityData {
+    /// ISO 8601 calendar date (`YYYY-MM-DD`), not an instant
     pub date: String,
     pub applications_count: i32,
     pub screenings_count: i32,
@@ -72,9 +74,7 @@ impl ActivityService {
             ORDER BY date
         "#;
 
-        let rows = sqlx::query(query)
-            .bind(user_id)
-            .fetch_all(&self.pool)
+        let rows = with_retry(|| sqlx::query(query).bind(user_id).fetch_all(&self.pool))
             .await
             .map_err(|e| ActivityError::DatabaseError(e.to_string()))?;
 
@@ -115,7 +115,10 @@ impl ActivityService {
     }
 
     /// Get admin activity overview (all users)
-    pub async fn get_admin_activity(&self) -> Result<Vec<ActivityData>, ActivityError> {
+    pub async fn get_admin_activity(
+        &self,
+        cohort_id: Option<i32>,
+    ) -> Result<Vec<ActivityData>, ActivityError> {
         let start_time = Instant::now();
 
         LOGGER.log_business_event("admin_activity_request_started", None, HashMap::new());
@@ -130,23 +133,27 @@ impl ActivityService {
             LEFT JOIN (
                 SELECT DATE(created_at) as date, COUNT(*)::int as applications_count
                 FROM applications 
+                WHERE $1::int IS NULL OR cohort_id = $1
                 GROUP BY DATE(created_at)
             ) a ON CURRENT_DATE - s.i = a.date
             LEFT JOIN (
                 SELECT DATE(s.created_at) as date, COUNT(*)::int as screenings_count
                 FROM screenings s
+                JOIN applications ap ON s.application_id = ap.id
+                WHERE $1::int IS NULL OR ap.cohort_id = $1
                 GROUP BY DATE(s.created_at)
             ) sc ON CURRENT_DATE - s.i = sc.date
             LEFT JOIN (
                 SELECT DATE(i.created_at) as date, COUNT(*)::int as interviews_count
                 FROM interviews i
+                JOIN applications ap ON i.application_id = ap.id
+                WHERE $1::int IS NULL OR ap.cohort_id = $1
                 GROUP BY DATE(i.created_at)
             ) i ON CURRENT_DATE - s.i = i.date
             ORDER BY date
         "#;
 
-        let rows = sqlx::query(query)
-            .fetch_all(&self.pool)
+        let rows = with_retry(|| sqlx::query(query).bind(cohort_id).fetch_all(&self.pool))
             .await
             .map_err(|e| ActivityError::DatabaseError(e.to_string()))?;
 
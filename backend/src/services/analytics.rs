@@ -1,24 +1,181 @@
 use crate::handlers::admin::*;
 use crate::models::application::ApplicationResponse;
+use crate::services::cache::{data_version, CacheContext, CacheError, CacheService};
+use crate::services::metrics::{percent_of, INDUSTRY_CLASSIFICATION_SQL};
+use crate::utils::concurrency::ConcurrencyLimit;
+use crate::utils::database::{is_statement_timeout, with_retry};
 use crate::utils::logger::LOGGER;
+use chrono::{Duration, Utc};
 use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 use std::time::Instant;
 
+/// TTL for version-keyed analytics entries
+const ANALYTICS_CACHE_TTL_HOURS: i64 = 24;
+
+/// Bumped whenever `AnalyticsResponse` gains a required field, so entries
+/// cached in the old shape are never read back
+const ANALYTICS_CACHE_FORMAT: u32 = 2;
+
+/// Views created in `008_analytics_materialized_views.sql`
+const ANALYTICS_MATERIALIZED_VIEWS: &[&str] = &[
+    "analytics_status_summary",
+    "analytics_company_summary",
+    "analytics_success_summary",
+];
+
 #[derive(Debug)]
 pub struct AnalyticsService {
     pool: PgPool,
+    /// Restrict every query to this cohort; `None` covers all cohorts
+    cohort_id: Option<i32>,
+}
+
+/// Funnel totals behind `SuccessRateStats`
+#[derive(Debug, Default, Clone, Copy)]
+struct SuccessCounts {
+    total_apps: i64,
+    interview_passed: i64,
+    screening_passed: i64,
+    apps_with_urls: i64,
+    apps_without_urls: i64,
+    offers_received: i64,
+    offers_accepted: i64,
+}
+
+impl SuccessCounts {
+    /// Every rate is 0 when the stage it is measured against saw nothing
+    fn rates(&self) -> SuccessRateStats {
+        SuccessRateStats {
+            // An application only counts as a success once it produced an offer
+            overall_success_rate: percent_of(self.offers_received, self.total_apps),
+            screening_to_interview_rate: percent_of(self.interview_passed, self.screening_passed),
+            interview_success_rate: if self.interview_passed > 0 {
+                percent_of(self.interview_passed, self.screening_passed.max(1))
+            } else {
+                0.0
+            },
+            offer_rate: percent_of(self.offers_received, self.interview_passed),
+            offer_acceptance_rate: percent_of(self.offers_accepted, self.offers_received),
+            offers_received: self.offers_received,
+            offers_accepted: self.offers_accepted,
+            applications_with_urls: self.apps_with_urls,
+            applications_without_urls: self.apps_without_urls,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum AnalyticsError {
     DatabaseError(String),
-    PermissionDenied,
+    /// A query ran past `statement_timeout`
+    QueryTimeout,
+    /// Too many computations were already running and none finished in time
+    Overloaded,
 }
 
 impl AnalyticsService {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, cohort_id: Option<i32>) -> Self {
+        Self { pool, cohort_id }
+    }
+
+    /// Analytics cached under a key derived from the data version.
+    ///
+    /// The key changes whenever the underlying tables change, so the long TTL
+    /// only bounds how long superseded entries linger. The current date is part
+    /// of the key because the stale-application window is relative to today.
+    ///
+    /// Only cache misses take a slot from `limit`, so served-from-cache requests
+    /// never queue behind a computation.
+    pub async fn get_cached_analytics(
+        &self,
+        cache: &CacheService,
+        limit: &ConcurrencyLimit,
+        context: Option<&CacheContext>,
+    ) -> Result<AnalyticsResponse, AnalyticsError> {
+        let version = data_version(&self.pool)
+            .await
+            .map_err(|e| AnalyticsError::DatabaseError(e.to_string()))?;
+        let cache_key = format!(
+            "analytics_f{}_{}_{}_v{}",
+            ANALYTICS_CACHE_FORMAT,
+            self.cohort_id
+                .map(|id| format!("c{}", id))
+                .unwrap_or_else(|| "all".to_string()),
+            Utc::now().date_naive().format("%Y%m%d"),
+            version
+        );
+
+        cache
+            .get_or_compute(
+                &cache_key,
+                Duration::hours(ANALYTICS_CACHE_TTL_HOURS),
+                context,
+                || async {
+                    let Some(_permit) = limit.acquire().await else {
+                        return Err(CacheError::Overloaded);
+                    };
+                    self.get_comprehensive_analytics()
+                        .await
+                        .map_err(|e| match e {
+                            AnalyticsError::DatabaseError(msg) => CacheError::DatabaseError(msg),
+                            AnalyticsError::QueryTimeout => CacheError::QueryTimeout,
+                            AnalyticsError::Overloaded => CacheError::Overloaded,
+                        })
+                },
+            )
+            .await
+            .map_err(|e| match e {
+                CacheError::DatabaseError(msg) | CacheError::SerializationError(msg) => {
+                    AnalyticsError::DatabaseError(msg)
+                }
+                CacheError::QueryTimeout => AnalyticsError::QueryTimeout,
+                CacheError::Overloaded => AnalyticsError::Overloaded,
+                CacheError::NotFound => {
+                    AnalyticsError::DatabaseError("Analytics unavailable".to_string())
+                }
+            })
+    }
+
+    /// Recomputes the analytics materialized views.
+    ///
+    /// Status, company and success-rate figures read from these views, so they lag
+    /// behind live data until the next refresh. Cached analytics are dropped so the
+    /// refreshed numbers show up immediately.
+    pub async fn refresh_materialized_views(
+        pool: &PgPool,
+        cache: &CacheService,
+        context: Option<&CacheContext>,
+    ) -> Result<(), AnalyticsError> {
+        let start_time = Instant::now();
+
+        for view in ANALYTICS_MATERIALIZED_VIEWS {
+            sqlx::query(&format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {}", view))
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    if is_statement_timeout(&e) {
+                        AnalyticsError::QueryTimeout
+                    } else {
+                        AnalyticsError::DatabaseError(e.to_string())
+                    }
+                })?;
+        }
+
+        cache
+            .invalidate_pattern("analytics_", context)
+            .await
+            .map_err(|_| {
+                AnalyticsError::DatabaseError("Failed to clear analytics cache".to_string())
+            })?;
+
+        LOGGER.log_performance_metric(
+            "analytics_views_refresh_duration",
+            start_time.elapsed().as_millis() as f64,
+            HashMap::new(),
+        );
+
+        Ok(())
     }
 
     pub async fn get_comprehensive_analytics(&self) -> Result<AnalyticsResponse, AnalyticsError> {
@@ -35,7 +192,10 @@ impl AnalyticsService {
             self.get_screening_stats(),
             self.get_interview_stats(),
             self.get_success_rate_stats(),
-            self.get_top_performing_students()
+            self.get_top_performing_students(),
+            self.get_compensation_stats(),
+            self.get_failure_reasons(),
+            self.get_company_scores()
         );
 
         let duration = start_time.elapsed();
@@ -56,6 +216,9 @@ impl AnalyticsService {
                 interview_stats,
                 success_rate,
                 top_performing_students,
+                compensation,
+                failure_reasons,
+                company_scores,
             )) => {
                 let daily_stats = vec![]; // Simplified for now
                 let response_times = ResponseTimeStats {
@@ -66,6 +229,8 @@ impl AnalyticsService {
                 };
 
                 let analytics = AnalyticsResponse {
+                    has_data: total_applications > 0,
+                    sample_size: total_applications,
                     total_students,
                     total_applications,
                     status_breakdown,
@@ -78,12 +243,16 @@ impl AnalyticsService {
                     success_rate,
                     response_times,
                     top_performing_students,
+                    compensation,
+                    failure_reasons,
+                    company_scores,
                 };
 
                 LOGGER.log_business_event("analytics_request_completed", None, HashMap::new());
 
                 Ok(analytics)
             }
+            Err(e) if is_statement_timeout(&e) => Err(AnalyticsError::QueryTimeout),
             Err(_) => Err(AnalyticsError::DatabaseError(
                 "Failed to fetch analytics".to_string(),
             )),
@@ -91,24 +260,33 @@ impl AnalyticsService {
     }
 
     async fn get_basic_counts(&self) -> Result<(i64, i64), sqlx::Error> {
-        let row = sqlx::query(
-            "SELECT 
-                (SELECT COUNT(*)::bigint FROM users WHERE role = 'student') as students,
-                (SELECT COUNT(*)::bigint FROM applications) as applications",
-        )
-        .fetch_one(&self.pool)
+        let row = with_retry(|| {
+            sqlx::query(
+                "SELECT 
+                (SELECT COUNT(*)::bigint FROM users
+                 WHERE role = 'student' AND ($1::int IS NULL OR cohort_id = $1)) as students,
+                (SELECT COUNT(*)::bigint FROM applications
+                 WHERE $1::int IS NULL OR cohort_id = $1) as applications",
+            )
+            .bind(self.cohort_id)
+            .fetch_one(&self.pool)
+        })
         .await?;
 
         Ok((row.get(0), row.get(1)))
     }
 
     async fn get_status_breakdown(&self) -> Result<HashMap<String, i64>, sqlx::Error> {
-        let rows = sqlx::query(
-            "SELECT status::text, COUNT(*)::bigint as count 
-             FROM applications 
-             GROUP BY status",
-        )
-        .fetch_all(&self.pool)
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT status, SUM(application_count)::bigint as count
+                 FROM analytics_status_summary
+                 WHERE $1::int IS NULL OR cohort_key = $1
+                 GROUP BY status",
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+        })
         .await?;
 
         let mut breakdown = HashMap::new();
@@ -122,14 +300,19 @@ impl AnalyticsService {
     }
 
     async fn get_company_stats(&self) -> Result<Vec<CompanyStats>, sqlx::Error> {
-        let rows = sqlx::query(
-            "SELECT company, COUNT(*)::bigint as count, COUNT(DISTINCT user_id)::bigint as unique_students
-             FROM applications 
-             GROUP BY company 
-             ORDER BY count DESC 
-             LIMIT 10"
-        )
-        .fetch_all(&self.pool)
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT MIN(company) as company, SUM(application_count)::bigint as count,
+                        SUM(unique_students)::bigint as unique_students
+                 FROM analytics_company_summary
+                 WHERE $1::int IS NULL OR cohort_key = $1
+                 GROUP BY company_key
+                 ORDER BY count DESC
+                 LIMIT 10",
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+        })
         .await?;
 
         let mut stats = Vec::new();
@@ -145,15 +328,19 @@ impl AnalyticsService {
     }
 
     async fn get_popular_job_urls(&self) -> Result<Vec<JobUrlStats>, sqlx::Error> {
-        let rows = sqlx::query(
-            "SELECT job_url, COUNT(*)::bigint as count, COUNT(DISTINCT user_id)::bigint as unique_students
-             FROM applications 
-             WHERE job_url IS NOT NULL
-             GROUP BY job_url 
-             ORDER BY count DESC 
-             LIMIT 5"
-        )
-        .fetch_all(&self.pool)
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT job_url, COUNT(*)::bigint as count, COUNT(DISTINCT user_id)::bigint as unique_students
+                 FROM applications 
+                 WHERE job_url IS NOT NULL
+                   AND ($1::int IS NULL OR cohort_id = $1)
+                 GROUP BY job_url 
+                 ORDER BY count DESC 
+                 LIMIT 5"
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+        })
         .await?;
 
         let mut stats = Vec::new();
@@ -169,28 +356,37 @@ impl AnalyticsService {
     }
 
     async fn get_stale_applications(&self) -> Result<Vec<ApplicationResponse>, sqlx::Error> {
-        let rows = sqlx::query_as::<_, crate::models::application::Application>(
-            "SELECT * FROM applications 
-             WHERE updated_at < NOW() - INTERVAL '7 days' 
-               AND status NOT IN ('rejected', 'next_stage')
-             ORDER BY updated_at ASC
+        let rows = with_retry(|| {
+            sqlx::query_as::<_, crate::models::application::Application>(
+                "SELECT * FROM applications 
+             WHERE last_activity_at < NOW() - INTERVAL '7 days' 
+               AND status NOT IN ('rejected', 'next_stage', 'accepted')
+               AND ($1::int IS NULL OR cohort_id = $1)
+             ORDER BY last_activity_at ASC
              LIMIT 5",
-        )
-        .fetch_all(&self.pool)
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+        })
         .await?;
 
         Ok(rows.into_iter().map(ApplicationResponse::from).collect())
     }
 
     async fn get_screening_stats(&self) -> Result<ScreeningStats, sqlx::Error> {
-        let row = sqlx::query(
-            "SELECT 
+        let row = with_retry(|| {
+            sqlx::query(
+                "SELECT 
                 COUNT(*)::bigint as total,
                 COUNT(CASE WHEN result = 'passed' THEN 1 END)::bigint as passed,
                 COUNT(CASE WHEN result = 'failed' THEN 1 END)::bigint as failed
-             FROM screenings",
-        )
-        .fetch_one(&self.pool)
+             FROM screenings s
+             JOIN applications a ON a.id = s.application_id
+             WHERE $1::int IS NULL OR a.cohort_id = $1",
+            )
+            .bind(self.cohort_id)
+            .fetch_one(&self.pool)
+        })
         .await?;
 
         let total: i64 = row.get(0);
@@ -206,14 +402,19 @@ impl AnalyticsService {
     }
 
     async fn get_interview_stats(&self) -> Result<InterviewStats, sqlx::Error> {
-        let row = sqlx::query(
-            "SELECT 
+        let row = with_retry(|| {
+            sqlx::query(
+                "SELECT 
                 COUNT(*)::bigint as total,
                 COUNT(CASE WHEN result = 'passed' THEN 1 END)::bigint as passed,
                 COUNT(CASE WHEN result = 'failed' THEN 1 END)::bigint as failed
-             FROM interviews",
-        )
-        .fetch_one(&self.pool)
+             FROM interviews i
+             JOIN applications a ON a.id = i.application_id
+             WHERE $1::int IS NULL OR a.cohort_id = $1",
+            )
+            .bind(self.cohort_id)
+            .fetch_one(&self.pool)
+        })
         .await?;
 
         let total: i64 = row.get(0);
@@ -228,70 +429,142 @@ impl AnalyticsService {
         })
     }
 
-    async fn get_success_rate_stats(&self) -> Result<SuccessRateStats, sqlx::Error> {
-        let row = sqlx::query(
-            "SELECT 
-                (SELECT COUNT(*)::bigint FROM applications) as total_apps,
-                (SELECT COUNT(*)::bigint FROM interviews WHERE result = 'passed') as interview_passed,
-                (SELECT COUNT(*)::bigint FROM screenings WHERE result = 'passed') as screening_passed,
-                (SELECT COUNT(*)::bigint FROM applications WHERE job_url IS NOT NULL) as apps_with_urls,
-                (SELECT COUNT(*)::bigint FROM applications WHERE job_url IS NULL) as apps_without_urls"
-        )
-        .fetch_one(&self.pool)
+    /// Most common reasons screenings and interviews were failed
+    async fn get_failure_reasons(&self) -> Result<Vec<FailureReasonStats>, sqlx::Error> {
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT stage, reason_code, COUNT(*)::bigint as count
+                 FROM (
+                     SELECT 'screening' as stage, s.reason_code
+                     FROM screenings s
+                     JOIN applications a ON a.id = s.application_id
+                     WHERE s.result = 'failed' AND s.reason_code IS NOT NULL
+                       AND ($1::int IS NULL OR a.cohort_id = $1)
+                     UNION ALL
+                     SELECT 'interview' as stage, i.reason_code
+                     FROM interviews i
+                     JOIN applications a ON a.id = i.application_id
+                     WHERE i.result = 'failed' AND i.reason_code IS NOT NULL
+                       AND ($1::int IS NULL OR a.cohort_id = $1)
+                 ) failures
+                 GROUP BY stage, reason_code
+                 ORDER BY count DESC, stage, reason_code
+                 LIMIT 10",
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+        })
         .await?;
 
-        let total_apps: i64 = row.get(0);
-        let interview_passed: i64 = row.get(1);
-        let screening_passed: i64 = row.get(2);
-        let apps_with_urls: i64 = row.get(3);
-        let apps_without_urls: i64 = row.get(4);
-
-        let overall_success_rate = if total_apps > 0 {
-            (interview_passed as f64 / total_apps as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let screening_to_interview_rate = if screening_passed > 0 {
-            (interview_passed as f64 / screening_passed as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let interview_success_rate = if interview_passed > 0 {
-            (interview_passed as f64 / (screening_passed as f64).max(1.0)) * 100.0
-        } else {
-            0.0
-        };
-
-        Ok(SuccessRateStats {
-            overall_success_rate,
-            screening_to_interview_rate,
-            interview_success_rate,
-            applications_with_urls: apps_with_urls,
-            applications_without_urls: apps_without_urls,
+        Ok(rows
+            .into_iter()
+            .map(|row| FailureReasonStats {
+                stage: row.get(0),
+                reason_code: row.get(1),
+                count: row.get(2),
+            })
+            .collect())
+    }
+
+    /// Average scores per company, for the companies with the most scored outcomes
+    async fn get_company_scores(&self) -> Result<Vec<CompanyScoreStats>, sqlx::Error> {
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT MODE() WITHIN GROUP (ORDER BY company) as company,
+                        AVG(score) FILTER (WHERE stage = 'screening')::float8 as avg_screening,
+                        AVG(score) FILTER (WHERE stage = 'interview')::float8 as avg_interview,
+                        COUNT(*)::bigint as scored_count
+                 FROM (
+                     SELECT 'screening' as stage, s.score, a.company,
+                            COALESCE(a.company_normalized, normalize_company(a.company)) as company_key
+                     FROM screenings s
+                     JOIN applications a ON a.id = s.application_id
+                     WHERE s.score IS NOT NULL
+                       AND ($1::int IS NULL OR a.cohort_id = $1)
+                     UNION ALL
+                     SELECT 'interview' as stage, i.score, a.company,
+                            COALESCE(a.company_normalized, normalize_company(a.company)) as company_key
+                     FROM interviews i
+                     JOIN applications a ON a.id = i.application_id
+                     WHERE i.score IS NOT NULL
+                       AND ($1::int IS NULL OR a.cohort_id = $1)
+                 ) scored
+                 GROUP BY company_key
+                 ORDER BY scored_count DESC, company
+                 LIMIT 10",
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
         })
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CompanyScoreStats {
+                company: row.get(0),
+                avg_screening_score: row.get(1),
+                avg_interview_score: row.get(2),
+                scored_count: row.get(3),
+            })
+            .collect())
+    }
+
+    async fn get_success_rate_stats(&self) -> Result<SuccessRateStats, sqlx::Error> {
+        let row = with_retry(|| {
+            sqlx::query(
+                "SELECT
+                    COALESCE(SUM(total_apps), 0)::bigint as total_apps,
+                    COALESCE(SUM(interview_passed), 0)::bigint as interview_passed,
+                    COALESCE(SUM(screening_passed), 0)::bigint as screening_passed,
+                    COALESCE(SUM(apps_with_urls), 0)::bigint as apps_with_urls,
+                    COALESCE(SUM(apps_without_urls), 0)::bigint as apps_without_urls,
+                    COALESCE(SUM(offers_received), 0)::bigint as offers_received,
+                    COALESCE(SUM(offers_accepted), 0)::bigint as offers_accepted
+                 FROM analytics_success_summary
+                 WHERE $1::int IS NULL OR cohort_key = $1",
+            )
+            .bind(self.cohort_id)
+            .fetch_one(&self.pool)
+        })
+        .await?;
+
+        Ok(SuccessCounts {
+            total_apps: row.get(0),
+            interview_passed: row.get(1),
+            screening_passed: row.get(2),
+            apps_with_urls: row.get(3),
+            apps_without_urls: row.get(4),
+            offers_received: row.get(5),
+            offers_accepted: row.get(6),
+        }
+        .rates())
     }
 
     async fn get_top_performing_students(&self) -> Result<Vec<StudentPerformance>, sqlx::Error> {
-        let rows = sqlx::query(
-            "SELECT 
-                u.email,
-                u.first_name || ' ' || u.last_name as name,
-                COUNT(a.id)::bigint as total_applications,
-                COUNT(CASE WHEN s.result = 'passed' THEN 1 END)::bigint as screenings_passed,
-                COUNT(CASE WHEN i.result = 'passed' THEN 1 END)::bigint as interviews_passed
-             FROM users u
-             LEFT JOIN applications a ON u.id = a.user_id
-             LEFT JOIN screenings s ON a.id = s.application_id
-             LEFT JOIN interviews i ON a.id = i.application_id
-             WHERE u.role = 'student'
-             GROUP BY u.id, u.email, u.first_name, u.last_name
-             HAVING COUNT(a.id) > 0
-             ORDER BY COUNT(CASE WHEN i.result = 'passed' THEN 1 END) DESC
-             LIMIT 5",
-        )
-        .fetch_all(&self.pool)
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT 
+                    u.email,
+                    u.first_name || ' ' || u.last_name as name,
+                    COUNT(a.id)::bigint as total_applications,
+                    COUNT(CASE WHEN s.result = 'passed' THEN 1 END)::bigint as screenings_passed,
+                    COUNT(CASE WHEN i.result = 'passed' THEN 1 END)::bigint as interviews_passed,
+                    COUNT(CASE WHEN a.status IN ('offer', 'accepted') THEN 1 END)::bigint as offers_received
+                 FROM users u
+                 LEFT JOIN applications a ON u.id = a.user_id
+                 LEFT JOIN screenings s ON a.id = s.application_id
+                 LEFT JOIN interviews i ON a.id = i.application_id
+                 WHERE u.role = 'student'
+                   AND ($1::int IS NULL OR u.cohort_id = $1)
+                 GROUP BY u.id, u.email, u.first_name, u.last_name
+                 HAVING COUNT(a.id) > 0
+                 ORDER BY COUNT(CASE WHEN a.status IN ('offer', 'accepted') THEN 1 END) DESC,
+                          COUNT(CASE WHEN i.result = 'passed' THEN 1 END) DESC
+                 LIMIT 5",
+            )
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+        })
         .await?;
 
         let mut students = Vec::new();
@@ -299,12 +572,9 @@ impl AnalyticsService {
             let total_apps: i64 = row.get(2);
             let screenings_passed: i64 = row.get(3);
             let interviews_passed: i64 = row.get(4);
+            let offers_received: i64 = row.get(5);
 
-            let success_rate = if total_apps > 0 {
-                (interviews_passed as f64 / total_apps as f64) * 100.0
-            } else {
-                0.0
-            };
+            let success_rate = percent_of(offers_received, total_apps);
 
             students.push(StudentPerformance {
                 student_email: row.get(0),
@@ -312,10 +582,122 @@ impl AnalyticsService {
                 total_applications: total_apps,
                 screenings_passed,
                 interviews_passed,
+                offers_received,
                 success_rate,
             });
         }
 
         Ok(students)
     }
+
+    async fn get_compensation_stats(&self) -> Result<CompensationStats, sqlx::Error> {
+        let (by_company, by_industry) = tokio::try_join!(
+            self.get_compensation_groups("company"),
+            self.get_compensation_groups(INDUSTRY_CLASSIFICATION_SQL)
+        )?;
+
+        Ok(CompensationStats {
+            by_company,
+            by_industry,
+        })
+    }
+
+    /// Median offered compensation (range midpoint) grouped by `group_expr`,
+    /// for applications that reached the offer stage. Currencies are never mixed.
+    async fn get_compensation_groups(
+        &self,
+        group_expr: &str,
+    ) -> Result<Vec<CompensationGroup>, sqlx::Error> {
+        let query = format!(
+            "SELECT
+                {} as group_name,
+                currency,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (
+                    ORDER BY (COALESCE(salary_min, salary_max) + COALESCE(salary_max, salary_min)) / 2.0
+                ) as median_offered,
+                COUNT(*)::bigint as offers_count
+             FROM applications
+             WHERE status IN ('offer', 'accepted')
+               AND currency IS NOT NULL
+               AND (salary_min IS NOT NULL OR salary_max IS NOT NULL)
+               AND ($1::int IS NULL OR cohort_id = $1)
+             GROUP BY group_name, currency
+             ORDER BY offers_count DESC, median_offered DESC
+             LIMIT 20",
+            group_expr
+        );
+
+        let rows = with_retry(|| {
+            sqlx::query(&query)
+                .bind(self.cohort_id)
+                .fetch_all(&self.pool)
+        })
+        .await?;
+
+        let mut groups = Vec::new();
+        for row in rows {
+            groups.push(CompensationGroup {
+                name: row.get(0),
+                currency: row.get(1),
+                median_offered: row.get(2),
+                offers_count: row.get(3),
+            });
+        }
+
+        Ok(groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_rates_without_applications_are_zero() {
+        let stats = SuccessCounts::default().rates();
+        assert_eq!(stats.overall_success_rate, 0.0);
+        assert_eq!(stats.screening_to_interview_rate, 0.0);
+        assert_eq!(stats.interview_success_rate, 0.0);
+        assert_eq!(stats.offer_rate, 0.0);
+        assert_eq!(stats.offer_acceptance_rate, 0.0);
+        assert_eq!(stats.offers_received, 0);
+        assert_eq!(stats.offers_accepted, 0);
+        assert_eq!(stats.applications_with_urls, 0);
+        assert_eq!(stats.applications_without_urls, 0);
+    }
+
+    #[test]
+    fn success_rates_for_empty_later_stages_are_zero() {
+        // Applications went out, but nothing came back yet
+        let stats = SuccessCounts {
+            total_apps: 4,
+            apps_with_urls: 3,
+            apps_without_urls: 1,
+            ..SuccessCounts::default()
+        }
+        .rates();
+        assert_eq!(stats.overall_success_rate, 0.0);
+        assert_eq!(stats.screening_to_interview_rate, 0.0);
+        assert_eq!(stats.interview_success_rate, 0.0);
+        assert_eq!(stats.offer_rate, 0.0);
+        assert_eq!(stats.offer_acceptance_rate, 0.0);
+    }
+
+    #[test]
+    fn success_rates_with_data() {
+        let stats = SuccessCounts {
+            total_apps: 10,
+            screening_passed: 4,
+            interview_passed: 2,
+            offers_received: 1,
+            offers_accepted: 1,
+            ..SuccessCounts::default()
+        }
+        .rates();
+        assert_eq!(stats.overall_success_rate, 10.0);
+        assert_eq!(stats.screening_to_interview_rate, 50.0);
+        assert_eq!(stats.interview_success_rate, 50.0);
+        assert_eq!(stats.offer_rate, 50.0);
+        assert_eq!(stats.offer_acceptance_rate, 100.0);
+    }
 }
@@ -1,16 +1,114 @@
+use crate::middleware::request_id::RequestId;
 use crate::utils::logger::LOGGER;
 use chrono::{DateTime, Duration, Utc};
+use password_hash::rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 
+/// Upper bound for TTL jitter as a fraction of the TTL (10%)
+const TTL_JITTER_DIVISOR: i64 = 10;
+
+/// Extend a TTL by a random amount (up to 10%) so entries written together
+/// don't all expire at the same instant
+pub fn ttl_with_jitter(ttl: Duration) -> Duration {
+    let max_jitter = ttl.num_seconds() / TTL_JITTER_DIVISOR;
+    if max_jitter <= 0 {
+        return ttl;
+    }
+
+    let jitter = (OsRng.next_u64() % (max_jitter as u64 + 1)) as i64;
+    ttl + Duration::seconds(jitter)
+}
+
 /// Production-ready caching service with multiple storage backends
 #[derive(Debug)]
 pub struct CacheService {
     pool: PgPool,
     in_memory_cache: std::sync::RwLock<HashMap<String, CacheEntry>>,
     max_memory_entries: usize,
+    /// Entries dropped from memory to make room, since startup
+    evictions: AtomicU64,
+    /// Lookups per key prefix, since startup
+    prefix_lookups: std::sync::Mutex<HashMap<String, PrefixStats>>,
+    compute_locks: std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+/// Fingerprint of the tracked tables' contents.
+///
+/// Changes whenever a row is inserted, updated or deleted in `users`,
+/// `applications`, `screenings` or `interviews`, so keys derived from it are
+/// invalidated automatically and can use a long TTL.
+pub async fn data_version(pool: &PgPool) -> Result<String, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT
+            GREATEST(
+                (SELECT MAX(updated_at) FROM users),
+                (SELECT MAX(updated_at) FROM applications),
+                (SELECT MAX(updated_at) FROM screenings),
+                (SELECT MAX(updated_at) FROM interviews)
+            ) as last_modified,
+            (SELECT COUNT(*) FROM users)
+                + (SELECT COUNT(*) FROM applications)
+                + (SELECT COUNT(*) FROM screenings)
+                + (SELECT COUNT(*) FROM interviews) as total_rows",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let last_modified: Option<DateTime<Utc>> = row.get(0);
+    let total_rows: i64 = row.get::<Option<i64>, _>(1).unwrap_or(0);
+
+    Ok(format!(
+        "{}_{}",
+        last_modified.map(|ts| ts.timestamp_micros()).unwrap_or(0),
+        total_rows
+    ))
+}
+
+/// Who triggered a cache operation.
+///
+/// Attached to cache log entries so they can be joined with the request logs,
+/// e.g. to find out why a particular admin saw stale data.
+#[derive(Debug, Clone, Default)]
+pub struct CacheContext {
+    pub user_id: Option<i32>,
+    pub request_id: Option<String>,
+}
+
+impl CacheContext {
+    pub fn new(user_id: i32, request_id: &RequestId) -> Self {
+        Self {
+            user_id: Some(user_id),
+            request_id: Some(request_id.0.clone()),
+        }
+    }
+}
+
+fn context_user_id(context: Option<&CacheContext>) -> Option<i32> {
+    context.and_then(|ctx| ctx.user_id)
+}
+
+/// Log metadata for `log_business_event`, with the request id when there is one
+fn event_metadata(
+    context: Option<&CacheContext>,
+    fields: Vec<(&str, serde_json::Value)>,
+) -> HashMap<String, serde_json::Value> {
+    let mut metadata: HashMap<String, serde_json::Value> = fields
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    if let Some(request_id) = context.and_then(|ctx| ctx.request_id.as_ref()) {
+        metadata.insert(
+            "request_id".to_string(),
+            serde_json::Value::String(request_id.clone()),
+        );
+    }
+    metadata
 }
 
 #[derive(Debug, Clone)]
@@ -28,26 +126,129 @@ pub struct CacheStats {
     pub memory_usage_mb: f64,
     pub hit_ratio: f64,
     pub average_retrieval_time_ms: f64,
+    pub memory_keys: usize,
+    pub max_memory_entries: usize,
+    /// Entries evicted from memory because it was full, since startup
+    pub evictions: u64,
+    /// Hits and misses per data type, keyed by the part of the cache key
+    /// before the first `_` or `:` (`metrics`, `analytics`, `dashboard`, ...)
+    #[serde(default)]
+    pub by_prefix: BTreeMap<String, PrefixStats>,
+}
+
+/// Lookup outcomes for one key prefix, since startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrefixStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of lookups answered from the cache, 0 when there were none
+    pub hit_ratio: f64,
+}
+
+/// The data type a cache key belongs to, e.g. `metrics` for
+/// `metrics_all_30d_aggregate_v...`
+fn key_prefix(key: &str) -> &str {
+    key.split(['_', ':']).next().unwrap_or(key)
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheEntryDetails {
+    pub key: String,
+    pub value: serde_json::Value,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(with = "crate::utils::time::rfc3339::option")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub hit_count: Option<u64>,
+    pub in_memory: bool,
+    pub persisted: bool,
+    pub expired: bool,
+}
+
+/// A persisted cache entry as exported for backup
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheSnapshotEntry {
+    pub key: String,
+    pub value: serde_json::Value,
+    /// Lifetime left when exported; an import restarts it from the import time
+    pub ttl_seconds: i64,
 }
 
 #[derive(Debug)]
 pub enum CacheError {
     SerializationError(String),
     DatabaseError(String),
+    /// The value computation hit the database statement timeout
+    QueryTimeout,
+    /// The value computation was turned away because too many were running
+    Overloaded,
     NotFound,
 }
 
+/// A caller's share of a key's compute lock.
+///
+/// Dropping it removes the lock from `compute_locks` once nobody else is
+/// waiting on it, including when the caller's future is cancelled mid-compute.
+struct ComputeLock<'a> {
+    locks: &'a std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    key: String,
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ComputeLock<'_> {
+    async fn lock(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.lock.lock().await
+    }
+}
+
+impl Drop for ComputeLock<'_> {
+    fn drop(&mut self) {
+        let mut locks = self
+            .locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Only the map and this caller hold the lock: nobody else is waiting
+        if Arc::strong_count(&self.lock) <= 2 {
+            locks.remove(&self.key);
+        }
+    }
+}
+
 impl CacheService {
     pub fn new(pool: PgPool, max_memory_entries: usize) -> Self {
         Self {
             pool,
             in_memory_cache: std::sync::RwLock::new(HashMap::new()),
             max_memory_entries,
+            evictions: AtomicU64::new(0),
+            prefix_lookups: std::sync::Mutex::new(HashMap::new()),
+            compute_locks: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
+    /// Memory capacity from `CACHE_MAX_ENTRIES` (default 1000)
+    pub fn from_env(pool: PgPool) -> Self {
+        let max_memory_entries = env::var("CACHE_MAX_ENTRIES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(1000);
+
+        Self::new(pool, max_memory_entries)
+    }
+
     /// Get value from cache with fallback strategy
-    pub async fn get<T>(&self, key: &str) -> Result<T, CacheError>
+    pub async fn get<T>(&self, key: &str, context: Option<&CacheContext>) -> Result<T, CacheError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let result = self.lookup(key, context).await;
+        self.record_lookup(key, !matches!(result, Err(CacheError::NotFound)));
+        result
+    }
+
+    /// `get` without counting towards the per-prefix stats
+    async fn lookup<T>(&self, key: &str, context: Option<&CacheContext>) -> Result<T, CacheError>
     where
         T: for<'de> Deserialize<'de>,
     {
@@ -55,7 +256,12 @@ impl CacheService {
 
         // Try memory cache first (fastest)
         if let Ok(value) = self.get_from_memory(key) {
-            self.log_cache_hit("memory", key, start_time.elapsed().as_millis() as f64);
+            self.log_cache_hit(
+                "memory",
+                key,
+                start_time.elapsed().as_millis() as f64,
+                context,
+            );
             return serde_json::from_value(value)
                 .map_err(|e| CacheError::SerializationError(e.to_string()));
         }
@@ -66,19 +272,30 @@ impl CacheService {
                 // Store in memory for next time
                 self.store_in_memory(key, &value, Duration::minutes(30));
 
-                self.log_cache_hit("database", key, start_time.elapsed().as_millis() as f64);
+                self.log_cache_hit(
+                    "database",
+                    key,
+                    start_time.elapsed().as_millis() as f64,
+                    context,
+                );
                 serde_json::from_value(value)
                     .map_err(|e| CacheError::SerializationError(e.to_string()))
             }
             Err(_) => {
-                self.log_cache_miss(key, start_time.elapsed().as_millis() as f64);
+                self.log_cache_miss(key, start_time.elapsed().as_millis() as f64, context);
                 Err(CacheError::NotFound)
             }
         }
     }
 
     /// Store value in cache with TTL
-    pub async fn set<T>(&self, key: &str, value: &T, ttl: Duration) -> Result<(), CacheError>
+    pub async fn set<T>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Duration,
+        context: Option<&CacheContext>,
+    ) -> Result<(), CacheError>
     where
         T: Serialize,
     {
@@ -91,30 +308,31 @@ impl CacheService {
 
         LOGGER.log_business_event(
             "cache_set",
-            None,
-            [
-                (
-                    "cache_key".to_string(),
-                    serde_json::Value::String(key.to_string()),
-                ),
-                (
-                    "ttl_seconds".to_string(),
-                    serde_json::Value::Number(serde_json::Number::from(ttl.num_seconds())),
-                ),
-            ]
-            .iter()
-            .cloned()
-            .collect(),
+            context_user_id(context),
+            event_metadata(
+                context,
+                vec![
+                    ("cache_key", serde_json::Value::String(key.to_string())),
+                    (
+                        "ttl_seconds",
+                        serde_json::Value::Number(serde_json::Number::from(ttl.num_seconds())),
+                    ),
+                ],
+            ),
         );
 
         Ok(())
     }
 
-    /// Get or compute value with caching
+    /// Get or compute value with caching.
+    ///
+    /// Concurrent misses on the same key are serialized through a per-key lock,
+    /// so only the first caller recomputes and the rest read its result.
     pub async fn get_or_compute<T, F, Fut>(
         &self,
         key: &str,
         ttl: Duration,
+        context: Option<&CacheContext>,
         compute_fn: F,
     ) -> Result<T, CacheError>
     where
@@ -123,35 +341,62 @@ impl CacheService {
         Fut: std::future::Future<Output = Result<T, CacheError>>,
     {
         // Try to get from cache first
-        match self.get::<T>(key).await {
+        match self.get::<T>(key, context).await {
+            Ok(value) => return Ok(value),
+            Err(CacheError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        let key_lock = self.compute_lock(key);
+        let _guard = key_lock.lock().await;
+
+        // Another caller may have filled the cache while we were waiting.
+        // The lookup above already counted this call as a miss.
+        match self.lookup::<T>(key, context).await {
             Ok(value) => Ok(value),
             Err(CacheError::NotFound) => {
-                // Compute new value
-                let computed_value = compute_fn().await?;
-
-                // Store in cache
-                self.set(key, &computed_value, ttl).await?;
-
-                LOGGER.log_business_event(
-                    "cache_computed",
-                    None,
-                    [(
-                        "cache_key".to_string(),
-                        serde_json::Value::String(key.to_string()),
-                    )]
-                    .iter()
-                    .cloned()
-                    .collect(),
-                );
-
-                Ok(computed_value)
+                self.compute_and_store(key, ttl, context, compute_fn).await
             }
             Err(e) => Err(e),
         }
     }
 
+    async fn compute_and_store<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        context: Option<&CacheContext>,
+        compute_fn: F,
+    ) -> Result<T, CacheError>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T, CacheError>>,
+    {
+        // Compute new value
+        let computed_value = compute_fn().await?;
+
+        // Store in cache
+        self.set(key, &computed_value, ttl, context).await?;
+
+        LOGGER.log_business_event(
+            "cache_computed",
+            context_user_id(context),
+            event_metadata(
+                context,
+                vec![("cache_key", serde_json::Value::String(key.to_string()))],
+            ),
+        );
+
+        Ok(computed_value)
+    }
+
     /// Invalidate cache key
-    pub async fn invalidate(&self, key: &str) -> Result<(), CacheError> {
+    pub async fn invalidate(
+        &self,
+        key: &str,
+        context: Option<&CacheContext>,
+    ) -> Result<(), CacheError> {
         // Remove from memory
         if let Ok(mut cache) = self.in_memory_cache.write() {
             cache.remove(key);
@@ -166,21 +411,22 @@ impl CacheService {
 
         LOGGER.log_business_event(
             "cache_invalidated",
-            None,
-            [(
-                "cache_key".to_string(),
-                serde_json::Value::String(key.to_string()),
-            )]
-            .iter()
-            .cloned()
-            .collect(),
+            context_user_id(context),
+            event_metadata(
+                context,
+                vec![("cache_key", serde_json::Value::String(key.to_string()))],
+            ),
         );
 
         Ok(())
     }
 
     /// Invalidate cache keys matching pattern
-    pub async fn invalidate_pattern(&self, pattern: &str) -> Result<usize, CacheError> {
+    pub async fn invalidate_pattern(
+        &self,
+        pattern: &str,
+        context: Option<&CacheContext>,
+    ) -> Result<usize, CacheError> {
         let mut invalidated = 0;
 
         // Remove from memory cache
@@ -208,20 +454,17 @@ impl CacheService {
 
         LOGGER.log_business_event(
             "cache_pattern_invalidated",
-            None,
-            [
-                (
-                    "pattern".to_string(),
-                    serde_json::Value::String(pattern.to_string()),
-                ),
-                (
-                    "invalidated_count".to_string(),
-                    serde_json::Value::Number(serde_json::Number::from(invalidated)),
-                ),
-            ]
-            .iter()
-            .cloned()
-            .collect(),
+            context_user_id(context),
+            event_metadata(
+                context,
+                vec![
+                    ("pattern", serde_json::Value::String(pattern.to_string())),
+                    (
+                        "invalidated_count",
+                        serde_json::Value::Number(serde_json::Number::from(invalidated)),
+                    ),
+                ],
+            ),
         );
 
         Ok(invalidated)
@@ -267,6 +510,86 @@ impl CacheService {
             memory_usage_mb: (memory_keys * 1024) as f64 / 1024.0 / 1024.0, // Rough estimate
             hit_ratio,
             average_retrieval_time_ms: avg_retrieval,
+            memory_keys,
+            max_memory_entries: self.max_memory_entries,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            by_prefix: self.prefix_stats(),
+        })
+    }
+
+    fn record_lookup(&self, key: &str, hit: bool) {
+        if let Ok(mut lookups) = self.prefix_lookups.lock() {
+            let stats = lookups.entry(key_prefix(key).to_string()).or_default();
+            if hit {
+                stats.hits += 1;
+            } else {
+                stats.misses += 1;
+            }
+        }
+    }
+
+    fn prefix_stats(&self) -> BTreeMap<String, PrefixStats> {
+        let Ok(lookups) = self.prefix_lookups.lock() else {
+            return BTreeMap::new();
+        };
+
+        lookups
+            .iter()
+            .map(|(prefix, stats)| {
+                let total = stats.hits + stats.misses;
+                let hit_ratio = if total > 0 {
+                    stats.hits as f64 / total as f64
+                } else {
+                    0.0
+                };
+                (
+                    prefix.clone(),
+                    PrefixStats {
+                        hit_ratio,
+                        ..stats.clone()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Inspect a single cache entry without affecting hit counts
+    pub async fn inspect(&self, key: &str) -> Result<CacheEntryDetails, CacheError> {
+        let memory_entry = self
+            .in_memory_cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(key).cloned());
+
+        let db_row =
+            sqlx::query("SELECT value, expires_at, created_at FROM cache_store WHERE key = $1")
+                .bind(key)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| CacheError::DatabaseError(e.to_string()))?;
+
+        let persisted = db_row.is_some();
+
+        let (value, expires_at, created_at, hit_count) = match (memory_entry, db_row) {
+            (Some(entry), _) => (
+                entry.value,
+                entry.expires_at,
+                Some(entry.created_at),
+                Some(entry.hit_count),
+            ),
+            (None, Some(row)) => (row.get(0), row.get(1), row.get(2), None),
+            (None, None) => return Err(CacheError::NotFound),
+        };
+
+        Ok(CacheEntryDetails {
+            key: key.to_string(),
+            value,
+            expires_at,
+            created_at,
+            in_memory: hit_count.is_some(),
+            hit_count,
+            persisted,
+            expired: expires_at < Utc::now(),
         })
     }
 
@@ -314,13 +637,76 @@ impl CacheService {
         Ok(cleaned)
     }
 
+    /// Every unexpired entry of the persistent layer, for backup
+    pub async fn export(&self) -> Result<Vec<CacheSnapshotEntry>, CacheError> {
+        let rows = sqlx::query(
+            "SELECT key, value, CEIL(EXTRACT(EPOCH FROM (expires_at - NOW())))::bigint
+             FROM cache_store
+             WHERE expires_at > NOW()
+             ORDER BY key",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| CacheError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CacheSnapshotEntry {
+                key: row.get(0),
+                value: row.get(1),
+                ttl_seconds: row.get(2),
+            })
+            .collect())
+    }
+
+    /// Restores exported entries into the persistent layer, replacing entries
+    /// with the same key. Each expires `ttl_seconds` after the import.
+    ///
+    /// Keys derived from the data version never match after the data changed,
+    /// so restoring an old snapshot can't serve stale results for them.
+    pub async fn import(&self, entries: &[CacheSnapshotEntry]) -> Result<usize, CacheError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| CacheError::DatabaseError(e.to_string()))?;
+
+        for entry in entries {
+            let expires_at = Utc::now() + ttl_with_jitter(Duration::seconds(entry.ttl_seconds));
+            sqlx::query(
+                "INSERT INTO cache_store (key, value, expires_at)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (key) DO UPDATE SET value = $2, expires_at = $3",
+            )
+            .bind(&entry.key)
+            .bind(&entry.value)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| CacheError::DatabaseError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| CacheError::DatabaseError(e.to_string()))?;
+
+        // Drop older copies from memory so the restored values are read next
+        if let Ok(mut cache) = self.in_memory_cache.write() {
+            for entry in entries {
+                cache.remove(&entry.key);
+            }
+        }
+
+        Ok(entries.len())
+    }
+
     /// Warm cache with commonly accessed data
     pub async fn warm_cache(&self) -> Result<(), CacheError> {
         let start_time = Instant::now();
 
         // Pre-cache commonly accessed analytics
         let _ = self
-            .get_or_compute("analytics_summary", Duration::minutes(30), || async {
+            .get_or_compute("analytics_summary", Duration::minutes(30), None, || async {
                 // Simulate analytics computation
                 Ok(serde_json::json!({
                     "total_students": 0,
@@ -332,7 +718,7 @@ impl CacheService {
 
         // Pre-cache user activity patterns
         let _ = self
-            .get_or_compute("activity_patterns", Duration::minutes(15), || async {
+            .get_or_compute("activity_patterns", Duration::minutes(15), None, || async {
                 Ok(serde_json::json!({
                     "patterns": [],
                     "cached": true
@@ -348,6 +734,23 @@ impl CacheService {
 
     // Private helper methods
 
+    fn compute_lock(&self, key: &str) -> ComputeLock<'_> {
+        let mut locks = self
+            .compute_locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let lock = locks
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone();
+
+        ComputeLock {
+            locks: &self.compute_locks,
+            key: key.to_string(),
+            lock,
+        }
+    }
+
     fn get_from_memory(&self, key: &str) -> Result<serde_json::Value, CacheError> {
         if let Ok(mut cache) = self.in_memory_cache.write() {
             if let Some(entry) = cache.get_mut(key) {
@@ -374,6 +777,7 @@ impl CacheService {
                     .map(|(key, _)| key.clone())
                 {
                     cache.remove(&oldest_key);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
                 }
             }
 
@@ -405,7 +809,7 @@ impl CacheService {
         value: &serde_json::Value,
         ttl: Duration,
     ) -> Result<(), CacheError> {
-        let expires_at = Utc::now() + ttl;
+        let expires_at = Utc::now() + ttl_with_jitter(ttl);
 
         sqlx::query(
             "INSERT INTO cache_store (key, value, expires_at) 
@@ -422,29 +826,45 @@ impl CacheService {
         Ok(())
     }
 
-    fn log_cache_hit(&self, cache_type: &str, key: &str, duration_ms: f64) {
-        LOGGER.log_performance_metric(
-            "cache_hit",
-            duration_ms,
-            [
-                ("cache_type".to_string(), cache_type.to_string()),
-                ("cache_key".to_string(), key.to_string()),
-            ]
+    fn log_cache_hit(
+        &self,
+        cache_type: &str,
+        key: &str,
+        duration_ms: f64,
+        context: Option<&CacheContext>,
+    ) {
+        let mut tags: HashMap<String, String> = [
+            ("cache_type".to_string(), cache_type.to_string()),
+            ("cache_key".to_string(), key.to_string()),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        Self::add_context_tags(&mut tags, context);
+
+        LOGGER.log_performance_metric("cache_hit", duration_ms, tags);
+    }
+
+    fn log_cache_miss(&self, key: &str, duration_ms: f64, context: Option<&CacheContext>) {
+        let mut tags: HashMap<String, String> = [("cache_key".to_string(), key.to_string())]
             .iter()
             .cloned()
-            .collect(),
-        );
+            .collect();
+        Self::add_context_tags(&mut tags, context);
+
+        LOGGER.log_performance_metric("cache_miss", duration_ms, tags);
     }
 
-    fn log_cache_miss(&self, key: &str, duration_ms: f64) {
-        LOGGER.log_performance_metric(
-            "cache_miss",
-            duration_ms,
-            [("cache_key".to_string(), key.to_string())]
-                .iter()
-                .cloned()
-                .collect(),
-        );
+    fn add_context_tags(tags: &mut HashMap<String, String>, context: Option<&CacheContext>) {
+        let Some(context) = context else {
+            return;
+        };
+        if let Some(user_id) = context.user_id {
+            tags.insert("user_id".to_string(), user_id.to_string());
+        }
+        if let Some(request_id) = &context.request_id {
+            tags.insert("request_id".to_string(), request_id.clone());
+        }
     }
 }
 
@@ -483,12 +903,12 @@ where
 
     pub async fn get(&self, id: &str) -> Result<T, CacheError> {
         let key = format!("{}:{}", self.key_prefix, id);
-        self.cache.get(&key).await
+        self.cache.get(&key, None).await
     }
 
     pub async fn set(&self, id: &str, value: &T) -> Result<(), CacheError> {
         let key = format!("{}:{}", self.key_prefix, id);
-        self.cache.set(&key, value, self.default_ttl).await
+        self.cache.set(&key, value, self.default_ttl, None).await
     }
 
     pub async fn get_or_compute<F, Fut>(&self, id: &str, compute_fn: F) -> Result<T, CacheError>
@@ -498,16 +918,56 @@ where
     {
         let key = format!("{}:{}", self.key_prefix, id);
         self.cache
-            .get_or_compute(&key, self.default_ttl, compute_fn)
+            .get_or_compute(&key, self.default_ttl, None, compute_fn)
             .await
     }
 
     pub async fn invalidate(&self, id: &str) -> Result<(), CacheError> {
         let key = format!("{}:{}", self.key_prefix, id);
-        self.cache.invalidate(&key).await
+        self.cache.invalidate(&key, None).await
     }
 
     pub async fn invalidate_all(&self) -> Result<usize, CacheError> {
-        self.cache.invalidate_pattern(&self.key_prefix).await
+        self.cache.invalidate_pattern(&self.key_prefix, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> CacheService {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        CacheService::new(pool, 10)
+    }
+
+    #[tokio::test]
+    async fn compute_lock_is_released_when_the_last_holder_drops() {
+        let cache = cache();
+        {
+            let key_lock = cache.compute_lock("k");
+            let _guard = key_lock.lock().await;
+            assert_eq!(cache.compute_locks.lock().unwrap().len(), 1);
+        }
+        assert!(cache.compute_locks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn compute_lock_is_released_when_a_waiter_is_cancelled() {
+        let cache = cache();
+        let holder = cache.compute_lock("k");
+        let guard = holder.lock().await;
+
+        let waiter = async {
+            let key_lock = cache.compute_lock("k");
+            let _guard = key_lock.lock().await;
+        };
+        let cancelled = tokio::time::timeout(std::time::Duration::from_millis(10), waiter).await;
+        assert!(cancelled.is_err());
+        assert_eq!(cache.compute_locks.lock().unwrap().len(), 1);
+
+        drop(guard);
+        drop(holder);
+        assert!(cache.compute_locks.lock().unwrap().is_empty());
     }
 }
@@ -0,0 +1,243 @@
+use crate::handlers::applications::with_sub_resources;
+use crate::models::application::{Application, ApplicationResponse, ApplicationStatus};
+use crate::services::activity::{ActivityData, ActivityError, ActivityService};
+use crate::services::analytics::{AnalyticsError, AnalyticsService};
+use crate::services::cache::{CacheContext, CacheError, CacheService};
+use crate::services::notification::NotificationService;
+use crate::utils::concurrency::ConcurrencyLimit;
+use crate::utils::database::{is_statement_timeout, with_retry};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+/// How long a user's dashboard is served from cache before being rebuilt
+const DASHBOARD_CACHE_TTL_SECONDS: i64 = 60;
+
+/// Days of activity included in a dashboard
+const DASHBOARD_ACTIVITY_DAYS: usize = 30;
+
+/// Latest applications included in a student's dashboard
+const DASHBOARD_RECENT_APPLICATIONS: i64 = 5;
+
+/// Everything the student dashboard shows on first load
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserDashboard {
+    /// Every status is present, with 0 when the user has none in it
+    pub status_counts: HashMap<String, i64>,
+    /// One entry per day, oldest first
+    pub recent_activity: Vec<ActivityData>,
+    pub stale_applications: usize,
+    /// Newest first
+    pub recent_applications: Vec<ApplicationResponse>,
+}
+
+/// The admin counterpart of `UserDashboard`, scoped like `/admin/analytics`
+#[derive(Debug, Serialize)]
+pub struct AdminDashboard {
+    pub total_students: i64,
+    pub total_applications: i64,
+    pub status_counts: HashMap<String, i64>,
+    /// One entry per day, oldest first
+    pub recent_activity: Vec<ActivityData>,
+    /// Oldest first, at most five
+    pub stale_applications: Vec<ApplicationResponse>,
+}
+
+#[derive(Debug)]
+pub enum DashboardError {
+    DatabaseError(String),
+    /// A query ran past `statement_timeout`
+    QueryTimeout,
+    /// Analytics were too busy to compute the admin figures
+    Overloaded,
+}
+
+impl From<sqlx::Error> for DashboardError {
+    fn from(e: sqlx::Error) -> Self {
+        if is_statement_timeout(&e) {
+            DashboardError::QueryTimeout
+        } else {
+            DashboardError::DatabaseError(e.to_string())
+        }
+    }
+}
+
+impl From<ActivityError> for DashboardError {
+    fn from(e: ActivityError) -> Self {
+        match e {
+            ActivityError::DatabaseError(msg) => DashboardError::DatabaseError(msg),
+            ActivityError::PermissionDenied => {
+                DashboardError::DatabaseError("Activity unavailable".to_string())
+            }
+        }
+    }
+}
+
+impl From<AnalyticsError> for DashboardError {
+    fn from(e: AnalyticsError) -> Self {
+        match e {
+            AnalyticsError::DatabaseError(msg) => DashboardError::DatabaseError(msg),
+            AnalyticsError::QueryTimeout => DashboardError::QueryTimeout,
+            AnalyticsError::Overloaded => DashboardError::Overloaded,
+        }
+    }
+}
+
+/// The last `DASHBOARD_ACTIVITY_DAYS` entries of an oldest-first daily series
+fn last_days<T>(mut days: Vec<T>) -> Vec<T> {
+    let skip = days.len().saturating_sub(DASHBOARD_ACTIVITY_DAYS);
+    days.drain(..skip);
+    days
+}
+
+pub struct DashboardService {
+    pool: PgPool,
+}
+
+impl DashboardService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// A user's dashboard, cached per user for `DASHBOARD_CACHE_TTL_SECONDS`.
+    ///
+    /// The TTL is short instead of keyed on the data version, so a change shows
+    /// up within a minute without paying for a version check on every load.
+    pub async fn get_cached_user_dashboard(
+        &self,
+        cache: &CacheService,
+        context: Option<&CacheContext>,
+        user_id: i32,
+    ) -> Result<UserDashboard, DashboardError> {
+        cache
+            .get_or_compute(
+                &format!("dashboard_u{}", user_id),
+                Duration::seconds(DASHBOARD_CACHE_TTL_SECONDS),
+                context,
+                || async {
+                    self.get_user_dashboard(user_id).await.map_err(|e| match e {
+                        DashboardError::DatabaseError(msg) => CacheError::DatabaseError(msg),
+                        DashboardError::QueryTimeout => CacheError::QueryTimeout,
+                        DashboardError::Overloaded => CacheError::Overloaded,
+                    })
+                },
+            )
+            .await
+            .map_err(|e| match e {
+                CacheError::DatabaseError(msg) | CacheError::SerializationError(msg) => {
+                    DashboardError::DatabaseError(msg)
+                }
+                CacheError::QueryTimeout => DashboardError::QueryTimeout,
+                CacheError::Overloaded => DashboardError::Overloaded,
+                CacheError::NotFound => {
+                    DashboardError::DatabaseError("Dashboard unavailable".to_string())
+                }
+            })
+    }
+
+    /// Builds a user's dashboard from the same queries as the individual endpoints
+    pub async fn get_user_dashboard(&self, user_id: i32) -> Result<UserDashboard, DashboardError> {
+        let activity = ActivityService::new(self.pool.clone());
+        let notifications = NotificationService::new(self.pool.clone());
+
+        let (status_counts, recent_activity, stale_applications, recent_applications) = tokio::try_join!(
+            self.get_status_counts(user_id),
+            async { Ok::<_, DashboardError>(activity.get_user_activity(user_id).await?) },
+            async {
+                notifications
+                    .find_user_stale_applications(user_id, None)
+                    .await
+                    .map_err(|e| DashboardError::DatabaseError(e.to_string()))
+            },
+            self.get_recent_applications(user_id),
+        )?;
+
+        Ok(UserDashboard {
+            status_counts,
+            recent_activity: last_days(recent_activity),
+            stale_applications: stale_applications.len(),
+            recent_applications,
+        })
+    }
+
+    /// Counts and stale applications come from the cached analytics, activity
+    /// from the admin activity series
+    pub async fn get_admin_dashboard(
+        &self,
+        cache: &CacheService,
+        analytics_limit: &ConcurrencyLimit,
+        context: Option<&CacheContext>,
+        cohort_id: Option<i32>,
+    ) -> Result<AdminDashboard, DashboardError> {
+        let analytics = AnalyticsService::new(self.pool.clone(), cohort_id);
+        let activity = ActivityService::new(self.pool.clone());
+
+        let (analytics, recent_activity) = tokio::try_join!(
+            async {
+                Ok::<_, DashboardError>(
+                    analytics
+                        .get_cached_analytics(cache, analytics_limit, context)
+                        .await?,
+                )
+            },
+            async { Ok::<_, DashboardError>(activity.get_admin_activity(cohort_id).await?) },
+        )?;
+
+        let mut status_counts = analytics.status_breakdown;
+        for status in ApplicationStatus::ALL {
+            status_counts
+                .entry(status.as_str().to_string())
+                .or_insert(0);
+        }
+
+        Ok(AdminDashboard {
+            total_students: analytics.total_students,
+            total_applications: analytics.total_applications,
+            status_counts,
+            recent_activity: last_days(recent_activity),
+            stale_applications: analytics.stale_applications,
+        })
+    }
+
+    async fn get_status_counts(
+        &self,
+        user_id: i32,
+    ) -> Result<HashMap<String, i64>, DashboardError> {
+        let rows = with_retry(|| {
+            sqlx::query(
+                "SELECT status::text, COUNT(*)::bigint FROM applications WHERE user_id = $1 GROUP BY status",
+            )
+            .bind(user_id)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        let mut counts: HashMap<String, i64> = ApplicationStatus::ALL
+            .iter()
+            .map(|status| (status.as_str().to_string(), 0))
+            .collect();
+        for row in rows {
+            counts.insert(row.get(0), row.get(1));
+        }
+
+        Ok(counts)
+    }
+
+    async fn get_recent_applications(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<ApplicationResponse>, DashboardError> {
+        let applications = with_retry(|| {
+            sqlx::query_as::<_, Application>(
+                "SELECT * FROM applications WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2",
+            )
+            .bind(user_id)
+            .bind(DASHBOARD_RECENT_APPLICATIONS)
+            .fetch_all(&self.pool)
+        })
+        .await?;
+
+        Ok(with_sub_resources(&self.pool, applications).await)
+    }
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::models::application::ApplicationStatus;
+
+/// Buffered events per channel before slow subscribers start lagging
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    ApplicationCreated,
+    ApplicationUpdated,
+    StatusChanged,
+    ScreeningUpdated,
+    InterviewUpdated,
+    DocumentUploaded,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppEvent {
+    pub event_type: EventType,
+    pub user_id: i32,
+    pub cohort_id: Option<i32>,
+    pub application_id: i32,
+    pub status: Option<ApplicationStatus>,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl AppEvent {
+    pub fn new(
+        event_type: EventType,
+        user_id: i32,
+        cohort_id: Option<i32>,
+        application_id: i32,
+    ) -> Self {
+        Self {
+            event_type,
+            user_id,
+            cohort_id,
+            application_id,
+            status: None,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    pub fn with_status(mut self, status: ApplicationStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Whether an admin with the given cohort scope may see this event
+    pub fn in_cohort_scope(&self, cohort_scope: Option<i32>) -> bool {
+        cohort_scope.is_none_or(|cohort_id| self.cohort_id == Some(cohort_id))
+    }
+
+    /// New applications and uploaded recordings or documents, as shown in the admin activity feed
+    pub fn is_activity(&self) -> bool {
+        matches!(
+            self.event_type,
+            EventType::ApplicationCreated
+                | EventType::ScreeningUpdated
+                | EventType::InterviewUpdated
+                | EventType::DocumentUploaded
+        )
+    }
+}
+
+/// In-process fan-out of application events.
+///
+/// Each user gets a lazily created channel for their own events, and every
+/// event is also sent on a global feed that admin subscribers filter by cohort.
+#[derive(Debug)]
+pub struct EventBus {
+    user_channels: Mutex<HashMap<i32, broadcast::Sender<AppEvent>>>,
+    global: broadcast::Sender<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (global, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            user_channels: Mutex::new(HashMap::new()),
+            global,
+        }
+    }
+
+    pub fn publish(&self, event: AppEvent) {
+        if let Ok(channels) = self.user_channels.lock() {
+            if let Some(sender) = channels.get(&event.user_id) {
+                // No receivers is not an error: nobody is listening right now
+                let _ = sender.send(event.clone());
+            }
+        }
+
+        let _ = self.global.send(event);
+    }
+
+    pub fn subscribe_user(&self, user_id: i32) -> broadcast::Receiver<AppEvent> {
+        let mut channels = self
+            .user_channels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        channels
+            .entry(user_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    pub fn subscribe_all(&self) -> broadcast::Receiver<AppEvent> {
+        self.global.subscribe()
+    }
+
+    /// Drop the user's channel once the last subscriber has disconnected
+    pub fn release_user(&self, user_id: i32) {
+        if let Ok(mut channels) = self.user_channels.lock() {
+            if channels
+                .get(&user_id)
+                .is_some_and(|sender| sender.receiver_count() == 0)
+            {
+                channels.remove(&user_id);
+            }
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
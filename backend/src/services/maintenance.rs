@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+
+/// Derived columns that `POST /admin/maintenance/backfill` can recompute
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillField {
+    /// `applications.company_normalized`, from `company`
+    CompanyNormalized,
+    /// `applications.last_activity_at`, from the application and its
+    /// screening, interview and documents
+    LastActivityAt,
+}
+
+impl BackfillField {
+    /// Recomputes the column for the rows in `batch` whose value differs, so
+    /// running it again over the same rows changes nothing
+    fn update_sql(&self) -> &'static str {
+        match self {
+            BackfillField::CompanyNormalized => {
+                r#"
+                UPDATE applications a
+                SET company_normalized = normalize_company(a.company)
+                FROM batch
+                WHERE a.id = batch.id
+                AND a.company_normalized IS DISTINCT FROM normalize_company(a.company)
+                RETURNING a.id
+                "#
+            }
+            // Same formula as the backfill in 020_last_activity.sql
+            BackfillField::LastActivityAt => {
+                r#"
+                UPDATE applications a
+                SET last_activity_at = computed.value
+                FROM (
+                    SELECT a.id, COALESCE(
+                        GREATEST(
+                            a.updated_at,
+                            (SELECT MAX(s.updated_at) FROM screenings s WHERE s.application_id = a.id),
+                            (SELECT MAX(i.updated_at) FROM interviews i WHERE i.application_id = a.id),
+                            (SELECT MAX(d.created_at) FROM documents d WHERE d.application_id = a.id)
+                        ),
+                        a.created_at,
+                        NOW()
+                    ) AS value
+                    FROM applications a
+                    JOIN batch ON batch.id = a.id
+                ) computed
+                WHERE a.id = computed.id
+                AND a.last_activity_at IS DISTINCT FROM computed.value
+                RETURNING a.id
+                "#
+            }
+        }
+    }
+}
+
+/// What one batch of a backfill went through
+#[derive(Debug, Clone, Copy)]
+pub struct BackfillBatch {
+    /// Applications looked at
+    pub scanned: i64,
+    /// Applications whose value changed
+    pub updated: i64,
+    /// Highest id looked at; `None` when there was nothing left
+    pub last_id: Option<i32>,
+}
+
+/// Recomputes `field` for up to `batch_size` applications with an id above
+/// `after_id`, in id order.
+///
+/// Each batch is its own transaction, so row locks are only held for one batch.
+/// Triggers see `otchet.backfill` and leave `updated_at` and the audit log alone.
+pub async fn backfill_batch(
+    pool: &PgPool,
+    field: BackfillField,
+    after_id: i32,
+    batch_size: i64,
+) -> Result<BackfillBatch, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT set_config('otchet.backfill', 'on', true)")
+        .execute(&mut *tx)
+        .await?;
+
+    let row = sqlx::query(&format!(
+        r#"
+        WITH batch AS (
+            SELECT id FROM applications WHERE id > $1 ORDER BY id LIMIT $2
+        ), updated AS ({})
+        SELECT (SELECT COUNT(*) FROM batch) AS scanned,
+               (SELECT COUNT(*) FROM updated) AS updated,
+               (SELECT MAX(id) FROM batch) AS last_id
+        "#,
+        field.update_sql()
+    ))
+    .bind(after_id)
+    .bind(batch_size)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(BackfillBatch {
+        scanned: row.get("scanned"),
+        updated: row.get("updated"),
+        last_id: row.get("last_id"),
+    })
+}
+
+/// Applications with an id above `after_id`, i.e. not yet reached by a backfill
+pub async fn remaining_after(pool: &PgPool, after_id: i32) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM applications WHERE id > $1")
+        .bind(after_id)
+        .fetch_one(pool)
+        .await
+}
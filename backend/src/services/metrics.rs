@@ -1,18 +1,87 @@
+use crate::services::cache::{data_version, CacheContext, CacheError, CacheService};
+use crate::utils::database::is_statement_timeout;
 use crate::utils::logger::LOGGER;
-use chrono::{DateTime, Duration, Utc};
-use serde::Serialize;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 use std::time::Instant;
 
+/// SQL expression classifying `company` into a coarse industry bucket
+pub(crate) const INDUSTRY_CLASSIFICATION_SQL: &str = "
+    CASE 
+        WHEN LOWER(company) LIKE '%tech%' OR LOWER(company) LIKE '%software%' 
+             OR LOWER(company) LIKE '%it%' THEN 'Technology'
+        WHEN LOWER(company) LIKE '%bank%' OR LOWER(company) LIKE '%financial%'
+             OR LOWER(company) LIKE '%insurance%' THEN 'Financial Services'  
+        WHEN LOWER(company) LIKE '%retail%' OR LOWER(company) LIKE '%ecommerce%'
+             OR LOWER(company) LIKE '%shop%' THEN 'Retail & E-commerce'
+        WHEN LOWER(company) LIKE '%health%' OR LOWER(company) LIKE '%medical%'
+             OR LOWER(company) LIKE '%pharma%' THEN 'Healthcare'
+        WHEN LOWER(company) LIKE '%consult%' OR LOWER(company) LIKE '%advisory%'
+             THEN 'Consulting'
+        WHEN LOWER(company) LIKE '%media%' OR LOWER(company) LIKE '%marketing%'
+             OR LOWER(company) LIKE '%advertising%' THEN 'Media & Marketing'
+        ELSE 'Other Industries'
+    END";
+
+/// How much of the underlying data a metrics report may reveal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyTier {
+    /// Small groups are suppressed and companies only appear as categories
+    #[default]
+    Aggregate,
+    /// Every group and the raw company names; super-admins only
+    Detailed,
+}
+
+impl PrivacyTier {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PrivacyTier::Aggregate => "aggregate",
+            PrivacyTier::Detailed => "detailed",
+        }
+    }
+
+    /// Smallest group reported, given the k-anonymity threshold `k` of a breakdown
+    fn min_group_size(&self, k: i64) -> i64 {
+        match self {
+            PrivacyTier::Aggregate => k,
+            PrivacyTier::Detailed => 1,
+        }
+    }
+}
+
+/// Companies listed in a detailed report
+const DETAILED_COMPANY_LIMIT: i64 = 50;
+
 #[derive(Debug, Serialize, serde::Deserialize)]
 pub struct TimeBasedMetrics {
     pub period: String,
+    /// False when no applications fall in the period; the rates are then 0
+    /// for lack of data, not a measured 0%
+    pub has_data: bool,
+    /// Applications in the period the statistics are computed from
+    pub sample_size: i64,
+    #[serde(default)]
+    pub privacy_tier: PrivacyTier,
     pub anonymous_statistics: AnonymousStatistics,
     pub trends: TrendAnalysis,
+    /// Only in the detailed tier; most applications first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub companies: Option<Vec<CompanyStat>>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub generated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize, serde::Deserialize)]
+pub struct CompanyStat {
+    pub company: String,
+    pub application_count: i64,
+    pub success_rate: f64,
+}
+
 #[derive(Debug, Serialize, serde::Deserialize)]
 pub struct AnonymousStatistics {
     pub total_job_postings_analyzed: i64,
@@ -47,36 +116,258 @@ pub struct TrendAnalysis {
     pub anomalies_detected: Vec<String>,
 }
 
+impl TrendAnalysis {
+    /// Trends from `(current, previous)` application counts per week and month.
+    /// A previous period without applications counts as no change.
+    fn from_counts(weekly: (i64, i64), monthly: (i64, i64)) -> Self {
+        let (current_week, previous_week) = weekly;
+        let weekly_change = percent_change(current_week, previous_week);
+        let monthly_change = percent_change(monthly.0, monthly.1);
+
+        // Simple prediction based on trend
+        let prediction = if weekly_change > 0.0 {
+            (current_week as f64 * (1.0 + weekly_change / 100.0)) as i64
+        } else {
+            current_week
+        };
+
+        // Detect anomalies
+        let mut anomalies = Vec::new();
+        if weekly_change.abs() > 50.0 {
+            anomalies.push(format!("Unusual weekly change: {:.1}%", weekly_change));
+        }
+        if monthly_change.abs() > 30.0 {
+            anomalies.push(format!("Unusual monthly change: {:.1}%", monthly_change));
+        }
+
+        Self {
+            weekly_change_percent: weekly_change,
+            monthly_change_percent: monthly_change,
+            prediction_next_week: prediction,
+            anomalies_detected: anomalies,
+        }
+    }
+}
+
+/// `part` as a percentage of `whole`, or 0 when `whole` is empty
+pub(crate) fn percent_of(part: i64, whole: i64) -> f64 {
+    if whole > 0 {
+        part as f64 * 100.0 / whole as f64
+    } else {
+        0.0
+    }
+}
+
+/// Change from `previous` to `current` in percent, or 0 when `previous` is empty
+fn percent_change(current: i64, previous: i64) -> f64 {
+    percent_of(current - previous, previous)
+}
+
+/// Bumped whenever `TimeBasedMetrics` gains a required field, so entries
+/// cached in the old shape are never read back
+const METRICS_CACHE_FORMAT: u32 = 2;
+
+/// TTL for time series entries; the key already changes with the data
+const TIMESERIES_CACHE_TTL_MINUTES: i64 = 60;
+
+/// Longest window any metrics query covers
+pub const MAX_METRICS_DAYS: i32 = 730;
+
+/// What a time series counts per bucket
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeseriesMetric {
+    /// Applications by application date
+    #[default]
+    Applications,
+    /// Screenings by when they were recorded
+    Screenings,
+    /// Interviews by when they were recorded
+    Interviews,
+    /// Percentage of the bucket's applications that reached an offer
+    SuccessRate,
+}
+
+impl TimeseriesMetric {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TimeseriesMetric::Applications => "applications",
+            TimeseriesMetric::Screenings => "screenings",
+            TimeseriesMetric::Interviews => "interviews",
+            TimeseriesMetric::SuccessRate => "success_rate",
+        }
+    }
+
+    /// Rows to bucket, each with its date as `d` and the application's status
+    fn source_sql(&self) -> &'static str {
+        match self {
+            TimeseriesMetric::Applications | TimeseriesMetric::SuccessRate => {
+                "SELECT a.applied_date AS d, a.status FROM applications a
+                 WHERE $3::int IS NULL OR a.cohort_id = $3"
+            }
+            TimeseriesMetric::Screenings => {
+                "SELECT s.created_at::date AS d, a.status FROM screenings s
+                 JOIN applications a ON a.id = s.application_id
+                 WHERE $3::int IS NULL OR a.cohort_id = $3"
+            }
+            TimeseriesMetric::Interviews => {
+                "SELECT i.created_at::date AS d, a.status FROM interviews i
+                 JOIN applications a ON a.id = i.application_id
+                 WHERE $3::int IS NULL OR a.cohort_id = $3"
+            }
+        }
+    }
+
+    /// Aggregate over a bucket's rows; NULL for a rate without any rows
+    fn value_sql(&self) -> &'static str {
+        match self {
+            TimeseriesMetric::SuccessRate => {
+                "CASE WHEN COUNT(r.d) = 0 THEN NULL
+                      ELSE COUNT(r.d) FILTER (WHERE r.status IN ('offer', 'accepted')) * 100.0 / COUNT(r.d)
+                 END::float8"
+            }
+            _ => "COUNT(r.d)::float8",
+        }
+    }
+}
+
+/// Width of a time series bucket
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    #[default]
+    Day,
+    /// Weeks start on Monday
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// Field name for `date_trunc` and the unit of the bucket interval
+    fn as_str(&self) -> &'static str {
+        match self {
+            Granularity::Day => "day",
+            Granularity::Week => "week",
+            Granularity::Month => "month",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeseriesBucket {
+    /// First day of the bucket
+    pub start: NaiveDate,
+    /// `None` for a rate over a bucket without applications
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricsTimeseries {
+    pub metric: TimeseriesMetric,
+    pub granularity: Granularity,
+    pub period: String,
+    /// Oldest first, one per bucket including empty ones
+    pub buckets: Vec<TimeseriesBucket>,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Fewest other students a cohort needs before its averages are shown to a student
+pub const BENCHMARK_MIN_COHORT_SIZE: usize = 5;
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkFigures {
+    /// Applications per student over the period
+    pub applications: f64,
+    /// Passed screenings out of screenings with a result; `None` without any
+    pub screening_pass_rate_percent: Option<f64>,
+    /// Applications that reached an offer
+    pub success_rate_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StudentBenchmark {
+    pub period: String,
+    pub you: BenchmarkFigures,
+    /// Averages over the other students of the cohort; `None` when there are
+    /// fewer than `min_cohort_size` of them
+    pub cohort: Option<BenchmarkFigures>,
+    pub min_cohort_size: usize,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Per-student totals behind a benchmark
+#[derive(Debug, Default, Clone, Copy)]
+struct StudentTotals {
+    applications: i64,
+    successful: i64,
+    screened: i64,
+    screenings_passed: i64,
+}
+
+impl StudentTotals {
+    fn add(self, other: StudentTotals) -> Self {
+        Self {
+            applications: self.applications + other.applications,
+            successful: self.successful + other.successful,
+            screened: self.screened + other.screened,
+            screenings_passed: self.screenings_passed + other.screenings_passed,
+        }
+    }
+
+    fn figures(&self, students: usize) -> BenchmarkFigures {
+        BenchmarkFigures {
+            applications: self.applications as f64 / students.max(1) as f64,
+            screening_pass_rate_percent: (self.screened > 0)
+                .then(|| self.screenings_passed as f64 * 100.0 / self.screened as f64),
+            success_rate_percent: percent_of(self.successful, self.applications),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MetricsService {
     pool: PgPool,
+    /// Restrict every query to this cohort; `None` covers all cohorts
+    cohort_id: Option<i32>,
 }
 
 #[derive(Debug)]
 pub enum MetricsError {
     DatabaseError(String),
     CalculationError(String),
+    /// A query ran past `statement_timeout`
+    QueryTimeout,
 }
 
 impl MetricsService {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, cohort_id: Option<i32>) -> Self {
+        Self { pool, cohort_id }
     }
 
-    /// Generate anonymized time-based metrics for the specified period
+    /// Generate time-based metrics for the specified period, revealing only
+    /// what `tier` allows
     pub async fn generate_anonymous_metrics(
         &self,
         days_back: i32,
+        tier: PrivacyTier,
     ) -> Result<TimeBasedMetrics, MetricsError> {
         let start_time = Instant::now();
 
         LOGGER.log_business_event(
             "anonymous_metrics_generation_started",
             None,
-            [(
-                "period_days".to_string(),
-                serde_json::Value::Number(serde_json::Number::from(days_back)),
-            )]
+            [
+                (
+                    "period_days".to_string(),
+                    serde_json::Value::Number(serde_json::Number::from(days_back)),
+                ),
+                (
+                    "privacy_tier".to_string(),
+                    serde_json::Value::String(tier.as_str().to_string()),
+                ),
+            ]
             .iter()
             .cloned()
             .collect(),
@@ -84,16 +375,34 @@ impl MetricsService {
 
         let period = format!("last_{}_days", days_back);
 
-        let (anonymous_stats, trends) = tokio::try_join!(
-            self.calculate_anonymous_statistics(days_back),
-            self.calculate_trend_analysis(days_back)
+        let ((anonymous_stats, sample_size), trends, companies) = tokio::try_join!(
+            self.calculate_anonymous_statistics(days_back, tier),
+            self.calculate_trend_analysis(days_back),
+            async {
+                match tier {
+                    PrivacyTier::Aggregate => Ok(None),
+                    PrivacyTier::Detailed => {
+                        self.calculate_company_stats(days_back).await.map(Some)
+                    }
+                }
+            }
         )
-        .map_err(|e| MetricsError::DatabaseError(e.to_string()))?;
+        .map_err(|e| {
+            if is_statement_timeout(&e) {
+                MetricsError::QueryTimeout
+            } else {
+                MetricsError::DatabaseError(e.to_string())
+            }
+        })?;
 
         let metrics = TimeBasedMetrics {
             period,
+            has_data: sample_size > 0,
+            sample_size,
+            privacy_tier: tier,
             anonymous_statistics: anonymous_stats,
             trends,
+            companies,
             generated_at: Utc::now(),
         };
 
@@ -107,10 +416,12 @@ impl MetricsService {
         Ok(metrics)
     }
 
+    /// The statistics, with how many applications they cover
     async fn calculate_anonymous_statistics(
         &self,
         days_back: i32,
-    ) -> Result<AnonymousStatistics, sqlx::Error> {
+        tier: PrivacyTier,
+    ) -> Result<(AnonymousStatistics, i64), sqlx::Error> {
         let cutoff_date = Utc::now().naive_utc().date() - Duration::days(days_back as i64);
 
         // Basic counts with privacy protection
@@ -119,14 +430,15 @@ impl MetricsService {
                 COUNT(DISTINCT a.job_url) as total_postings,
                 COUNT(DISTINCT a.company) as unique_companies,
                 COUNT(DISTINCT a.id) as total_applications,
-                COUNT(CASE WHEN i.result = 'passed' THEN 1 END) as successful_applications,
+                COUNT(CASE WHEN a.status IN ('offer', 'accepted') THEN 1 END) as successful_applications,
                 AVG(EXTRACT(EPOCH FROM (s.screening_date - a.applied_date))/86400.0) as avg_response_days
              FROM applications a
              LEFT JOIN screenings s ON a.id = s.application_id
-             LEFT JOIN interviews i ON a.id = i.application_id  
-             WHERE a.applied_date >= $1"
+             WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)"
         )
         .bind(cutoff_date)
+        .bind(self.cohort_id)
         .fetch_one(&self.pool)
         .await?;
 
@@ -136,11 +448,7 @@ impl MetricsService {
         let successful_applications: i64 = basic_stats.get(3);
         let avg_response_days: Option<f64> = basic_stats.get(4);
 
-        let success_rate = if total_applications > 0 {
-            (successful_applications as f64 / total_applications as f64) * 100.0
-        } else {
-            0.0
-        };
+        let success_rate = percent_of(successful_applications, total_applications);
 
         // Get anonymized job domains based on company patterns
         let job_domains = self.calculate_job_domains(days_back).await?;
@@ -149,21 +457,24 @@ impl MetricsService {
         let temporal_patterns = self.calculate_temporal_patterns(days_back).await?;
 
         // Anonymized geographical distribution (use first letter of city + size bucket)
-        let geo_distribution = self.calculate_anonymous_geography(days_back).await?;
+        let geo_distribution = self.calculate_anonymous_geography(days_back, tier).await?;
 
         // Industry breakdown based on company keywords (anonymized)
-        let industry_breakdown = self.calculate_industry_breakdown(days_back).await?;
-
-        Ok(AnonymousStatistics {
-            total_job_postings_analyzed: total_postings,
-            unique_companies,
-            application_success_rate_percent: success_rate,
-            average_response_time_days: avg_response_days.unwrap_or(0.0),
-            popular_job_domains: job_domains,
-            geographical_distribution: geo_distribution,
-            industry_breakdown,
-            temporal_patterns,
-        })
+        let industry_breakdown = self.calculate_industry_breakdown(days_back, tier).await?;
+
+        Ok((
+            AnonymousStatistics {
+                total_job_postings_analyzed: total_postings,
+                unique_companies,
+                application_success_rate_percent: success_rate,
+                average_response_time_days: avg_response_days.unwrap_or(0.0),
+                popular_job_domains: job_domains,
+                geographical_distribution: geo_distribution,
+                industry_breakdown,
+                temporal_patterns,
+            },
+            total_applications,
+        ))
     }
 
     async fn calculate_job_domains(&self, days_back: i32) -> Result<Vec<JobDomain>, sqlx::Error> {
@@ -190,15 +501,16 @@ impl MetricsService {
                     ELSE 'other'
                 END as domain,
                 COUNT(*) as application_count,
-                AVG(CASE WHEN i.result = 'passed' THEN 1.0 ELSE 0.0 END) * 100 as success_rate
+                AVG(CASE WHEN a.status IN ('offer', 'accepted') THEN 1.0 ELSE 0.0 END) * 100 as success_rate
              FROM applications a
-             LEFT JOIN interviews i ON a.id = i.application_id
              WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
              GROUP BY domain
              ORDER BY application_count DESC
              LIMIT 10",
         )
         .bind(cutoff_date)
+        .bind(self.cohort_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -225,14 +537,15 @@ impl MetricsService {
             "SELECT 
                 TO_CHAR(applied_date, 'Day') as day_name,
                 COUNT(*) as applications,
-                AVG(CASE WHEN i.result = 'passed' THEN 1.0 ELSE 0.0 END) as success_rate
+                AVG(CASE WHEN a.status IN ('offer', 'accepted') THEN 1.0 ELSE 0.0 END) as success_rate
              FROM applications a
-             LEFT JOIN interviews i ON a.id = i.application_id
              WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
              GROUP BY EXTRACT(DOW FROM applied_date), TO_CHAR(applied_date, 'Day')
              ORDER BY success_rate DESC, applications DESC",
         )
         .bind(cutoff_date)
+        .bind(self.cohort_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -249,10 +562,12 @@ impl MetricsService {
                 COUNT(*) as applications
              FROM applications a
              WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
              GROUP BY EXTRACT(MONTH FROM applied_date), TO_CHAR(applied_date, 'Month')
              ORDER BY EXTRACT(MONTH FROM applied_date)",
         )
         .bind(cutoff_date)
+        .bind(self.cohort_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -276,11 +591,13 @@ impl MetricsService {
                 COUNT(*) as applications
              FROM applications a  
              WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
              GROUP BY EXTRACT(HOUR FROM created_at)
              ORDER BY applications DESC
              LIMIT 3",
         )
         .bind(cutoff_date)
+        .bind(self.cohort_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -299,6 +616,7 @@ impl MetricsService {
     async fn calculate_anonymous_geography(
         &self,
         days_back: i32,
+        tier: PrivacyTier,
     ) -> Result<HashMap<String, i64>, sqlx::Error> {
         let cutoff_date = Utc::now().naive_utc().date() - Duration::days(days_back as i32 as i64);
 
@@ -315,10 +633,13 @@ impl MetricsService {
                 COUNT(*) as count
              FROM applications a
              WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
              GROUP BY geo_region
-             HAVING COUNT(*) >= 3" // Only show regions with sufficient data for anonymity
+             HAVING COUNT(*) >= $3" // Only show regions with sufficient data for anonymity
         )
         .bind(cutoff_date)
+        .bind(self.cohort_id)
+        .bind(tier.min_group_size(3))
         .fetch_all(&self.pool)
         .await?;
 
@@ -333,35 +654,28 @@ impl MetricsService {
     async fn calculate_industry_breakdown(
         &self,
         days_back: i32,
+        tier: PrivacyTier,
     ) -> Result<HashMap<String, i64>, sqlx::Error> {
         let cutoff_date = Utc::now().naive_utc().date() - Duration::days(days_back as i32 as i64);
 
-        let industry_data = sqlx::query(
+        let query = format!(
             "SELECT 
-                CASE 
-                    WHEN LOWER(company) LIKE '%tech%' OR LOWER(company) LIKE '%software%' 
-                         OR LOWER(company) LIKE '%it%' THEN 'Technology'
-                    WHEN LOWER(company) LIKE '%bank%' OR LOWER(company) LIKE '%financial%'
-                         OR LOWER(company) LIKE '%insurance%' THEN 'Financial Services'  
-                    WHEN LOWER(company) LIKE '%retail%' OR LOWER(company) LIKE '%ecommerce%'
-                         OR LOWER(company) LIKE '%shop%' THEN 'Retail & E-commerce'
-                    WHEN LOWER(company) LIKE '%health%' OR LOWER(company) LIKE '%medical%'
-                         OR LOWER(company) LIKE '%pharma%' THEN 'Healthcare'
-                    WHEN LOWER(company) LIKE '%consult%' OR LOWER(company) LIKE '%advisory%'
-                         THEN 'Consulting'
-                    WHEN LOWER(company) LIKE '%media%' OR LOWER(company) LIKE '%marketing%'
-                         OR LOWER(company) LIKE '%advertising%' THEN 'Media & Marketing'
-                    ELSE 'Other Industries'
-                END as industry,
+                {} as industry,
                 COUNT(*) as count
              FROM applications a
-             WHERE a.applied_date >= $1  
+             WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)  
              GROUP BY industry
-             HAVING COUNT(*) >= 2", // Minimum for anonymization
-        )
-        .bind(cutoff_date)
-        .fetch_all(&self.pool)
-        .await?;
+             HAVING COUNT(*) >= $3", // Minimum for anonymization
+            INDUSTRY_CLASSIFICATION_SQL
+        );
+
+        let industry_data = sqlx::query(&query)
+            .bind(cutoff_date)
+            .bind(self.cohort_id)
+            .bind(tier.min_group_size(2))
+            .fetch_all(&self.pool)
+            .await?;
 
         let mut industries = HashMap::new();
         for row in industry_data {
@@ -371,6 +685,41 @@ impl MetricsService {
         Ok(industries)
     }
 
+    /// Raw company names with their counts, for the detailed tier only
+    async fn calculate_company_stats(
+        &self,
+        days_back: i32,
+    ) -> Result<Vec<CompanyStat>, sqlx::Error> {
+        let cutoff_date = Utc::now().naive_utc().date() - Duration::days(days_back as i64);
+
+        let rows = sqlx::query(
+            "SELECT
+                company,
+                COUNT(*) as application_count,
+                AVG(CASE WHEN a.status IN ('offer', 'accepted') THEN 1.0 ELSE 0.0 END) * 100 as success_rate
+             FROM applications a
+             WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
+             GROUP BY company
+             ORDER BY application_count DESC, company
+             LIMIT $3",
+        )
+        .bind(cutoff_date)
+        .bind(self.cohort_id)
+        .bind(DETAILED_COMPANY_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CompanyStat {
+                company: row.get(0),
+                application_count: row.get(1),
+                success_rate: row.get::<Option<f64>, _>(2).unwrap_or(0.0),
+            })
+            .collect())
+    }
+
     async fn calculate_trend_analysis(&self, days_back: i32) -> Result<TrendAnalysis, sqlx::Error> {
         let current_period = Utc::now().naive_utc().date() - Duration::days(days_back as i64);
         let prev_week = current_period - Duration::weeks(1);
@@ -381,137 +730,355 @@ impl MetricsService {
             "SELECT 
                 COUNT(CASE WHEN applied_date >= $1 THEN 1 END) as current_week,
                 COUNT(CASE WHEN applied_date >= $2 AND applied_date < $1 THEN 1 END) as prev_week
-             FROM applications WHERE applied_date >= $2",
+             FROM applications
+             WHERE applied_date >= $2 AND ($3::int IS NULL OR cohort_id = $3)",
         )
         .bind(current_period)
         .bind(prev_week)
+        .bind(self.cohort_id)
         .fetch_one(&self.pool)
         .await?;
 
         let current_week: i64 = weekly_data.get(0);
         let previous_week: i64 = weekly_data.get(1);
 
-        let weekly_change = if previous_week > 0 {
-            ((current_week - previous_week) as f64 / previous_week as f64) * 100.0
-        } else {
-            0.0
-        };
-
         // Monthly comparison
         let monthly_data = sqlx::query(
             "SELECT 
                 COUNT(CASE WHEN applied_date >= $1 THEN 1 END) as current_month,
                 COUNT(CASE WHEN applied_date >= $2 AND applied_date < $1 THEN 1 END) as prev_month
-             FROM applications WHERE applied_date >= $2",
+             FROM applications
+             WHERE applied_date >= $2 AND ($3::int IS NULL OR cohort_id = $3)",
         )
         .bind(current_period)
         .bind(prev_month)
+        .bind(self.cohort_id)
         .fetch_one(&self.pool)
         .await?;
 
         let current_month: i64 = monthly_data.get(0);
         let previous_month: i64 = monthly_data.get(1);
 
-        let monthly_change = if previous_month > 0 {
-            ((current_month - previous_month) as f64 / previous_month as f64) * 100.0
-        } else {
-            0.0
-        };
+        Ok(TrendAnalysis::from_counts(
+            (current_week, previous_week),
+            (current_month, previous_month),
+        ))
+    }
 
-        // Simple prediction based on trend
-        let prediction = if weekly_change > 0.0 {
-            (current_week as f64 * (1.0 + weekly_change / 100.0)) as i64
-        } else {
-            current_week
-        };
+    /// A student's own figures next to the anonymized averages of the rest of the cohort
+    pub async fn student_benchmark(
+        &self,
+        user_id: i32,
+        days_back: i32,
+    ) -> Result<StudentBenchmark, MetricsError> {
+        let cutoff_date = Utc::now().naive_utc().date() - Duration::days(days_back as i64);
 
-        // Detect anomalies
-        let mut anomalies = Vec::new();
-        if weekly_change.abs() > 50.0 {
-            anomalies.push(format!("Unusual weekly change: {:.1}%", weekly_change));
-        }
-        if monthly_change.abs() > 30.0 {
-            anomalies.push(format!("Unusual monthly change: {:.1}%", monthly_change));
+        let rows = sqlx::query(
+            "SELECT
+                a.user_id,
+                COUNT(DISTINCT a.id)::bigint,
+                COUNT(DISTINCT a.id) FILTER (WHERE a.status IN ('offer', 'accepted'))::bigint,
+                COUNT(s.id) FILTER (WHERE s.result IS NOT NULL)::bigint,
+                COUNT(s.id) FILTER (WHERE s.result = 'passed')::bigint
+             FROM applications a
+             JOIN users u ON u.id = a.user_id AND u.role = 'student'
+             LEFT JOIN screenings s ON s.application_id = a.id
+             WHERE a.applied_date >= $1
+               AND ($2::int IS NULL OR a.cohort_id = $2)
+             GROUP BY a.user_id",
+        )
+        .bind(cutoff_date)
+        .bind(self.cohort_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            if is_statement_timeout(&e) {
+                MetricsError::QueryTimeout
+            } else {
+                MetricsError::DatabaseError(e.to_string())
+            }
+        })?;
+
+        let mut own = StudentTotals::default();
+        let mut others = StudentTotals::default();
+        let mut other_students = 0;
+        for row in rows {
+            let totals = StudentTotals {
+                applications: row.get(1),
+                successful: row.get(2),
+                screened: row.get(3),
+                screenings_passed: row.get(4),
+            };
+            if row.get::<i32, _>(0) == user_id {
+                own = totals;
+            } else {
+                others = others.add(totals);
+                other_students += 1;
+            }
         }
 
-        Ok(TrendAnalysis {
-            weekly_change_percent: weekly_change,
-            monthly_change_percent: monthly_change,
-            prediction_next_week: prediction,
-            anomalies_detected: anomalies,
+        Ok(StudentBenchmark {
+            period: format!("last_{}_days", days_back),
+            you: own.figures(1),
+            cohort: (other_students >= BENCHMARK_MIN_COHORT_SIZE)
+                .then(|| others.figures(other_students)),
+            min_cohort_size: BENCHMARK_MIN_COHORT_SIZE,
+            generated_at: Utc::now(),
         })
     }
 
+    /// `metric` per bucket over the last `days_back` days.
+    ///
+    /// Buckets come from `generate_series`, so periods without any rows are
+    /// still listed. The first bucket starts at the beginning of the period
+    /// containing the cutoff, so it is never partial.
+    pub async fn generate_timeseries(
+        &self,
+        metric: TimeseriesMetric,
+        granularity: Granularity,
+        days_back: i32,
+    ) -> Result<MetricsTimeseries, MetricsError> {
+        let start_time = Instant::now();
+
+        let query = format!(
+            r#"
+            WITH bounds AS (
+                SELECT date_trunc($1, (CURRENT_DATE - $2::int)::timestamp)::date AS first_bucket
+            ), buckets AS (
+                SELECT generate_series(
+                    (SELECT first_bucket FROM bounds)::timestamp,
+                    date_trunc($1, CURRENT_DATE::timestamp),
+                    ('1 ' || $1)::interval
+                )::date AS start
+            ), source AS ({})
+            SELECT b.start, {} AS value
+            FROM buckets b
+            LEFT JOIN source r
+                ON r.d >= (SELECT first_bucket FROM bounds)
+                AND date_trunc($1, r.d::timestamp)::date = b.start
+            GROUP BY b.start
+            ORDER BY b.start
+            "#,
+            metric.source_sql(),
+            metric.value_sql()
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(granularity.as_str())
+            .bind(days_back)
+            .bind(self.cohort_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                if is_statement_timeout(&e) {
+                    MetricsError::QueryTimeout
+                } else {
+                    MetricsError::DatabaseError(e.to_string())
+                }
+            })?;
+
+        LOGGER.log_performance_metric(
+            "metrics_timeseries_generation",
+            start_time.elapsed().as_millis() as f64,
+            HashMap::new(),
+        );
+
+        Ok(MetricsTimeseries {
+            metric,
+            granularity,
+            period: format!("last_{}_days", days_back),
+            buckets: rows
+                .iter()
+                .map(|row| TimeseriesBucket {
+                    start: row.get(0),
+                    value: row.get(1),
+                })
+                .collect(),
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Cached `generate_timeseries`, one entry per parameter combination and
+    /// data version
+    pub async fn get_cached_timeseries(
+        &self,
+        cache: &CacheService,
+        context: Option<&CacheContext>,
+        metric: TimeseriesMetric,
+        granularity: Granularity,
+        days_back: i32,
+    ) -> Result<MetricsTimeseries, MetricsError> {
+        let version = data_version(&self.pool)
+            .await
+            .map_err(|e| MetricsError::DatabaseError(e.to_string()))?;
+        // Buckets are relative to today, so the date is part of the key too
+        let cache_key = format!(
+            "metrics_timeseries_{}_{}_{}_{}d_{}_v{}",
+            self.cohort_id
+                .map(|id| format!("c{}", id))
+                .unwrap_or_else(|| "all".to_string()),
+            metric.as_str(),
+            granularity.as_str(),
+            days_back,
+            Utc::now().date_naive().format("%Y%m%d"),
+            version
+        );
+
+        cache
+            .get_or_compute(
+                &cache_key,
+                Duration::minutes(TIMESERIES_CACHE_TTL_MINUTES),
+                context,
+                || async {
+                    self.generate_timeseries(metric, granularity, days_back)
+                        .await
+                        .map_err(|e| match e {
+                            MetricsError::DatabaseError(msg) => CacheError::DatabaseError(msg),
+                            MetricsError::CalculationError(msg) => {
+                                CacheError::SerializationError(msg)
+                            }
+                            MetricsError::QueryTimeout => CacheError::QueryTimeout,
+                        })
+                },
+            )
+            .await
+            .map_err(|e| match e {
+                CacheError::DatabaseError(msg) => MetricsError::DatabaseError(msg),
+                CacheError::SerializationError(msg) => MetricsError::CalculationError(msg),
+                CacheError::QueryTimeout => MetricsError::QueryTimeout,
+                CacheError::NotFound | CacheError::Overloaded => {
+                    MetricsError::CalculationError("Metrics unavailable".to_string())
+                }
+            })
+    }
+
     /// Get cached metrics or generate new ones
     pub async fn get_cached_metrics(
         &self,
+        cache: &CacheService,
+        context: Option<&CacheContext>,
         days_back: i32,
+        tier: PrivacyTier,
         cache_duration_minutes: i32,
     ) -> Result<TimeBasedMetrics, MetricsError> {
-        let cache_key = format!("metrics_{}d", days_back);
-
-        // Try to get from cache first
-        if let Ok(cached) = self.get_from_cache(&cache_key).await {
-            LOGGER.log_business_event(
-                "metrics_cache_hit",
-                None,
-                [(
-                    "cache_key".to_string(),
-                    serde_json::Value::String(cache_key.clone()),
-                )]
-                .iter()
-                .cloned()
-                .collect(),
-            );
-            return Ok(cached);
-        }
+        // Key on the data version so any write invalidates the cached metrics
+        let version = data_version(&self.pool)
+            .await
+            .map_err(|e| MetricsError::DatabaseError(e.to_string()))?;
+        let cache_key = format!(
+            "metrics_f{}_{}_{}d_{}_v{}",
+            METRICS_CACHE_FORMAT,
+            self.cohort_id
+                .map(|id| format!("c{}", id))
+                .unwrap_or_else(|| "all".to_string()),
+            days_back,
+            tier.as_str(),
+            version
+        );
 
-        // Generate new metrics
-        let metrics = self.generate_anonymous_metrics(days_back).await?;
+        cache
+            .get_or_compute(
+                &cache_key,
+                Duration::minutes(cache_duration_minutes as i64),
+                context,
+                || async {
+                    self.generate_anonymous_metrics(days_back, tier)
+                        .await
+                        .map_err(|e| match e {
+                            MetricsError::DatabaseError(msg) => CacheError::DatabaseError(msg),
+                            MetricsError::CalculationError(msg) => {
+                                CacheError::SerializationError(msg)
+                            }
+                            MetricsError::QueryTimeout => CacheError::QueryTimeout,
+                        })
+                },
+            )
+            .await
+            .map_err(|e| match e {
+                CacheError::DatabaseError(msg) => MetricsError::DatabaseError(msg),
+                CacheError::SerializationError(msg) => MetricsError::CalculationError(msg),
+                CacheError::QueryTimeout => MetricsError::QueryTimeout,
+                CacheError::NotFound | CacheError::Overloaded => {
+                    MetricsError::CalculationError("Metrics unavailable".to_string())
+                }
+            })
+    }
+}
 
-        // Cache the result
-        self.cache_metrics(&cache_key, &metrics, cache_duration_minutes)
-            .await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_metrics() -> TimeBasedMetrics {
+        TimeBasedMetrics {
+            period: "last_30_days".to_string(),
+            has_data: false,
+            sample_size: 0,
+            privacy_tier: PrivacyTier::default(),
+            anonymous_statistics: AnonymousStatistics {
+                total_job_postings_analyzed: 0,
+                unique_companies: 0,
+                application_success_rate_percent: percent_of(0, 0),
+                average_response_time_days: 0.0,
+                popular_job_domains: vec![],
+                geographical_distribution: HashMap::new(),
+                industry_breakdown: HashMap::new(),
+                temporal_patterns: TemporalPatterns {
+                    best_application_days: vec![],
+                    seasonal_trends: HashMap::new(),
+                    peak_hours: vec![],
+                },
+            },
+            trends: TrendAnalysis::from_counts((0, 0), (0, 0)),
+            companies: None,
+            generated_at: Utc::now(),
+        }
+    }
 
-        Ok(metrics)
+    #[test]
+    fn percentages_of_nothing_are_zero() {
+        assert_eq!(percent_of(0, 0), 0.0);
+        assert_eq!(percent_of(3, 0), 0.0);
+        assert_eq!(percent_change(0, 0), 0.0);
+        assert_eq!(percent_change(5, 0), 0.0);
+        assert_eq!(percent_of(1, 4), 25.0);
+        assert_eq!(percent_change(6, 4), 50.0);
     }
 
-    async fn get_from_cache(&self, key: &str) -> Result<TimeBasedMetrics, MetricsError> {
-        let row =
-            sqlx::query("SELECT value FROM cache_store WHERE key = $1 AND expires_at > NOW()")
-                .bind(key)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|_| MetricsError::DatabaseError("Cache miss".to_string()))?;
-
-        let json_value: serde_json::Value = row.get(0);
-        serde_json::from_value(json_value).map_err(|e| {
-            MetricsError::CalculationError(format!("Cache deserialization error: {}", e))
-        })
+    #[test]
+    fn trends_without_applications_are_flat() {
+        let trends = TrendAnalysis::from_counts((0, 0), (0, 0));
+        assert_eq!(trends.weekly_change_percent, 0.0);
+        assert_eq!(trends.monthly_change_percent, 0.0);
+        assert_eq!(trends.prediction_next_week, 0);
+        assert!(trends.anomalies_detected.is_empty());
     }
 
-    async fn cache_metrics(
-        &self,
-        key: &str,
-        metrics: &TimeBasedMetrics,
-        duration_minutes: i32,
-    ) -> Result<(), MetricsError> {
-        let expires_at = Utc::now() + Duration::minutes(duration_minutes as i64);
-        let json_value = serde_json::to_value(metrics)
-            .map_err(|e| MetricsError::CalculationError(format!("Serialization error: {}", e)))?;
-
-        sqlx::query(
-            "INSERT INTO cache_store (key, value, expires_at) 
-             VALUES ($1, $2, $3) 
-             ON CONFLICT (key) DO UPDATE SET value = $2, expires_at = $3",
-        )
-        .bind(key)
-        .bind(json_value)
-        .bind(expires_at)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| MetricsError::DatabaseError(e.to_string()))?;
+    #[test]
+    fn benchmark_without_students_is_zero() {
+        let figures = StudentTotals::default().figures(0);
+        assert_eq!(figures.applications, 0.0);
+        assert_eq!(figures.screening_pass_rate_percent, None);
+        assert_eq!(figures.success_rate_percent, 0.0);
+    }
+
+    #[test]
+    fn empty_period_round_trips_as_no_data() {
+        let json = serde_json::to_value(empty_metrics()).unwrap();
+        assert_eq!(json["has_data"], false);
+        assert_eq!(json["sample_size"], 0);
 
-        Ok(())
+        let metrics: TimeBasedMetrics = serde_json::from_value(json).unwrap();
+        assert!(!metrics.has_data);
+        assert_eq!(metrics.sample_size, 0);
+    }
+
+    #[test]
+    fn cached_metrics_without_coverage_are_rejected() {
+        for field in ["has_data", "sample_size"] {
+            let mut json = serde_json::to_value(empty_metrics()).unwrap();
+            json.as_object_mut().unwrap().remove(field);
+            assert!(serde_json::from_value::<TimeBasedMetrics>(json).is_err());
+        }
     }
 }
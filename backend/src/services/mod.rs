@@ -1,5 +1,13 @@
 pub mod activity;
 pub mod analytics;
 pub mod cache;
+pub mod dashboard;
+pub mod events;
+pub mod maintenance;
 pub mod metrics;
 pub mod notification;
+pub mod scheduler;
+pub mod settings;
+pub mod storage;
+pub mod transcode;
+pub mod webhooks;
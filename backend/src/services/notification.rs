@@ -1,8 +1,65 @@
 use anyhow::Result;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::str::FromStr;
 
-use crate::models::{application::Application, user::User};
+use crate::models::{
+    application::Application,
+    notification::{DeliveryStatus, NotificationChannel},
+    user::User,
+};
+
+/// Delivery attempts before an outbox row is marked `failed`
+const OUTBOX_MAX_ATTEMPTS: i32 = 5;
+
+/// Outbox rows delivered per worker run
+pub const OUTBOX_BATCH_SIZE: i64 = 100;
+
+/// How long a worker run owns the outbox rows it claimed before another run
+/// may pick them up again
+const OUTBOX_CLAIM_LEASE_MINUTES: i32 = 10;
+
+/// When stale reminders are queued, as a cron expression in UTC
+pub const STALE_REMINDER_SCHEDULE: &str = "0 0 9 * * *";
+
+/// The next time `STALE_REMINDER_SCHEDULE` fires
+pub fn next_reminder_run() -> Option<DateTime<Utc>> {
+    cron::Schedule::from_str(STALE_REMINDER_SCHEDULE)
+        .ok()?
+        .upcoming(Utc)
+        .next()
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OutboxEntry {
+    id: i64,
+    recipient: String,
+    subject: String,
+    body: String,
+    attempts: i32,
+}
+
+/// A stale application with what made it stale
+#[derive(Debug, sqlx::FromRow)]
+pub struct StaleApplication {
+    #[sqlx(flatten)]
+    pub application: Application,
+    pub owner_email: String,
+    pub owner_first_name: String,
+    pub owner_last_name: String,
+    /// Whole days since `last_activity_at`
+    pub days_since_activity: i32,
+    /// Threshold the application was measured against
+    pub threshold_days: i32,
+}
+
+#[derive(Debug, Default)]
+pub struct OutboxDrainSummary {
+    pub sent: usize,
+    pub retrying: usize,
+    pub failed: usize,
+}
 
 pub struct NotificationService {
     pub db: PgPool,
@@ -13,37 +70,73 @@ impl NotificationService {
         Self { db }
     }
 
-    pub async fn find_stale_applications(&self, days: i32) -> Result<Vec<Application>> {
-        let cutoff_date = Utc::now() - Duration::days(days as i64);
-
+    /// Stale applications across all cohorts, or only `cohort_id` when given.
+    ///
+    /// Each status goes stale after its own number of days from `stale_thresholds`;
+    /// `days` overrides every threshold when given.
+    pub async fn find_stale_applications(
+        &self,
+        days: Option<i32>,
+        cohort_id: Option<i32>,
+    ) -> Result<Vec<Application>> {
         let results = sqlx::query_as::<_, Application>(
             r#"
-            SELECT * FROM applications
-            WHERE updated_at < $1 
-            AND status IN ('waiting', 'next_stage')
-            ORDER BY updated_at ASC
+            SELECT a.* FROM applications a
+            JOIN stale_thresholds t ON t.status = a.status
+            WHERE a.last_activity_at < NOW() - make_interval(days => COALESCE($1::int, t.days))
+            AND ($2::int IS NULL OR a.cohort_id = $2)
+            ORDER BY a.last_activity_at ASC
             "#,
         )
-        .bind(cutoff_date)
+        .bind(days)
+        .bind(cohort_id)
         .fetch_all(&self.db)
         .await?;
 
         Ok(results)
     }
 
-    pub async fn send_notification(
+    /// Same selection as `find_stale_applications`, with the owner and the
+    /// numbers behind each match, ordered by owner then oldest activity first
+    pub async fn find_stale_applications_with_reasons(
         &self,
-        user_email: &str,
-        applications: &[Application],
-    ) -> Result<()> {
+        days: Option<i32>,
+        cohort_id: Option<i32>,
+    ) -> Result<Vec<StaleApplication>> {
+        let results = sqlx::query_as::<_, StaleApplication>(
+            r#"
+            SELECT a.*,
+                   u.email AS owner_email,
+                   u.first_name AS owner_first_name,
+                   u.last_name AS owner_last_name,
+                   EXTRACT(DAY FROM NOW() - a.last_activity_at)::int AS days_since_activity,
+                   COALESCE($1::int, t.days) AS threshold_days
+            FROM applications a
+            JOIN stale_thresholds t ON t.status = a.status
+            JOIN users u ON u.id = a.user_id
+            WHERE a.last_activity_at < NOW() - make_interval(days => COALESCE($1::int, t.days))
+            AND ($2::int IS NULL OR a.cohort_id = $2)
+            ORDER BY a.user_id, a.last_activity_at ASC
+            "#,
+        )
+        .bind(days)
+        .bind(cohort_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(results)
+    }
+
+    /// The channel `deliver` sends through
+    pub fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Log
+    }
+
+    /// Hands a message to the delivery channel
+    pub async fn deliver(&self, recipient: &str, subject: &str, body: &str) -> Result<()> {
         // In a real implementation, this would send emails or push notifications
         // For now, we'll just log the notification
-        tracing::info!(
-            "Notification: User {} has {} stale applications: {:?}",
-            user_email,
-            applications.len(),
-            applications.iter().map(|a| &a.company).collect::<Vec<_>>()
-        );
+        tracing::info!("Notification to {}: {} - {}", recipient, subject, body);
 
         // TODO: Implement actual email/notification sending
         // This could use services like SendGrid, AWS SES, or a notification service
@@ -51,57 +144,213 @@ impl NotificationService {
         Ok(())
     }
 
-    pub async fn process_stale_notifications(&self) -> Result<()> {
-        self.process_stale_notifications_with_days(7).await
+    /// Subject and body of the reminder for a user's stale applications
+    pub fn compose_stale_notification(applications: &[Application]) -> (String, String) {
+        let body = format!(
+            "You have {} stale applications: {:?}",
+            applications.len(),
+            applications.iter().map(|a| &a.company).collect::<Vec<_>>()
+        );
+
+        ("Stale applications".to_string(), body)
+    }
+
+    /// Sends a sample message through the configured channel to check delivery works.
+    /// Nothing about real applications is sent or recorded.
+    pub async fn send_test_notification(&self, recipient: &str) -> Result<()> {
+        self.deliver(
+            recipient,
+            "Test notification",
+            "This is a test notification from the job tracker. No action is needed.",
+        )
+        .await
     }
 
-    pub async fn process_stale_notifications_with_days(&self, days: i32) -> Result<()> {
-        let stale_applications = self.find_stale_applications(days).await?;
+    pub async fn process_stale_notifications(&self) -> Result<usize> {
+        self.queue_stale_notifications(None, None).await
+    }
+
+    /// Stale applications grouped by the user who owns them, ordered by user id
+    pub async fn stale_applications_by_user(
+        &self,
+        days: Option<i32>,
+        cohort_id: Option<i32>,
+    ) -> Result<Vec<(User, Vec<Application>)>> {
+        let stale_applications = self.find_stale_applications(days, cohort_id).await?;
 
         // Group applications by user_id
-        let mut user_applications: std::collections::HashMap<i32, Vec<Application>> =
-            std::collections::HashMap::new();
+        let mut user_applications: BTreeMap<i32, Vec<Application>> = BTreeMap::new();
 
         for application in stale_applications {
             user_applications
                 .entry(application.user_id)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(application);
         }
 
-        // Send notifications to each user
+        let mut grouped = Vec::with_capacity(user_applications.len());
         for (user_id, applications) in user_applications {
             let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
                 .bind(user_id)
                 .fetch_one(&self.db)
                 .await?;
+            grouped.push((user, applications));
+        }
 
-            if let Err(e) = self.send_notification(&user.email, &applications).await {
-                tracing::error!("Failed to send notification to {}: {}", user.email, e);
-            }
+        Ok(grouped)
+    }
+
+    /// Writes one outbox row per user with stale applications, all in one
+    /// transaction. Returns the number of notifications queued.
+    pub async fn queue_stale_notifications(
+        &self,
+        days: Option<i32>,
+        cohort_id: Option<i32>,
+    ) -> Result<usize> {
+        let grouped = self.stale_applications_by_user(days, cohort_id).await?;
+        self.enqueue_notifications(&grouped).await?;
+        Ok(grouped.len())
+    }
+
+    /// Queues the reminders for already grouped stale applications in one transaction
+    pub async fn enqueue_notifications(&self, grouped: &[(User, Vec<Application>)]) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        for (user, applications) in grouped {
+            let (subject, body) = Self::compose_stale_notification(applications);
+            sqlx::query(
+                "INSERT INTO notification_outbox (user_id, recipient, subject, body, channel) VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(user.id)
+            .bind(&user.email)
+            .bind(subject)
+            .bind(body)
+            .bind(self.channel())
+            .execute(&mut *tx)
+            .await?;
         }
+        tx.commit().await?;
 
         Ok(())
     }
 
+    /// Delivers due outbox rows, marking each sent or scheduling a retry.
+    ///
+    /// Rows are claimed in one short statement that pushes their
+    /// `next_attempt_at` past a lease, so concurrent workers skip them and no
+    /// connection is held while mail goes out. Each outcome is written on its
+    /// own. A crash mid-batch leaves the unrecorded rows to be retried once the
+    /// lease runs out, which means a notification can be delivered twice but
+    /// never silently dropped.
+    pub async fn drain_outbox(&self, limit: i64) -> Result<OutboxDrainSummary> {
+        let due = sqlx::query_as::<_, OutboxEntry>(
+            r#"
+            UPDATE notification_outbox o
+            SET next_attempt_at = NOW() + make_interval(mins => $3)
+            FROM (
+                SELECT id FROM notification_outbox
+                WHERE status = $1 AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            ) claimed
+            WHERE o.id = claimed.id
+            RETURNING o.id, o.recipient, o.subject, o.body, o.attempts
+            "#,
+        )
+        .bind(DeliveryStatus::Pending)
+        .bind(limit)
+        .bind(OUTBOX_CLAIM_LEASE_MINUTES)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut summary = OutboxDrainSummary::default();
+        for entry in due {
+            match self
+                .deliver(&entry.recipient, &entry.subject, &entry.body)
+                .await
+            {
+                Ok(()) => {
+                    sqlx::query(
+                        "UPDATE notification_outbox SET status = $2, attempts = attempts + 1, sent_at = NOW(), last_error = NULL WHERE id = $1",
+                    )
+                    .bind(entry.id)
+                    .bind(DeliveryStatus::Sent)
+                    .execute(&self.db)
+                    .await?;
+                    summary.sent += 1;
+                }
+                Err(e) => {
+                    let attempts = entry.attempts + 1;
+                    let status = if attempts >= OUTBOX_MAX_ATTEMPTS {
+                        DeliveryStatus::Failed
+                    } else {
+                        DeliveryStatus::Pending
+                    };
+                    tracing::error!(
+                        "Failed to send notification to {} (attempt {}): {}",
+                        entry.recipient,
+                        attempts,
+                        e
+                    );
+
+                    // Back off exponentially: 2, 4, 8, ... minutes
+                    sqlx::query(
+                        r#"
+                        UPDATE notification_outbox
+                        SET status = $2,
+                            attempts = $3,
+                            last_error = $4,
+                            next_attempt_at = NOW() + make_interval(mins => (2 ^ $3)::int)
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(entry.id)
+                    .bind(status)
+                    .bind(attempts)
+                    .bind(e.to_string())
+                    .execute(&self.db)
+                    .await?;
+
+                    match status {
+                        DeliveryStatus::Failed => summary.failed += 1,
+                        _ => summary.retrying += 1,
+                    }
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
     pub async fn find_user_stale_applications(
         &self,
         user_id: i32,
-        days: i32,
+        days: Option<i32>,
     ) -> Result<Vec<Application>> {
-        let cutoff_date = Utc::now() - Duration::days(days as i64);
+        self.find_user_stale_applications_at(user_id, days, Utc::now())
+            .await
+    }
 
+    /// The user's applications that are stale as of `at`; a future `at` gives
+    /// the ones that go stale by then unless there is new activity
+    pub async fn find_user_stale_applications_at(
+        &self,
+        user_id: i32,
+        days: Option<i32>,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<Application>> {
         let results = sqlx::query_as::<_, Application>(
             r#"
-            SELECT * FROM applications
-            WHERE user_id = $1 
-            AND updated_at < $2 
-            AND status IN ('waiting', 'next_stage')
-            ORDER BY updated_at ASC
+            SELECT a.* FROM applications a
+            JOIN stale_thresholds t ON t.status = a.status
+            WHERE a.user_id = $1
+            AND a.last_activity_at < $3 - make_interval(days => COALESCE($2::int, t.days))
+            ORDER BY a.last_activity_at ASC
             "#,
         )
         .bind(user_id)
-        .bind(cutoff_date)
+        .bind(days)
+        .bind(at)
         .fetch_all(&self.db)
         .await?;
 
@@ -0,0 +1,283 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::str::FromStr;
+
+use crate::services::notification::{NotificationService, STALE_REMINDER_SCHEDULE};
+use crate::utils::logger::LOGGER;
+
+pub const STALE_REMINDERS_JOB: &str = "stale_reminders";
+pub const NOTIFICATION_OUTBOX_JOB: &str = "notification_outbox";
+pub const WEBHOOK_DELIVERIES_JOB: &str = "webhook_deliveries";
+pub const ANALYTICS_REFRESH_JOB: &str = "analytics_refresh";
+
+/// Every minute, on the minute
+pub const NOTIFICATION_OUTBOX_SCHEDULE: &str = "0 * * * * *";
+/// Every minute, offset from the outbox so the two don't start together
+pub const WEBHOOK_DELIVERIES_SCHEDULE: &str = "30 * * * * *";
+pub const ANALYTICS_REFRESH_SCHEDULE: &str = "0 */15 * * * *";
+
+/// The jobs the background scheduler runs, with their cron expressions in UTC
+pub const JOBS: &[(&str, &str)] = &[
+    (STALE_REMINDERS_JOB, STALE_REMINDER_SCHEDULE),
+    (NOTIFICATION_OUTBOX_JOB, NOTIFICATION_OUTBOX_SCHEDULE),
+    (WEBHOOK_DELIVERIES_JOB, WEBHOOK_DELIVERIES_SCHEDULE),
+    (ANALYTICS_REFRESH_JOB, ANALYTICS_REFRESH_SCHEDULE),
+];
+
+/// How long after boot the `RUN_NOTIFICATIONS_ON_STARTUP` run starts, so it
+/// doesn't compete with startup for connections
+pub const STARTUP_NOTIFICATIONS_DELAY_SECS: u64 = 30;
+
+/// One stale-reminder run right after boot, from `RUN_NOTIFICATIONS_ON_STARTUP`:
+/// `true` queues reminders, `dry-run` only logs how many would go out.
+/// Off by default so a deploy never sends reminders by itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupNotifications {
+    Off,
+    Queue,
+    DryRun,
+}
+
+impl StartupNotifications {
+    pub fn from_env() -> Self {
+        match env::var("RUN_NOTIFICATIONS_ON_STARTUP").as_deref() {
+            Ok("true") | Ok("1") => StartupNotifications::Queue,
+            Ok("dry-run") => StartupNotifications::DryRun,
+            _ => StartupNotifications::Off,
+        }
+    }
+}
+
+/// How late a run may start before it counts as missed, from
+/// `SCHEDULER_MISSED_RUN_GRACE_MINUTES` (default 5)
+pub fn missed_run_grace() -> Duration {
+    let minutes = env::var("SCHEDULER_MISSED_RUN_GRACE_MINUTES")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|minutes| *minutes >= 0)
+        .unwrap_or(5);
+    Duration::minutes(minutes)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunOutcome {
+    Success,
+    /// The job ran but had nothing to do, e.g. notifications are disabled
+    Skipped,
+    Failed,
+}
+
+impl RunOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunOutcome::Success => "success",
+            RunOutcome::Skipped => "skipped",
+            RunOutcome::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "success" => Some(RunOutcome::Success),
+            "skipped" => Some(RunOutcome::Skipped),
+            "failed" => Some(RunOutcome::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct SchedulerRun {
+    job: String,
+    registered_at: DateTime<Utc>,
+    last_started_at: Option<DateTime<Utc>>,
+    last_finished_at: Option<DateTime<Utc>>,
+    last_outcome: Option<String>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobStatus {
+    pub job: String,
+    pub schedule: String,
+    #[serde(with = "crate::utils::time::rfc3339::option")]
+    pub last_started_at: Option<DateTime<Utc>>,
+    /// `None` while the first run is still going, or when it never finished
+    #[serde(with = "crate::utils::time::rfc3339::option")]
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_outcome: Option<RunOutcome>,
+    pub last_error: Option<String>,
+    /// When the run after the last one was due to start
+    #[serde(with = "crate::utils::time::rfc3339::option")]
+    pub next_due_at: Option<DateTime<Utc>>,
+    /// The run at `next_due_at` hasn't started within the grace period, or
+    /// the scheduler never registered the job at all
+    pub missed: bool,
+}
+
+/// The daily stale-reminder job, shared by the cron schedule and the startup run.
+///
+/// `enabled` is the `notifications.enabled` setting. A dry run finds the same
+/// recipients but queues nothing.
+pub async fn run_stale_reminders(
+    db: PgPool,
+    enabled: bool,
+    dry_run: bool,
+) -> Result<RunOutcome, String> {
+    if !enabled {
+        tracing::info!("Daily notifications are disabled in settings");
+        return Ok(RunOutcome::Skipped);
+    }
+
+    let notification_service = NotificationService::new(db);
+    if dry_run {
+        let grouped = notification_service
+            .stale_applications_by_user(None, None)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to find stale applications: {}", e);
+                e.to_string()
+            })?;
+        tracing::info!(
+            "Dry run: daily notifications would be queued for {} users",
+            grouped.len()
+        );
+        return Ok(RunOutcome::Skipped);
+    }
+
+    match notification_service.process_stale_notifications().await {
+        Ok(queued) => {
+            tracing::info!("Daily notifications queued for {} users", queued);
+            Ok(RunOutcome::Success)
+        }
+        Err(e) => {
+            tracing::error!("Failed to process notifications: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Adds a row for every job in `JOBS`; rows that exist keep their history
+pub async fn register_jobs(db: &PgPool) -> Result<(), sqlx::Error> {
+    for (job, schedule) in JOBS {
+        sqlx::query(
+            "INSERT INTO scheduler_runs (job, schedule) VALUES ($1, $2)
+             ON CONFLICT (job) DO UPDATE SET schedule = EXCLUDED.schedule",
+        )
+        .bind(job)
+        .bind(schedule)
+        .execute(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Runs one scheduled job, recording when it started and how it ended.
+///
+/// The job runs in its own task so a panic is recorded as a failure instead of
+/// only killing the run. Failing to record is logged but never stops the job.
+pub async fn run_recorded<Fut>(db: &PgPool, job: &str, run: Fut)
+where
+    Fut: Future<Output = Result<RunOutcome, String>> + Send + 'static,
+{
+    if let Err(e) = sqlx::query(
+        "UPDATE scheduler_runs
+         SET last_started_at = NOW(), last_finished_at = NULL, last_outcome = NULL, last_error = NULL
+         WHERE job = $1",
+    )
+    .bind(job)
+    .execute(db)
+    .await
+    {
+        tracing::warn!("Failed to record start of scheduled job {}: {}", job, e);
+    }
+
+    let (outcome, error) = match tokio::spawn(run).await {
+        Ok(Ok(outcome)) => (outcome, None),
+        Ok(Err(e)) => (RunOutcome::Failed, Some(e)),
+        Err(e) if e.is_panic() => (RunOutcome::Failed, Some("The job panicked".to_string())),
+        Err(e) => (RunOutcome::Failed, Some(e.to_string())),
+    };
+
+    if let Some(error) = &error {
+        let mut context = HashMap::new();
+        context.insert(
+            "error_type".to_string(),
+            serde_json::Value::String("scheduled_job_failed".to_string()),
+        );
+        context.insert(
+            "job".to_string(),
+            serde_json::Value::String(job.to_string()),
+        );
+        LOGGER.log_error(&format!("Scheduled job {} failed: {}", job, error), context);
+    }
+
+    if let Err(e) = sqlx::query(
+        "UPDATE scheduler_runs
+         SET last_finished_at = NOW(), last_outcome = $2, last_error = $3
+         WHERE job = $1",
+    )
+    .bind(job)
+    .bind(outcome.as_str())
+    .bind(&error)
+    .execute(db)
+    .await
+    {
+        tracing::warn!("Failed to record end of scheduled job {}: {}", job, e);
+    }
+}
+
+/// Where every job in `JOBS` stands at `now`
+pub async fn job_statuses(
+    db: &PgPool,
+    now: DateTime<Utc>,
+    grace: Duration,
+) -> Result<Vec<JobStatus>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, SchedulerRun>(
+        "SELECT job, registered_at, last_started_at, last_finished_at, last_outcome, last_error
+         FROM scheduler_runs",
+    )
+    .fetch_all(db)
+    .await?;
+    let mut rows: HashMap<String, SchedulerRun> =
+        rows.into_iter().map(|row| (row.job.clone(), row)).collect();
+
+    Ok(JOBS
+        .iter()
+        .map(|(job, schedule)| {
+            let Some(row) = rows.remove(*job) else {
+                return JobStatus {
+                    job: job.to_string(),
+                    schedule: schedule.to_string(),
+                    last_started_at: None,
+                    last_finished_at: None,
+                    last_outcome: None,
+                    last_error: None,
+                    next_due_at: None,
+                    missed: true,
+                };
+            };
+
+            let since = row.last_started_at.unwrap_or(row.registered_at);
+            let next_due_at = cron::Schedule::from_str(schedule)
+                .ok()
+                .and_then(|schedule| schedule.after(&since).next());
+
+            JobStatus {
+                job: row.job,
+                schedule: schedule.to_string(),
+                last_started_at: row.last_started_at,
+                last_finished_at: row.last_finished_at,
+                last_outcome: row.last_outcome.as_deref().and_then(RunOutcome::parse),
+                last_error: row.last_error,
+                next_due_at,
+                missed: next_due_at.is_some_and(|due| due + grace < now),
+            }
+        })
+        .collect())
+}
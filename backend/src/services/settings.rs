@@ -0,0 +1,166 @@
+use serde::de::DeserializeOwned;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::setting::Setting;
+
+/// `false` pauses the daily stale-application notifications
+pub const NOTIFICATIONS_ENABLED: &str = "notifications.enabled";
+
+/// `false` serves downloads with the content type of their extension alone,
+/// without checking it against the file's bytes. Only then are S3 downloads
+/// redirected to the bucket instead of passing through the server.
+pub const DETECT_CONTENT_TYPE: &str = "files.detect_content_type";
+
+/// Reason codes accepted for screening and interview outcomes on top of the built-in ones
+pub const OUTCOME_REASON_CODES: &str = "outcome.reason_codes";
+
+/// Scale of screening and interview scores: `5` for 1-5, anything else for 0-100
+pub const OUTCOME_SCORE_SCALE: &str = "outcome.score_scale";
+
+/// Longest key the `settings` table accepts
+pub const MAX_KEY_LENGTH: usize = 100;
+
+/// Longest reason code the `reason_code` columns accept (`VARCHAR(50)`)
+pub const MAX_REASON_CODE_LENGTH: usize = 50;
+
+/// In-memory snapshot of the `settings` table, shared through `AppState`
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    entries: BTreeMap<String, Setting>,
+}
+
+impl Settings {
+    pub async fn load(db: &PgPool) -> Result<Self, sqlx::Error> {
+        let rows = sqlx::query_as::<_, Setting>("SELECT * FROM settings")
+            .fetch_all(db)
+            .await?;
+
+        Ok(Self {
+            entries: rows.into_iter().map(|row| (row.key.clone(), row)).collect(),
+        })
+    }
+
+    /// Typed value of `key`, `None` when it is unset or stored with a different shape
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let setting = self.entries.get(key)?;
+        serde_json::from_value(setting.value.clone()).ok()
+    }
+
+    /// All settings ordered by key
+    pub fn all(&self) -> Vec<Setting> {
+        self.entries.values().cloned().collect()
+    }
+}
+
+/// Keys are lowercase identifiers such as `notifications.enabled`
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+}
+
+/// Problems with a value the `settings` table would store but its readers could not use
+pub fn value_errors(key: &str, value: &serde_json::Value) -> Vec<String> {
+    match key {
+        OUTCOME_REASON_CODES => match serde_json::from_value::<Vec<String>>(value.clone()) {
+            Ok(codes) => codes
+                .iter()
+                .filter(|code| {
+                    code.trim().is_empty() || code.chars().count() > MAX_REASON_CODE_LENGTH
+                })
+                .map(|code| {
+                    format!(
+                        "Reason code '{}' must be 1 to {} characters",
+                        code, MAX_REASON_CODE_LENGTH
+                    )
+                })
+                .collect(),
+            Err(_) => vec![format!("Setting '{}' must be a list of strings", key)],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Upserts `changes` in one transaction, writing an `audit_log` row for every value that changed
+pub async fn save_settings(
+    db: &PgPool,
+    changes: &HashMap<String, serde_json::Value>,
+    updated_by: i32,
+) -> Result<usize, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let mut changed = 0;
+
+    for (key, value) in changes {
+        // Unchanged values return no row from the upsert, so they are not audited
+        let result = sqlx::query(
+            r#"
+            WITH old AS (
+                SELECT * FROM settings WHERE key = $1 FOR UPDATE
+            ), new AS (
+                INSERT INTO settings (key, value, updated_by, updated_at)
+                VALUES ($1, $2, $3, NOW())
+                ON CONFLICT (key) DO UPDATE
+                SET value = EXCLUDED.value, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+                WHERE settings.value IS DISTINCT FROM EXCLUDED.value
+                RETURNING *
+            )
+            INSERT INTO audit_log (table_name, operation, old_data, new_data, user_id)
+            SELECT
+                'settings',
+                CASE WHEN EXISTS (SELECT 1 FROM old) THEN 'UPDATE' ELSE 'INSERT' END,
+                (SELECT to_jsonb(old) FROM old),
+                to_jsonb(new),
+                $3
+            FROM new
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .bind(updated_by)
+        .execute(&mut *tx)
+        .await?;
+
+        changed += result.rows_affected() as usize;
+    }
+
+    tx.commit().await?;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reason_codes_must_fit_the_column() {
+        let fits = "x".repeat(MAX_REASON_CODE_LENGTH);
+        assert!(value_errors(OUTCOME_REASON_CODES, &json!(["relocation", fits])).is_empty());
+
+        let too_long = "x".repeat(MAX_REASON_CODE_LENGTH + 1);
+        assert_eq!(
+            value_errors(OUTCOME_REASON_CODES, &json!(["relocation", too_long, " "])).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn reason_codes_length_counts_characters() {
+        let cyrillic = "я".repeat(MAX_REASON_CODE_LENGTH);
+        assert!(value_errors(OUTCOME_REASON_CODES, &json!([cyrillic])).is_empty());
+    }
+
+    #[test]
+    fn reason_codes_must_be_a_list_of_strings() {
+        assert_eq!(value_errors(OUTCOME_REASON_CODES, &json!("other")).len(), 1);
+        assert_eq!(value_errors(OUTCOME_REASON_CODES, &json!([1, 2])).len(), 1);
+    }
+
+    #[test]
+    fn other_keys_are_not_checked() {
+        assert!(value_errors(NOTIFICATIONS_ENABLED, &json!("anything")).is_empty());
+    }
+}
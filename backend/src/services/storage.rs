@@ -0,0 +1,401 @@
+use async_trait::async_trait;
+use s3::{creds::Credentials, Bucket, Region};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::AsyncRead;
+use tokio_stream::StreamExt;
+use tokio_util::io::StreamReader;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("file not found")]
+    NotFound,
+    #[error("invalid file key")]
+    InvalidKey,
+    #[error("file already exists")]
+    AlreadyExists,
+    /// The disk or quota ran out; not a bug, ops has to free space
+    #[error("storage full: {0}")]
+    StorageFull(String),
+    #[error("storage I/O error: {0}")]
+    IoError(String),
+    #[error("storage backend error: {0}")]
+    BackendError(String),
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(error: std::io::Error) -> Self {
+        if is_storage_full(&error) {
+            return StorageError::StorageFull(error.to_string());
+        }
+        match error.kind() {
+            std::io::ErrorKind::NotFound => StorageError::NotFound,
+            std::io::ErrorKind::AlreadyExists => StorageError::AlreadyExists,
+            _ => StorageError::IoError(error.to_string()),
+        }
+    }
+}
+
+/// Whether an I/O error means the disk or the user's quota is full.
+///
+/// Checked through the errno because the matching `ErrorKind`s are newer
+/// than the crate's minimum Rust version.
+pub fn is_storage_full(error: &std::io::Error) -> bool {
+    use nix::errno::Errno;
+
+    error
+        .raw_os_error()
+        .is_some_and(|code| code == Errno::ENOSPC as i32 || code == Errno::EDQUOT as i32)
+}
+
+/// Bytes available to unprivileged writers on the filesystem holding `path`
+pub fn available_space(path: impl AsRef<Path>) -> std::io::Result<u64> {
+    let stats = nix::sys::statvfs::statvfs(path.as_ref())?;
+    Ok(stats.blocks_available() as u64 * stats.fragment_size() as u64)
+}
+
+/// A stored file being read, chunk by chunk
+pub type FileReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// How long the URL behind `S3FileStore::open` stays valid; the download has
+/// to start within it, not finish
+const OPEN_URL_TTL_SECS: u32 = 60;
+
+/// Where uploaded recordings live.
+///
+/// Keys are the server-generated file names stored in `screenings.file_path`,
+/// `interviews.file_path` and `documents.file_path`. Uploads are staged on local
+/// disk first and moved into the store with `put_file` before the row
+/// referencing them is committed.
+#[async_trait]
+pub trait FileStore: Send + Sync {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError>;
+
+    /// Moves a file staged on local disk into the store.
+    ///
+    /// Never overwrites: fails with `AlreadyExists` if the key is taken.
+    async fn put_file(&self, key: &str, staged: &Path) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+
+    /// Reads the file as a stream, without holding it in memory as a whole
+    async fn open(&self, key: &str) -> Result<FileReader, StorageError>;
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError>;
+
+    /// A time-limited URL clients can download from directly, if the backend supports it.
+    ///
+    /// The download is served with `content_type`, not whatever type the object
+    /// was stored with.
+    fn presigned_url(
+        &self,
+        _key: &str,
+        _expires_in_secs: u32,
+        _content_disposition: &str,
+        _content_type: &str,
+    ) -> Result<Option<String>, StorageError> {
+        Ok(None)
+    }
+}
+
+/// Selects the backend from `STORAGE_BACKEND` (`local` by default, or `s3`)
+pub fn file_store_from_env(upload_dir: &str) -> anyhow::Result<Arc<dyn FileStore>> {
+    match env::var("STORAGE_BACKEND").as_deref() {
+        Ok("s3") => Ok(Arc::new(S3FileStore::from_env()?)),
+        Ok("local") | Err(_) => Ok(Arc::new(LocalFileStore::new(upload_dir))),
+        Ok(other) => anyhow::bail!("Unknown STORAGE_BACKEND '{}'", other),
+    }
+}
+
+/// Rejects keys that could escape the storage root
+fn validate_key(key: &str) -> Result<(), StorageError> {
+    if key.is_empty() || key.contains("..") || key.contains('/') || key.contains('\\') {
+        return Err(StorageError::InvalidKey);
+    }
+    Ok(())
+}
+
+/// Files in a directory on local disk
+pub struct LocalFileStore {
+    root: PathBuf,
+}
+
+impl LocalFileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Resolves a key to a path, making sure it stays inside the root directory
+    async fn resolve(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+
+        let canonical_file = fs::canonicalize(self.root.join(key)).await?;
+        let canonical_root = fs::canonicalize(&self.root).await?;
+
+        if !canonical_file.starts_with(&canonical_root) {
+            return Err(StorageError::InvalidKey);
+        }
+
+        Ok(canonical_file)
+    }
+}
+
+#[async_trait]
+impl FileStore for LocalFileStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        validate_key(key)?;
+        fs::write(self.root.join(key), data).await?;
+        Ok(())
+    }
+
+    async fn put_file(&self, key: &str, staged: &Path) -> Result<(), StorageError> {
+        validate_key(key)?;
+        // Unlike a rename, linking fails when the target already exists
+        fs::hard_link(staged, self.root.join(key)).await?;
+        fs::remove_file(staged).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = self.resolve(key).await?;
+        Ok(fs::read(path).await?)
+    }
+
+    async fn open(&self, key: &str) -> Result<FileReader, StorageError> {
+        let path = self.resolve(key).await?;
+        Ok(Box::new(fs::File::open(path).await?))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.resolve(key).await?;
+        fs::remove_file(path).await?;
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        match self.resolve(key).await {
+            Ok(path) => Ok(path.is_file()),
+            Err(StorageError::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Objects in an S3-compatible bucket.
+///
+/// Configured with `S3_BUCKET`, `S3_REGION`, optional `S3_ENDPOINT` (MinIO and
+/// other S3-compatible services, addressed path-style) and an optional
+/// `S3_PREFIX`. Credentials come from the usual `AWS_ACCESS_KEY_ID` /
+/// `AWS_SECRET_ACCESS_KEY` variables.
+pub struct S3FileStore {
+    bucket: Bucket,
+    prefix: String,
+    /// Streams objects for `open`
+    http: reqwest::Client,
+}
+
+impl S3FileStore {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let bucket_name = env::var("S3_BUCKET")
+            .map_err(|_| anyhow::anyhow!("S3_BUCKET must be set when STORAGE_BACKEND=s3"))?;
+        let region_name = env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+
+        let region = match env::var("S3_ENDPOINT") {
+            Ok(endpoint) => Region::Custom {
+                region: region_name,
+                endpoint,
+            },
+            Err(_) => region_name.parse()?,
+        };
+        let is_custom_endpoint = matches!(region, Region::Custom { .. });
+
+        let credentials = Credentials::from_env()?;
+        let mut bucket = Bucket::new(&bucket_name, region, credentials)?;
+        if is_custom_endpoint {
+            bucket = bucket.with_path_style();
+        }
+
+        let prefix = env::var("S3_PREFIX")
+            .map(|p| p.trim_matches('/').to_string())
+            .unwrap_or_default();
+
+        Ok(Self {
+            bucket,
+            prefix,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    fn object_path(&self, key: &str) -> Result<String, StorageError> {
+        validate_key(key)?;
+        Ok(if self.prefix.is_empty() {
+            format!("/{}", key)
+        } else {
+            format!("/{}/{}", self.prefix, key)
+        })
+    }
+}
+
+fn backend_error(error: s3::error::S3Error) -> StorageError {
+    StorageError::BackendError(error.to_string())
+}
+
+/// rust-s3 reports HTTP failures as status codes rather than errors
+fn check_status(status: u16) -> Result<(), StorageError> {
+    match status {
+        200..=299 => Ok(()),
+        404 => Err(StorageError::NotFound),
+        other => Err(StorageError::BackendError(format!(
+            "S3 responded with status {}",
+            other
+        ))),
+    }
+}
+
+#[async_trait]
+impl FileStore for S3FileStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.object_path(key)?;
+        let response = self
+            .bucket
+            .put_object(path, &data)
+            .await
+            .map_err(backend_error)?;
+        check_status(response.status_code())
+    }
+
+    async fn put_file(&self, key: &str, staged: &Path) -> Result<(), StorageError> {
+        // S3 has no conditional put here, so this check narrows rather than closes
+        // the window; keys are random UUIDs, which makes a clash a non-event
+        if self.exists(key).await? {
+            return Err(StorageError::AlreadyExists);
+        }
+
+        let path = self.object_path(key)?;
+        let mut file = fs::File::open(staged).await?;
+        let status = self
+            .bucket
+            .put_object_stream(&mut file, path)
+            .await
+            .map_err(backend_error)?;
+        check_status(status)?;
+
+        // The staged copy is only removed once the object is safely stored
+        fs::remove_file(staged).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = self.object_path(key)?;
+        let response = self.bucket.get_object(path).await.map_err(backend_error)?;
+        check_status(response.status_code())?;
+        Ok(response.bytes().to_vec())
+    }
+
+    async fn open(&self, key: &str) -> Result<FileReader, StorageError> {
+        // rust-s3's own object stream isn't Send, so the object is fetched
+        // through a short-lived presigned URL instead
+        let path = self.object_path(key)?;
+        let url = self
+            .bucket
+            .presign_get(path, OPEN_URL_TTL_SECS, None)
+            .map_err(backend_error)?;
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| StorageError::BackendError(e.to_string()))?;
+        check_status(response.status().as_u16())?;
+
+        let chunks = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(std::io::Error::other));
+        Ok(Box::new(StreamReader::new(Box::pin(chunks))))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.object_path(key)?;
+        let response = self
+            .bucket
+            .delete_object(path)
+            .await
+            .map_err(backend_error)?;
+        check_status(response.status_code())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        let path = self.object_path(key)?;
+        let (_, status) = self.bucket.head_object(path).await.map_err(backend_error)?;
+        match check_status(status) {
+            Ok(()) => Ok(true),
+            Err(StorageError::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn presigned_url(
+        &self,
+        key: &str,
+        expires_in_secs: u32,
+        content_disposition: &str,
+        content_type: &str,
+    ) -> Result<Option<String>, StorageError> {
+        let path = self.object_path(key)?;
+        let queries = HashMap::from([
+            (
+                "response-content-disposition".to_string(),
+                content_disposition.to_string(),
+            ),
+            (
+                "response-content-type".to_string(),
+                content_type.to_string(),
+            ),
+        ]);
+        let url = self
+            .bucket
+            .presign_get(path, expires_in_secs, Some(queries))
+            .map_err(backend_error)?;
+        Ok(Some(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::errno::Errno;
+    use std::io;
+
+    #[test]
+    fn full_disk_and_quota_are_storage_full() {
+        for errno in [Errno::ENOSPC, Errno::EDQUOT] {
+            let error = io::Error::from_raw_os_error(errno as i32);
+            assert!(is_storage_full(&error));
+            assert!(matches!(
+                StorageError::from(error),
+                StorageError::StorageFull(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn other_errors_keep_their_kind() {
+        let missing = io::Error::from_raw_os_error(Errno::ENOENT as i32);
+        assert!(!is_storage_full(&missing));
+        assert!(matches!(
+            StorageError::from(missing),
+            StorageError::NotFound
+        ));
+
+        let denied = io::Error::from_raw_os_error(Errno::EACCES as i32);
+        assert!(matches!(
+            StorageError::from(denied),
+            StorageError::IoError(_)
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{bail, Context, Result};
+use std::env;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::{fs, process::Command, sync::Semaphore};
+use uuid::Uuid;
+
+/// Uploaded formats browsers can't play; everything else is previewed as-is
+const NON_WEB_EXTENSIONS: &[&str] = &["avi", "mkv", "mov"];
+
+/// Previews are H.264/AAC MP4, which every major browser plays
+pub const PREVIEW_EXTENSION: &str = "mp4";
+
+pub fn needs_preview(filename: &str) -> bool {
+    std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| NON_WEB_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Storage key of the preview for `filename`, next to the original
+pub fn preview_key(filename: &str) -> String {
+    let stem = std::path::Path::new(filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(filename);
+    format!("{}.preview.{}", stem, PREVIEW_EXTENSION)
+}
+
+/// Converts recordings with an external ffmpeg binary.
+///
+/// Configured with `FFMPEG_PATH`; `TRANSCODE_CONCURRENCY` (default 1) caps how
+/// many ffmpeg processes run at once and `TRANSCODE_TIMEOUT_SECS` (default 900)
+/// kills runaway conversions.
+pub struct Transcoder {
+    ffmpeg_path: PathBuf,
+    work_dir: PathBuf,
+    timeout: Duration,
+    permits: Semaphore,
+}
+
+/// Removes a scratch file however the conversion ends
+struct ScratchFile(PathBuf);
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+impl Transcoder {
+    /// `None` when `FFMPEG_PATH` is unset, i.e. previews are disabled
+    pub fn from_env(work_dir: &str) -> Option<Self> {
+        let ffmpeg_path = env::var("FFMPEG_PATH")
+            .ok()
+            .filter(|path| !path.is_empty())?;
+        let concurrency = env::var("TRANSCODE_CONCURRENCY")
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(1);
+        let timeout_secs = env::var("TRANSCODE_TIMEOUT_SECS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(900);
+
+        Some(Self {
+            ffmpeg_path: PathBuf::from(ffmpeg_path),
+            work_dir: PathBuf::from(work_dir),
+            timeout: Duration::from_secs(timeout_secs),
+            permits: Semaphore::new(concurrency),
+        })
+    }
+
+    /// Converts `source` (a file with extension `source_extension`) to a web-friendly MP4
+    pub async fn to_preview(&self, source: &[u8], source_extension: &str) -> Result<Vec<u8>> {
+        let _permit = self.permits.acquire().await?;
+
+        let id = Uuid::new_v4();
+        let input = ScratchFile(
+            self.work_dir
+                .join(format!("{}.source.{}", id, source_extension)),
+        );
+        let output = ScratchFile(
+            self.work_dir
+                .join(format!("{}.preview.{}", id, PREVIEW_EXTENSION)),
+        );
+        fs::write(&input.0, source)
+            .await
+            .context("Failed to write transcode input")?;
+
+        let child = Command::new(&self.ffmpeg_path)
+            .args(["-nostdin", "-loglevel", "error", "-y", "-i"])
+            .arg(&input.0)
+            .args([
+                "-c:v",
+                "libx264",
+                "-preset",
+                "veryfast",
+                "-crf",
+                "28",
+                "-pix_fmt",
+                "yuv420p",
+                "-c:a",
+                "aac",
+                "-movflags",
+                "+faststart",
+            ])
+            .arg(&output.0)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start ffmpeg")?;
+
+        let result = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .context("ffmpeg timed out")?
+            .context("Failed to run ffmpeg")?;
+
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            bail!("ffmpeg exited with {}: {}", result.status, stderr.trim());
+        }
+
+        fs::read(&output.0)
+            .await
+            .context("Failed to read transcode output")
+    }
+}
@@ -0,0 +1,482 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::{PgExecutor, PgPool};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use crate::models::application::{Application, ApplicationStatus};
+
+/// Delivery attempts before a delivery is marked `failed`
+const WEBHOOK_MAX_ATTEMPTS: i32 = 8;
+
+/// Deliveries sent per dispatcher run
+pub const WEBHOOK_BATCH_SIZE: i64 = 50;
+
+/// Receivers get this long to answer before the attempt counts as failed
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a dispatcher run owns the deliveries it claimed. Covers a full
+/// batch of timed-out attempts; past it, another run may pick them up again.
+const WEBHOOK_CLAIM_LEASE_MINUTES: i32 = 10;
+
+/// Longest response body kept in `last_error` for a failed attempt
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventType {
+    ApplicationCreated,
+    StatusChanged,
+    ScreeningPassed,
+    ScreeningFailed,
+    InterviewPassed,
+    InterviewFailed,
+}
+
+impl WebhookEventType {
+    /// Same spelling as the serialized value and `webhooks.event_types`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::ApplicationCreated => "application_created",
+            WebhookEventType::StatusChanged => "status_changed",
+            WebhookEventType::ScreeningPassed => "screening_passed",
+            WebhookEventType::ScreeningFailed => "screening_failed",
+            WebhookEventType::InterviewPassed => "interview_passed",
+            WebhookEventType::InterviewFailed => "interview_failed",
+        }
+    }
+}
+
+/// The application an event is about, as sent to receivers
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookApplication {
+    pub id: i32,
+    pub user_id: i32,
+    pub cohort_id: Option<i32>,
+    pub company: String,
+    pub status: ApplicationStatus,
+}
+
+/// JSON body POSTed to receivers
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEventType,
+    #[serde(with = "crate::utils::time::rfc3339")]
+    pub occurred_at: DateTime<Utc>,
+    pub application: WebhookApplication,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<ApplicationStatus>,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEventType, application: &Application) -> Self {
+        Self {
+            event,
+            occurred_at: Utc::now(),
+            application: WebhookApplication {
+                id: application.id,
+                user_id: application.user_id,
+                cohort_id: application.cohort_id,
+                company: application.company.clone(),
+                status: application.status.clone(),
+            },
+            previous_status: None,
+        }
+    }
+
+    /// `application` as it was before moving to `status`
+    pub fn status_changed(application: &Application, status: ApplicationStatus) -> Self {
+        let mut payload = Self::new(WebhookEventType::StatusChanged, application);
+        payload.previous_status = Some(application.status.clone());
+        payload.application.status = status;
+        payload
+    }
+}
+
+/// Queues a delivery of `payload` for every active webhook subscribed to it.
+///
+/// Run it on the transaction making the change, so the event is only sent
+/// when the change is committed. Returns the number of deliveries queued.
+pub async fn enqueue<'e, E: PgExecutor<'e>>(
+    executor: E,
+    payload: &WebhookPayload,
+) -> Result<u64, sqlx::Error> {
+    let body = serde_json::to_value(payload).unwrap_or_default();
+    let result = sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_type, payload)
+        SELECT id, $1, $3 FROM webhooks
+        WHERE active
+        AND (cohort_id IS NULL OR cohort_id = $2)
+        AND (cardinality(event_types) = 0 OR $1 = ANY(event_types))
+        "#,
+    )
+    .bind(payload.event.as_str())
+    .bind(payload.application.cohort_id)
+    .bind(body)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Hex HMAC-SHA256 of `{timestamp}.{body}` under the webhook's secret, sent as
+/// `X-Webhook-Signature: sha256=<hex>`. Including the timestamp lets receivers
+/// reject replayed deliveries.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DueDelivery {
+    id: i64,
+    event_type: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// What a failed attempt left behind for the delivery log
+struct AttemptError {
+    response_status: Option<i32>,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct WebhookDrainSummary {
+    pub delivered: usize,
+    pub retrying: usize,
+    pub failed: usize,
+}
+
+/// Sends queued webhook deliveries and records each outcome
+pub struct WebhookDispatcher {
+    db: PgPool,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn send(&self, delivery: &DueDelivery) -> Result<i32, AttemptError> {
+        // The host is checked again here since its DNS may have changed since
+        // registration, and the client is pinned to the checked addresses so
+        // the connection cannot be rebound to an internal one
+        let (host, addrs) = resolve_public_target(&delivery.url)
+            .await
+            .map_err(|message| AttemptError {
+                response_status: None,
+                message,
+            })?;
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            // A receiver answering with a redirect is misconfigured, not a new target
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve_to_addrs(&host, &addrs)
+            .build()
+            .map_err(|e| AttemptError {
+                response_status: None,
+                message: e.to_string(),
+            })?;
+
+        let body = delivery.payload.to_string();
+        let timestamp = Utc::now().timestamp();
+        let signature = sign(&delivery.secret, timestamp, body.as_bytes());
+
+        let response = client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Webhook-Event", &delivery.event_type)
+            .header("X-Webhook-Delivery", delivery.id.to_string())
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AttemptError {
+                response_status: None,
+                message: e.to_string(),
+            })?;
+
+        let status = i32::from(response.status().as_u16());
+        if response.status().is_success() {
+            return Ok(status);
+        }
+
+        let text = response.text().await.unwrap_or_default();
+        Err(AttemptError {
+            response_status: Some(status),
+            message: format!(
+                "Receiver answered {}: {}",
+                status,
+                text.chars().take(MAX_ERROR_BODY_CHARS).collect::<String>()
+            ),
+        })
+    }
+
+    /// Sends due deliveries, marking each delivered or scheduling a retry.
+    ///
+    /// Deliveries are claimed in one short statement that pushes their
+    /// `next_attempt_at` past a lease, so concurrent dispatchers skip them and
+    /// no connection is held while receivers answer. Each outcome is written on
+    /// its own. A crash mid-batch leaves the unrecorded deliveries to be retried
+    /// once the lease runs out, so a delivery may arrive twice but is never
+    /// dropped. Receivers can deduplicate on `X-Webhook-Delivery`.
+    pub async fn drain(&self, limit: i64) -> Result<WebhookDrainSummary> {
+        let due = sqlx::query_as::<_, DueDelivery>(
+            r#"
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = NOW() + make_interval(mins => $2)
+            FROM (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            ) claimed, webhooks w
+            WHERE d.id = claimed.id AND w.id = d.webhook_id
+            RETURNING d.id, d.event_type, d.payload, d.attempts, w.url, w.secret
+            "#,
+        )
+        .bind(limit)
+        .bind(WEBHOOK_CLAIM_LEASE_MINUTES)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut summary = WebhookDrainSummary::default();
+        for delivery in due {
+            match self.send(&delivery).await {
+                Ok(response_status) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE webhook_deliveries
+                        SET status = 'delivered', attempts = attempts + 1, response_status = $2,
+                            last_error = NULL, delivered_at = NOW()
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(delivery.id)
+                    .bind(response_status)
+                    .execute(&self.db)
+                    .await?;
+                    summary.delivered += 1;
+                }
+                Err(e) => {
+                    let attempts = delivery.attempts + 1;
+                    let gave_up = attempts >= WEBHOOK_MAX_ATTEMPTS;
+                    tracing::warn!(
+                        "Webhook delivery {} to {} failed (attempt {}): {}",
+                        delivery.id,
+                        delivery.url,
+                        attempts,
+                        e.message
+                    );
+
+                    // Back off exponentially: 2, 4, 8, ... minutes
+                    sqlx::query(
+                        r#"
+                        UPDATE webhook_deliveries
+                        SET status = CASE WHEN $2 THEN 'failed' ELSE 'pending' END,
+                            attempts = $3,
+                            response_status = $4,
+                            last_error = $5,
+                            next_attempt_at = NOW() + make_interval(mins => (2 ^ $3)::int)
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(delivery.id)
+                    .bind(gave_up)
+                    .bind(attempts)
+                    .bind(e.response_status)
+                    .bind(&e.message)
+                    .execute(&self.db)
+                    .await?;
+
+                    if gave_up {
+                        summary.failed += 1;
+                    } else {
+                        summary.retrying += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Only plain http(s) endpoints can receive webhooks
+pub fn is_valid_webhook_url(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .map(|parsed| matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some())
+        .unwrap_or(false)
+}
+
+/// Resolves a webhook URL's host, refusing it unless every address it points
+/// at is publicly routable. Returns the host with the addresses to connect to.
+pub async fn resolve_public_target(url: &str) -> Result<(String, Vec<SocketAddr>), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| "Webhook URL has no host".to_string())?
+        .to_string();
+    let port = parsed
+        .port_or_known_default()
+        .ok_or_else(|| "Webhook URL has no port".to_string())?;
+
+    let lookup = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup, port))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("Could not resolve {}", host));
+    }
+    if let Some(addr) = addrs.iter().find(|a| !is_public_ip(a.ip())) {
+        return Err(format!(
+            "{} resolves to non-public address {}",
+            host,
+            addr.ip()
+        ));
+    }
+
+    Ok((host, addrs))
+}
+
+/// Whether an address is reachable on the public internet, so a webhook can't
+/// be aimed at loopback, private, link-local or other internal ranges
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64)
+                // IETF protocol assignments, 192.0.0.0/24
+                || (a == 192 && b == 0 && c == 0)
+                // Benchmarking, 198.18.0.0/15
+                || (a == 198 && (b & 0xfe) == 18)
+                // Reserved, 240.0.0.0/4
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = embedded_ipv4(v6) {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let segments = v6.segments();
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Unique local, fc00::/7
+                || (segments[0] & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (segments[0] & 0xffc0) == 0xfe80
+                // Local-use NAT64, 64:ff9b:1::/48
+                || segments[..3] == [0x64, 0xff9b, 1])
+        }
+    }
+}
+
+/// The IPv4 address an IPv6 address stands for, when the network would
+/// deliver it to that IPv4 host: IPv4-mapped and IPv4-compatible addresses,
+/// NAT64 (64:ff9b::/96) and 6to4 (2002::/16)
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = v6.segments();
+    let from_segments = |high: u16, low: u16| Ipv4Addr::from(((high as u32) << 16) | low as u32);
+
+    match segments {
+        // ::1 and :: are IPv4-compatible in form but handled as IPv6 themselves
+        _ if v6.is_loopback() || v6.is_unspecified() => None,
+        [0, 0, 0, 0, 0, 0xffff, high, low] | [0, 0, 0, 0, 0, 0, high, low] => {
+            Some(from_segments(high, low))
+        }
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some(from_segments(high, low)),
+        [0x2002, high, low, ..] => Some(from_segments(high, low)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(addr: &str) -> bool {
+        is_public_ip(addr.parse().unwrap())
+    }
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        assert!(public("93.184.216.34"));
+        assert!(public("2606:2800:220:1:248:1893:25c8:1946"));
+    }
+
+    #[test]
+    fn special_purpose_ipv4_ranges_are_refused() {
+        for addr in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "192.0.0.8",
+            "198.18.0.1",
+            "198.19.255.255",
+            "240.0.0.1",
+            "255.255.255.255",
+        ] {
+            assert!(!public(addr), "{}", addr);
+        }
+        assert!(public("198.20.0.1"));
+        assert!(public("192.0.1.1"));
+    }
+
+    #[test]
+    fn ipv4_mapped_and_compatible_addresses_use_the_embedded_ipv4() {
+        assert!(!public("::ffff:127.0.0.1"));
+        assert!(!public("::127.0.0.1"));
+        assert!(!public("::10.0.0.1"));
+        assert!(public("::ffff:93.184.216.34"));
+    }
+
+    #[test]
+    fn nat64_addresses_use_the_embedded_ipv4() {
+        assert!(!public("64:ff9b::7f00:1"));
+        assert!(!public("64:ff9b::a9fe:a9fe"));
+        assert!(!public("64:ff9b:1::5db8:d822"));
+        assert!(public("64:ff9b::5db8:d822"));
+    }
+
+    #[test]
+    fn six_to_four_addresses_use_the_embedded_ipv4() {
+        assert!(!public("2002:7f00:1::"));
+        assert!(!public("2002:c0a8:101::1"));
+        assert!(public("2002:5db8:d822::1"));
+    }
+
+    #[test]
+    fn internal_ipv6_ranges_are_refused() {
+        for addr in ["::1", "::", "fc00::1", "fd12::1", "fe80::1", "ff02::1"] {
+            assert!(!public(addr), "{}", addr);
+        }
+    }
+}
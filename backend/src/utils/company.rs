@@ -0,0 +1,32 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+// Mirrors the `normalize_company` SQL function in 012_company_normalization.sql;
+// keep the two in sync so backfilled and newly written rows group together.
+const QUOTES_PATTERN: &str = r#"[«»"']"#;
+const WHITESPACE_PATTERN: &str = r"\s+";
+const LEGAL_PREFIX_PATTERN: &str = r"(?i)^(ооо|зао|оао|пао|ао)\s+";
+const LEGAL_SUFFIX_PATTERN: &str =
+    r"(?i)[\s,.]+(inc|incorporated|llc|ltd|limited|corp|corporation|co|gmbh|plc|ag)\.?$";
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("valid company normalization regex"))
+}
+
+/// Grouping key for a company name.
+///
+/// Drops quotes, collapses whitespace, strips common legal forms ("Inc.", "LLC",
+/// "ООО", ...) and lowercases, so "Google", "google " and "Google Inc." share a key.
+pub fn normalize_company(name: &str) -> String {
+    static QUOTES: OnceLock<Regex> = OnceLock::new();
+    static WHITESPACE: OnceLock<Regex> = OnceLock::new();
+    static LEGAL_PREFIX: OnceLock<Regex> = OnceLock::new();
+    static LEGAL_SUFFIX: OnceLock<Regex> = OnceLock::new();
+
+    let name = regex(&QUOTES, QUOTES_PATTERN).replace_all(name, "");
+    let name = regex(&WHITESPACE, WHITESPACE_PATTERN).replace_all(&name, " ");
+    let name = regex(&LEGAL_PREFIX, LEGAL_PREFIX_PATTERN).replace(name.trim(), "");
+    let name = regex(&LEGAL_SUFFIX, LEGAL_SUFFIX_PATTERN).replace(&name, "");
+
+    name.trim().to_lowercase()
+}
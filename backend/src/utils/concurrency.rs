@@ -0,0 +1,45 @@
+use std::env;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Caps how many runs of an expensive computation execute at once.
+///
+/// Callers beyond the cap queue for up to `max_wait`, then give up, so a burst
+/// of requests can't hold every pool connection at the same time.
+#[derive(Debug)]
+pub struct ConcurrencyLimit {
+    permits: Semaphore,
+    max_wait: Duration,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max_concurrent: usize, max_wait: Duration) -> Self {
+        Self {
+            permits: Semaphore::new(max_concurrent.max(1)),
+            max_wait,
+        }
+    }
+
+    /// Reads `{prefix}_MAX_CONCURRENCY` (default 2) and `{prefix}_QUEUE_WAIT_MS`
+    /// (default 2000)
+    pub fn from_env(prefix: &str) -> Self {
+        let max_concurrent = env::var(format!("{}_MAX_CONCURRENCY", prefix))
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(2);
+        let max_wait_ms = env::var(format!("{}_QUEUE_WAIT_MS", prefix))
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(2000);
+
+        Self::new(max_concurrent, Duration::from_millis(max_wait_ms))
+    }
+
+    /// A slot to run in, or `None` when none freed up within `max_wait`
+    pub async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        tokio::time::timeout(self.max_wait, self.permits.acquire())
+            .await
+            .ok()?
+            .ok()
+    }
+}
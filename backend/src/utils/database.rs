@@ -1,11 +1,110 @@
 use anyhow::Result;
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    Executor, PgPool,
+};
+use std::env;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Reported as `application_name` in `pg_stat_activity`
+const APPLICATION_NAME: &str = "job-tracker-backend";
+/// Postgres truncates `application_name` beyond this length
+const MAX_APPLICATION_NAME_LEN: usize = 63;
+
+/// Attempts made by `with_retry`, including the first one
+const MAX_QUERY_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// `job-tracker-backend`, suffixed with `INSTANCE_ID` (or the pod `HOSTNAME`) when set
+fn application_name() -> String {
+    let name = match env::var("INSTANCE_ID").or_else(|_| env::var("HOSTNAME")) {
+        Ok(instance) if !instance.is_empty() => format!("{}/{}", APPLICATION_NAME, instance),
+        _ => APPLICATION_NAME.to_string(),
+    };
+    name.chars().take(MAX_APPLICATION_NAME_LEN).collect()
+}
 
 pub async fn create_pool(database_url: &str) -> Result<PgPool> {
+    let connect_options =
+        PgConnectOptions::from_str(database_url)?.application_name(&application_name());
+
+    // Server-side cap on query duration; unset or 0 leaves it disabled
+    let statement_timeout_ms = env::var("DB_STATEMENT_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+
     let pool = PgPoolOptions::new()
         .max_connections(10)
-        .connect(database_url)
+        .after_connect(move |conn, _meta| {
+            Box::pin(async move {
+                if statement_timeout_ms > 0 {
+                    conn.execute(
+                        format!("SET statement_timeout = {}", statement_timeout_ms).as_str(),
+                    )
+                    .await?;
+                }
+                Ok(())
+            })
+        })
+        .connect_with(connect_options)
         .await?;
 
     Ok(pool)
 }
+
+/// Escapes `LIKE` wildcards so user input only ever matches literally
+pub fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Whether Postgres cancelled the query for exceeding `statement_timeout`
+pub fn is_statement_timeout(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("57014"))
+}
+
+/// Errors worth retrying: dropped connections, pool exhaustion, failover and
+/// serialization conflicts. Constraint violations and bad SQL are not.
+pub fn is_retryable(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_err) => db_err.code().is_some_and(|code| {
+            // 08xxx connection exceptions, 40001 serialization failure, 40P01 deadlock,
+            // 57P01-57P03 server shutting down or unavailable
+            code.starts_with("08")
+                || matches!(
+                    code.as_ref(),
+                    "40001" | "40P01" | "57P01" | "57P02" | "57P03"
+                )
+        }),
+        _ => false,
+    }
+}
+
+/// Runs a read query, retrying transient failures with exponential backoff
+pub async fn with_retry<T, F, Fut>(mut query: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        match query().await {
+            Err(e) if attempt < MAX_QUERY_ATTEMPTS && is_retryable(&e) => {
+                tracing::warn!(
+                    "Retrying query after transient error (attempt {}): {}",
+                    attempt,
+                    e
+                );
+                tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, bail, Result};
+use base64ct::{Base64, Encoding};
+use std::env;
+
+/// AES-256-GCM encryption of uploaded recordings.
+///
+/// Enabled by `FILE_ENCRYPTION_KEY` (32 bytes, base64). Each file gets a random
+/// nonce stored next to its `file_path`; files without a nonce are plaintext,
+/// which keeps uploads from before encryption was enabled readable.
+pub struct FileCipher {
+    cipher: Aes256Gcm,
+}
+
+impl FileCipher {
+    /// Returns `None` when no key is configured
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(encoded) = env::var("FILE_ENCRYPTION_KEY") else {
+            return Ok(None);
+        };
+
+        let key_bytes = Base64::decode_vec(encoded.trim())
+            .map_err(|_| anyhow!("FILE_ENCRYPTION_KEY must be base64"))?;
+        if key_bytes.len() != 32 {
+            bail!("FILE_ENCRYPTION_KEY must decode to 32 bytes");
+        }
+
+        let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
+        Ok(Some(Self {
+            cipher: Aes256Gcm::new(key),
+        }))
+    }
+
+    /// Returns the ciphertext and the nonce to store with it
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("Failed to encrypt file"))?;
+        Ok((ciphertext, nonce.to_vec()))
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8], nonce: &[u8]) -> Result<Vec<u8>> {
+        if nonce.len() != 12 {
+            bail!("Invalid file nonce");
+        }
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt file"))
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -7,25 +7,45 @@ use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Serialize)]
+use crate::utils::messages::{self, text, ErrorText};
+
+/// How long clients are asked to wait when the database is unreachable
+const DATABASE_RETRY_AFTER_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
-    pub message: String,
-    pub details: Option<HashMap<String, Vec<String>>>,
+    pub message: ErrorText,
+    pub details: Option<HashMap<String, Vec<ErrorText>>>,
+    #[serde(with = "crate::utils::time::rfc3339")]
     pub timestamp: DateTime<Utc>,
 }
 
 #[derive(Debug)]
 pub enum AppError {
-    ValidationError(HashMap<String, Vec<String>>),
-    NotFound(String),
-    Unauthorized(String),
-    Forbidden(String),
-    Conflict(String),
-    BadRequest(String),
-    UnsupportedMediaType(String),
-    PayloadTooLarge(String),
-    InternalServerError(String),
+    ValidationError(HashMap<String, Vec<ErrorText>>),
+    NotFound(ErrorText),
+    Unauthorized(ErrorText),
+    Forbidden(ErrorText),
+    /// Correct credentials, but the password has to be changed before signing in
+    PasswordResetRequired(ErrorText),
+    Conflict(ErrorText),
+    BadRequest(ErrorText),
+    UnsupportedMediaType(ErrorText),
+    PayloadTooLarge(ErrorText),
+    RequestTimeout(ErrorText),
+    QueryTimeout(ErrorText),
+    TooManyRequests {
+        message: ErrorText,
+        retry_after_secs: u64,
+    },
+    ServiceUnavailable {
+        message: ErrorText,
+        retry_after_secs: u64,
+    },
+    /// The server has no room left to store an upload
+    InsufficientStorage(ErrorText),
+    InternalServerError(ErrorText),
 }
 
 impl IntoResponse for AppError {
@@ -34,7 +54,7 @@ impl IntoResponse for AppError {
             AppError::ValidationError(errors) => (
                 StatusCode::BAD_REQUEST,
                 "VALIDATION_ERROR",
-                "Validation failed".to_string(),
+                text("validation_failed"),
                 Some(errors.clone()),
             ),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, "NOT_FOUND", msg.clone(), None),
@@ -42,6 +62,12 @@ impl IntoResponse for AppError {
                 (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", msg.clone(), None)
             }
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, "FORBIDDEN", msg.clone(), None),
+            AppError::PasswordResetRequired(msg) => (
+                StatusCode::FORBIDDEN,
+                "PASSWORD_RESET_REQUIRED",
+                msg.clone(),
+                None,
+            ),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, "CONFLICT", msg.clone(), None),
             AppError::BadRequest(msg) => {
                 (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg.clone(), None)
@@ -58,6 +84,36 @@ impl IntoResponse for AppError {
                 msg.clone(),
                 None,
             ),
+            AppError::RequestTimeout(msg) => (
+                StatusCode::REQUEST_TIMEOUT,
+                "REQUEST_TIMEOUT",
+                msg.clone(),
+                None,
+            ),
+            AppError::QueryTimeout(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "QUERY_TIMEOUT",
+                msg.clone(),
+                None,
+            ),
+            AppError::TooManyRequests { message, .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "TOO_MANY_REQUESTS",
+                message.clone(),
+                None,
+            ),
+            AppError::ServiceUnavailable { message, .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "SERVICE_UNAVAILABLE",
+                message.clone(),
+                None,
+            ),
+            AppError::InsufficientStorage(msg) => (
+                StatusCode::INSUFFICIENT_STORAGE,
+                "INSUFFICIENT_STORAGE",
+                msg.clone(),
+                None,
+            ),
             AppError::InternalServerError(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_SERVER_ERROR",
@@ -73,7 +129,23 @@ impl IntoResponse for AppError {
             timestamp: Utc::now(),
         };
 
-        (status, Json(error_response)).into_response()
+        // Kept on the response so the locale middleware can translate it
+        let mut response = (status, Json(error_response.clone())).into_response();
+        response.extensions_mut().insert(error_response);
+
+        if let AppError::TooManyRequests {
+            retry_after_secs, ..
+        }
+        | AppError::ServiceUnavailable {
+            retry_after_secs, ..
+        } = &self
+        {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(*retry_after_secs));
+        }
+
+        response
     }
 }
 
@@ -82,14 +154,16 @@ impl From<validator::ValidationErrors> for AppError {
         let mut error_map = HashMap::new();
 
         for (field, field_errors) in errors.field_errors() {
-            let messages: Vec<String> = field_errors
+            let messages: Vec<ErrorText> = field_errors
                 .iter()
                 .map(|error| {
-                    error
-                        .message
-                        .as_ref()
-                        .map(|msg| msg.to_string())
-                        .unwrap_or_else(|| format!("Invalid value for field '{}'", field))
+                    let key = format!("validation.{}", error.code);
+                    match &error.message {
+                        Some(msg) => messages::formatted(&key, msg.to_string()),
+                        None => messages::lookup(&key).unwrap_or_else(|| {
+                            format!("Invalid value for field '{}'", field).into()
+                        }),
+                    }
                 })
                 .collect();
             error_map.insert(field.to_string(), messages);
@@ -99,18 +173,39 @@ impl From<validator::ValidationErrors> for AppError {
     }
 }
 
+/// Errors that say nothing about the query itself, only that the database
+/// can't be reached right now, so retrying shortly is likely to succeed
+fn is_transient_db_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => true,
+        // Class 08 is connection exceptions; the rest are the server refusing
+        // connections while it is overloaded, starting up or shutting down
+        sqlx::Error::Database(db_err) => db_err.code().is_some_and(|code| {
+            code.starts_with("08") || matches!(code.as_ref(), "53300" | "57P01" | "57P03")
+        }),
+        _ => false,
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(error: sqlx::Error) -> Self {
+        if is_transient_db_error(&error) {
+            return AppError::ServiceUnavailable {
+                message: messages::text("database_unavailable"),
+                retry_after_secs: DATABASE_RETRY_AFTER_SECS,
+            };
+        }
+
         match error {
-            sqlx::Error::RowNotFound => AppError::NotFound("Resource not found".to_string()),
+            sqlx::Error::RowNotFound => AppError::NotFound(messages::text("resource_not_found")),
             sqlx::Error::Database(db_err) => {
                 if db_err.is_unique_violation() {
-                    AppError::Conflict("Resource already exists".to_string())
+                    AppError::Conflict(messages::text("resource_exists"))
                 } else {
-                    AppError::InternalServerError("Database error occurred".to_string())
+                    AppError::InternalServerError(messages::text("database_error"))
                 }
             }
-            _ => AppError::InternalServerError("Database error occurred".to_string()),
+            _ => AppError::InternalServerError(messages::text("database_error")),
         }
     }
 }
@@ -1,16 +1,166 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
 use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Key id used when only `JWT_SECRET` is configured
+const DEFAULT_KID: &str = "default";
+
+/// Lifetime of a read-only impersonation token
+pub const IMPERSONATION_TOKEN_MINUTES: i64 = 15;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32, // user_id
     pub role: String,
     pub exp: usize,
+    #[serde(default)]
+    pub cohort_id: Option<i32>,
+    /// Must match `users.token_version`; bumping it revokes the token
+    #[serde(default)]
+    pub token_version: i32,
+    /// Admin acting as `sub` through a read-only token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonated_by: Option<i32>,
+    /// The impersonating admin's `token_version` when the token was issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator_version: Option<i32>,
+}
+
+/// One entry of the `JWT_KEYS` JSON array.
+///
+/// HS256 keys take a `secret`; RS256 keys take PEM file paths. A key without
+/// its signing half can still verify tokens, which is how retired keys are kept.
+#[derive(Debug, Deserialize)]
+struct KeyConfig {
+    kid: String,
+    #[serde(default = "default_algorithm")]
+    alg: Algorithm,
+    secret: Option<String>,
+    private_key_path: Option<String>,
+    public_key_path: Option<String>,
+}
+
+fn default_algorithm() -> Algorithm {
+    Algorithm::HS256
+}
+
+struct JwtKey {
+    kid: String,
+    algorithm: Algorithm,
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+}
+
+/// Signing and verification keys.
+///
+/// Tokens are signed with the current key and carry its `kid`; verification
+/// accepts any configured key so sessions survive a rotation.
+pub struct JwtKeys {
+    current: usize,
+    keys: Vec<JwtKey>,
+}
+
+impl JwtKeys {
+    /// Single HS256 key, compatible with tokens issued before rotation support
+    pub fn from_secret(secret: &str) -> Self {
+        Self {
+            current: 0,
+            keys: vec![JwtKey {
+                kid: DEFAULT_KID.to_string(),
+                algorithm: Algorithm::HS256,
+                encoding: Some(EncodingKey::from_secret(secret.as_ref())),
+                decoding: DecodingKey::from_secret(secret.as_ref()),
+            }],
+        }
+    }
+
+    /// Loads `JWT_KEYS` if set, otherwise falls back to `JWT_SECRET`.
+    ///
+    /// `JWT_CURRENT_KID` picks the signing key; it defaults to the first entry.
+    pub fn from_env() -> Result<Self> {
+        let Ok(raw) = env::var("JWT_KEYS") else {
+            let secret = env::var("JWT_SECRET").context("JWT_SECRET or JWT_KEYS must be set")?;
+            return Ok(Self::from_secret(&secret));
+        };
+
+        let configs: Vec<KeyConfig> =
+            serde_json::from_str(&raw).context("JWT_KEYS must be a JSON array of keys")?;
+        let keys = configs
+            .into_iter()
+            .map(JwtKey::from_config)
+            .collect::<Result<Vec<_>>>()?;
+
+        let current = match env::var("JWT_CURRENT_KID") {
+            Ok(kid) => keys
+                .iter()
+                .position(|key| key.kid == kid)
+                .ok_or_else(|| anyhow!("JWT_CURRENT_KID '{}' is not in JWT_KEYS", kid))?,
+            Err(_) => 0,
+        };
+
+        match keys.get(current) {
+            Some(key) if key.encoding.is_some() => Ok(Self { current, keys }),
+            Some(key) => bail!("JWT key '{}' has no signing key", key.kid),
+            None => bail!("JWT_KEYS must contain at least one key"),
+        }
+    }
+
+    fn find(&self, kid: &str) -> Option<&JwtKey> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+}
+
+impl JwtKey {
+    fn from_config(config: KeyConfig) -> Result<Self> {
+        let (encoding, decoding) = match config.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = config
+                    .secret
+                    .ok_or_else(|| anyhow!("JWT key '{}' needs a secret", config.kid))?;
+                (
+                    Some(EncodingKey::from_secret(secret.as_ref())),
+                    DecodingKey::from_secret(secret.as_ref()),
+                )
+            }
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 => {
+                let public_path = config
+                    .public_key_path
+                    .ok_or_else(|| anyhow!("JWT key '{}' needs public_key_path", config.kid))?;
+                let public_pem = std::fs::read(&public_path)
+                    .with_context(|| format!("Failed to read {}", public_path))?;
+                let encoding = match config.private_key_path {
+                    Some(private_path) => {
+                        let private_pem = std::fs::read(&private_path)
+                            .with_context(|| format!("Failed to read {}", private_path))?;
+                        Some(EncodingKey::from_rsa_pem(&private_pem)?)
+                    }
+                    None => None,
+                };
+                (encoding, DecodingKey::from_rsa_pem(&public_pem)?)
+            }
+            other => bail!("Unsupported JWT algorithm {:?}", other),
+        };
+
+        Ok(Self {
+            kid: config.kid,
+            algorithm: config.alg,
+            encoding,
+            decoding,
+        })
+    }
 }
 
-pub fn create_jwt(user_id: i32, role: &str, secret: &str) -> Result<String> {
+pub fn create_jwt(
+    user_id: i32,
+    role: &str,
+    cohort_id: Option<i32>,
+    token_version: i32,
+    keys: &JwtKeys,
+) -> Result<String> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::hours(24))
         .expect("valid timestamp")
@@ -20,23 +170,79 @@ pub fn create_jwt(user_id: i32, role: &str, secret: &str) -> Result<String> {
         sub: user_id,
         role: role.to_string(),
         exp: expiration as usize,
+        cohort_id,
+        token_version,
+        impersonated_by: None,
+        impersonator_version: None,
     };
 
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
-    )?;
+    sign(&claims, keys)
+}
+
+/// A short-lived student token carrying `impersonated_by`; the auth middleware
+/// only lets it make read requests
+pub fn create_impersonation_jwt(
+    user_id: i32,
+    cohort_id: Option<i32>,
+    token_version: i32,
+    admin_id: i32,
+    admin_token_version: i32,
+    keys: &JwtKeys,
+) -> Result<String> {
+    let expiration = Utc::now()
+        .checked_add_signed(Duration::minutes(IMPERSONATION_TOKEN_MINUTES))
+        .expect("valid timestamp")
+        .timestamp();
+
+    let claims = Claims {
+        sub: user_id,
+        role: "student".to_string(),
+        exp: expiration as usize,
+        cohort_id,
+        token_version,
+        impersonated_by: Some(admin_id),
+        impersonator_version: Some(admin_token_version),
+    };
+
+    sign(&claims, keys)
+}
+
+fn sign(claims: &Claims, keys: &JwtKeys) -> Result<String> {
+    let key = &keys.keys[keys.current];
+    let encoding = key
+        .encoding
+        .as_ref()
+        .ok_or_else(|| anyhow!("JWT key '{}' has no signing key", key.kid))?;
+
+    let mut header = Header::new(key.algorithm);
+    header.kid = Some(key.kid.clone());
+
+    let token = encode(&header, claims, encoding)?;
 
     Ok(token)
 }
 
-pub fn verify_jwt(token: &str, secret: &str) -> Result<Claims> {
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::new(Algorithm::HS256),
-    )?;
+pub fn verify_jwt(token: &str, keys: &JwtKeys) -> Result<Claims> {
+    let header = decode_header(token)?;
+
+    // Tokens without a kid predate rotation; try every key of the same algorithm
+    let candidates: Vec<&JwtKey> = match header.kid.as_deref() {
+        Some(kid) => keys.find(kid).into_iter().collect(),
+        None => keys
+            .keys
+            .iter()
+            .filter(|key| key.algorithm == header.alg)
+            .collect(),
+    };
+
+    let mut last_error = anyhow!("No JWT key matches the token");
+    for key in candidates {
+        // Pin the algorithm to the key so a token can't pick its own
+        match decode::<Claims>(token, &key.decoding, &Validation::new(key.algorithm)) {
+            Ok(token_data) => return Ok(token_data.claims),
+            Err(e) => last_error = e.into(),
+        }
+    }
 
-    Ok(token_data.claims)
+    Err(last_error)
 }
@@ -1,8 +1,82 @@
 use chrono::Utc;
 use serde_json::json;
 use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
 use tracing::{error, info, warn};
 
+/// Business events sampled by default; everything else is always logged
+const DEFAULT_SAMPLED_EVENTS: &[&str] = &[
+    "*_request_started",
+    "*_request_completed",
+    "cache_set",
+    "cache_computed",
+    "metrics_cache_hit",
+];
+
+/// Sampling for high-volume log entries.
+///
+/// With `LOG_SAMPLE_RATE=N`, only one in N successful `http_request` entries,
+/// fast `database_query` entries, performance metrics and the business events
+/// in `LOG_SAMPLED_EVENTS` (comma-separated, `*` prefix wildcard) are emitted.
+/// Errors, failed requests, slow requests and security events are never sampled.
+#[derive(Debug)]
+struct Sampling {
+    every_n: u64,
+    sampled_events: Vec<String>,
+}
+
+impl Sampling {
+    fn from_env() -> Self {
+        let every_n = env::var("LOG_SAMPLE_RATE")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1)
+            .max(1);
+
+        let sampled_events = match env::var("LOG_SAMPLED_EVENTS") {
+            Ok(events) => events
+                .split(',')
+                .map(str::trim)
+                .filter(|event| !event.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(_) => DEFAULT_SAMPLED_EVENTS
+                .iter()
+                .map(|event| event.to_string())
+                .collect(),
+        };
+
+        Self {
+            every_n,
+            sampled_events,
+        }
+    }
+
+    fn is_sampled_event(&self, event_name: &str) -> bool {
+        self.sampled_events
+            .iter()
+            .any(|pattern| match pattern.strip_prefix('*') {
+                Some(suffix) => event_name.ends_with(suffix),
+                None => pattern == event_name,
+            })
+    }
+}
+
+static SAMPLING: OnceLock<Sampling> = OnceLock::new();
+static SAMPLE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn sampling() -> &'static Sampling {
+    SAMPLING.get_or_init(Sampling::from_env)
+}
+
+/// Whether this occurrence of a sampled entry should be emitted
+fn keep_sample() -> bool {
+    let every_n = sampling().every_n;
+    every_n == 1 || SAMPLE_COUNTER.fetch_add(1, Ordering::Relaxed) % every_n == 0
+}
+
 #[derive(Debug)]
 pub struct StructuredLogger;
 
@@ -12,6 +86,10 @@ impl StructuredLogger {
     }
 
     pub fn log_request(&self, method: &str, path: &str, user_id: Option<i32>, status: u16) {
+        if status < 400 && !keep_sample() {
+            return;
+        }
+
         let log_entry = json!({
             "timestamp": Utc::now().to_rfc3339(),
             "event_type": "http_request",
@@ -25,7 +103,33 @@ impl StructuredLogger {
         info!("{}", log_entry);
     }
 
+    pub fn log_slow_request(
+        &self,
+        method: &str,
+        path: &str,
+        user_id: Option<i32>,
+        status: u16,
+        duration_ms: u128,
+    ) {
+        let log_entry = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "event_type": "slow_request",
+            "method": method,
+            "path": path,
+            "user_id": user_id,
+            "status_code": status,
+            "duration_ms": duration_ms,
+            "service": "job-tracker-backend"
+        });
+
+        warn!("Slow request detected: {}", log_entry);
+    }
+
     pub fn log_database_query(&self, query: &str, duration_ms: u128, result_count: Option<usize>) {
+        if duration_ms <= 1000 && !keep_sample() {
+            return;
+        }
+
         let log_entry = json!({
             "timestamp": Utc::now().to_rfc3339(),
             "event_type": "database_query",
@@ -68,6 +172,10 @@ impl StructuredLogger {
         value: f64,
         tags: HashMap<String, String>,
     ) {
+        if !keep_sample() {
+            return;
+        }
+
         let log_entry = json!({
             "timestamp": Utc::now().to_rfc3339(),
             "event_type": "performance_metric",
@@ -86,6 +194,10 @@ impl StructuredLogger {
         user_id: Option<i32>,
         metadata: HashMap<String, serde_json::Value>,
     ) {
+        if sampling().is_sampled_event(event_name) && !keep_sample() {
+            return;
+        }
+
         let mut log_entry = json!({
             "timestamp": Utc::now().to_rfc3339(),
             "event_type": "business_event",
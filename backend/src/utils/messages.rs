@@ -0,0 +1,779 @@
+//! Catalog of user-facing error messages.
+//!
+//! Handlers build catalog messages with `text(key)`, which keeps the key next
+//! to the English text so the message can be translated by key later. Ad-hoc
+//! messages carry no key and are always served in English. Machine-readable
+//! error codes are never translated.
+
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Ru,
+}
+
+impl Locale {
+    /// Language tag for `Content-Language`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ru => "ru",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim();
+        if primary.eq_ignore_ascii_case("ru") {
+            Some(Locale::Ru)
+        } else if primary.eq_ignore_ascii_case("en") {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+
+    /// Picks the best supported locale from an `Accept-Language` header value,
+    /// honouring `q` weights and falling back to English
+    pub fn from_accept_language(header: &str) -> Self {
+        let mut best: Option<(Locale, f32)> = None;
+
+        for entry in header.split(',') {
+            let mut parts = entry.split(';');
+            let Some(locale) = parts.next().and_then(Locale::from_tag) else {
+                continue;
+            };
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if weight > 0.0 && best.is_none_or(|(_, w)| weight > w) {
+                best = Some((locale, weight));
+            }
+        }
+
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+}
+
+struct Message {
+    key: &'static str,
+    en: &'static str,
+    ru: &'static str,
+}
+
+const CATALOG: &[Message] = &[
+    // Generic messages produced by `AppError` conversions
+    Message {
+        key: "validation_failed",
+        en: "Validation failed",
+        ru: "Ошибка валидации",
+    },
+    Message {
+        key: "resource_not_found",
+        en: "Resource not found",
+        ru: "Ресурс не найден",
+    },
+    Message {
+        key: "resource_exists",
+        en: "Resource already exists",
+        ru: "Ресурс уже существует",
+    },
+    Message {
+        key: "database_error",
+        en: "Database error occurred",
+        ru: "Ошибка базы данных",
+    },
+    Message {
+        key: "database_unavailable",
+        en: "Database is temporarily unavailable, please retry",
+        ru: "База данных временно недоступна, повторите попытку",
+    },
+    Message {
+        key: "too_many_requests",
+        en: "Too many requests, please slow down",
+        ru: "Слишком много запросов, попробуйте позже",
+    },
+    // Validator messages, keyed by the validator code
+    Message {
+        key: "validation.email",
+        en: "Must be a valid email address",
+        ru: "Некорректный адрес электронной почты",
+    },
+    Message {
+        key: "validation.length",
+        en: "Value has an invalid length",
+        ru: "Недопустимая длина значения",
+    },
+    Message {
+        key: "validation.url",
+        en: "Must be a valid URL",
+        ru: "Некорректный URL",
+    },
+    Message {
+        key: "validation.range",
+        en: "Value is out of range",
+        ru: "Значение вне допустимого диапазона",
+    },
+    Message {
+        key: "validation.currency",
+        en: "Currency must be an ISO 4217 code (e.g. USD, EUR, RUB)",
+        ru: "Валюта должна быть кодом ISO 4217 (например, USD, EUR, RUB)",
+    },
+    Message {
+        key: "validation.salary_range",
+        en: "salary_min must not exceed salary_max",
+        ru: "salary_min не может быть больше salary_max",
+    },
+    Message {
+        key: "validation.include",
+        en: "include accepts screening and interview",
+        ru: "include принимает значения screening и interview",
+    },
+    // Pagination
+    Message {
+        key: "pagination.limit_not_integer",
+        en: "limit must be a whole number",
+        ru: "limit должен быть целым числом",
+    },
+    Message {
+        key: "pagination.offset_not_integer",
+        en: "offset must be a whole number",
+        ru: "offset должен быть целым числом",
+    },
+    Message {
+        key: "pagination.invalid_query",
+        en: "Invalid query string",
+        ru: "Некорректная строка запроса",
+    },
+    // Authentication
+    Message {
+        key: "auth.invalid_credentials",
+        en: "Invalid email or password",
+        ru: "Неверный адрес электронной почты или пароль",
+    },
+    Message {
+        key: "auth.password_reset_required",
+        en: "Your password must be changed before you can sign in",
+        ru: "Перед входом необходимо сменить пароль",
+    },
+    Message {
+        key: "auth.password_unchanged",
+        en: "The new password must differ from the current one",
+        ru: "Новый пароль должен отличаться от текущего",
+    },
+    Message {
+        key: "auth.email_taken",
+        en: "An account with this email already exists",
+        ru: "Учётная запись с таким адресом электронной почты уже существует",
+    },
+    Message {
+        key: "auth.password_too_short",
+        en: "Password must be at least 8 characters long",
+        ru: "Пароль должен содержать не менее 8 символов",
+    },
+    Message {
+        key: "auth.password_too_long",
+        en: "Password too long (max 72 characters for compatibility)",
+        ru: "Пароль слишком длинный (не более 72 символов)",
+    },
+    Message {
+        key: "auth.invalid_admin_code",
+        en: "Invalid admin code",
+        ru: "Неверный код администратора",
+    },
+    Message {
+        key: "auth.admin_only_register",
+        en: "Only admins can create new admins",
+        ru: "Только администраторы могут создавать администраторов",
+    },
+    Message {
+        key: "auth.invalid_token",
+        en: "Invalid or expired token",
+        ru: "Недействительный или просроченный токен",
+    },
+    Message {
+        key: "auth.admin_register_role",
+        en: "Only admin accounts can be created here",
+        ru: "Здесь можно создавать только учётные записи администраторов",
+    },
+    // Applications and uploads
+    Message {
+        key: "applications.not_found",
+        en: "Application not found",
+        ru: "Отклик не найден",
+    },
+    Message {
+        key: "applications.invalid_status_transition",
+        en: "Invalid status transition",
+        ru: "Недопустимый переход статуса",
+    },
+    Message {
+        key: "applications.no_fields_to_update",
+        en: "No fields to update",
+        ru: "Нет полей для обновления",
+    },
+    Message {
+        key: "activity.fetch_failed",
+        en: "Failed to fetch activity",
+        ru: "Не удалось получить активность",
+    },
+    Message {
+        key: "activity.not_allowed",
+        en: "Not allowed to view this activity",
+        ru: "Нет доступа к этой активности",
+    },
+    Message {
+        key: "uploads.no_file",
+        en: "No multipart file provided",
+        ru: "Файл не передан",
+    },
+    Message {
+        key: "uploads.empty_file",
+        en: "File field present but empty",
+        ru: "Поле файла передано, но файл пустой",
+    },
+    Message {
+        key: "uploads.filename_missing",
+        en: "Filename missing from file field",
+        ru: "В поле файла отсутствует имя файла",
+    },
+    Message {
+        key: "uploads.no_extension",
+        en: "Filename has no extension",
+        ru: "У имени файла нет расширения",
+    },
+    Message {
+        key: "uploads.unknown_type",
+        en: "Could not determine the file type",
+        ru: "Не удалось определить тип файла",
+    },
+    Message {
+        key: "uploads.store_failed",
+        en: "Failed to store file",
+        ru: "Не удалось сохранить файл",
+    },
+    Message {
+        key: "uploads.storage_full",
+        en: "Not enough storage space to save the file, please try again later",
+        ru: "Недостаточно места для сохранения файла, попробуйте позже",
+    },
+    // Admin features
+    Message {
+        key: "users.not_found",
+        en: "User not found",
+        ru: "Пользователь не найден",
+    },
+    Message {
+        key: "users.admin_only_password_migration",
+        en: "Only admins can view password migration status",
+        ru: "Только администраторы могут просматривать статус миграции паролей",
+    },
+    Message {
+        key: "users.super_admin_only_password_reset",
+        en: "Only super-admins can flag users for password reset",
+        ru: "Только главные администраторы могут назначать принудительную смену пароля",
+    },
+    Message {
+        key: "users.admin_only_impersonate",
+        en: "Only admins can impersonate users",
+        ru: "Только администраторы могут входить от имени пользователей",
+    },
+    Message {
+        key: "users.impersonate_students_only",
+        en: "Only students can be impersonated",
+        ru: "Входить можно только от имени студентов",
+    },
+    Message {
+        key: "auth.impersonation_read_only",
+        en: "Impersonation sessions are read-only",
+        ru: "Сеанс входа от имени пользователя доступен только для чтения",
+    },
+    Message {
+        key: "auth.impersonation_not_allowed",
+        en: "Impersonation tokens can't be used here",
+        ru: "Токены входа от имени пользователя здесь не принимаются",
+    },
+    Message {
+        key: "applications.admin_only_reassign",
+        en: "Only admins can reassign applications",
+        ru: "Только администраторы могут переназначать заявки",
+    },
+    Message {
+        key: "applications.reassign_students_only",
+        en: "Applications can only be reassigned to students",
+        ru: "Заявки можно переназначать только студентам",
+    },
+    Message {
+        key: "notifications.admin_only_test",
+        en: "Only admins can send test notifications",
+        ru: "Только администраторы могут отправлять тестовые уведомления",
+    },
+    Message {
+        key: "activity.admin_only",
+        en: "Only admins can view activity",
+        ru: "Только администраторы могут просматривать активность",
+    },
+    Message {
+        key: "users.admin_only_list_students",
+        en: "Only admins can list students",
+        ru: "Только администраторы могут просматривать список студентов",
+    },
+    Message {
+        key: "applications.admin_only_list_all",
+        en: "Only admins can list all applications",
+        ru: "Только администраторы могут просматривать все отклики",
+    },
+    Message {
+        key: "notifications.admin_only_trigger",
+        en: "Only admins can trigger notifications",
+        ru: "Только администраторы могут запускать рассылку уведомлений",
+    },
+    Message {
+        key: "notifications.admin_only_preview",
+        en: "Only admins can preview notifications",
+        ru: "Только администраторы могут просматривать предстоящие уведомления",
+    },
+    Message {
+        key: "notifications.process_failed",
+        en: "Failed to process notifications",
+        ru: "Не удалось обработать уведомления",
+    },
+    Message {
+        key: "realtime.admin_only_cohort_feed",
+        en: "Only admins can follow the cohort feed",
+        ru: "Только администраторы могут подписаться на ленту когорты",
+    },
+    Message {
+        key: "realtime.admin_ip_not_allowed",
+        en: "The cohort feed is not available from this network",
+        ru: "Лента когорты недоступна из этой сети",
+    },
+    Message {
+        key: "realtime.unknown_feed",
+        en: "Unknown feed",
+        ru: "Неизвестная лента",
+    },
+    Message {
+        key: "users.admin_only_revoke",
+        en: "Only admins can revoke sessions",
+        ru: "Только администраторы могут завершать сеансы",
+    },
+    Message {
+        key: "settings.admin_only",
+        en: "Only admins can view settings",
+        ru: "Только администраторы могут просматривать настройки",
+    },
+    Message {
+        key: "settings.super_admin_only_update",
+        en: "Only super-admins can change settings",
+        ru: "Только главные администраторы могут изменять настройки",
+    },
+    Message {
+        key: "scheduler.admin_only",
+        en: "Only admins can view the scheduler status",
+        ru: "Только администраторы могут просматривать состояние планировщика",
+    },
+    Message {
+        key: "cohorts.unknown",
+        en: "Unknown cohort",
+        ru: "Неизвестная когорта",
+    },
+    Message {
+        key: "cohorts.admin_only_list",
+        en: "Only admins can list cohorts",
+        ru: "Только администраторы могут просматривать когорты",
+    },
+    Message {
+        key: "cohorts.super_admin_only_create",
+        en: "Only super-admins can create cohorts",
+        ru: "Только главные администраторы могут создавать когорты",
+    },
+    Message {
+        key: "cohorts.super_admin_only_assign",
+        en: "Only super-admins can move users between cohorts",
+        ru: "Только главные администраторы могут переводить пользователей между когортами",
+    },
+    Message {
+        key: "webhooks.super_admin_only",
+        en: "Only super-admins can manage webhooks",
+        ru: "Только главные администраторы могут управлять вебхуками",
+    },
+    Message {
+        key: "webhooks.invalid_url",
+        en: "Webhook URLs must be http or https",
+        ru: "Адрес вебхука должен использовать http или https",
+    },
+    Message {
+        key: "webhooks.private_url",
+        en: "Webhook URLs must point at a public address",
+        ru: "Адрес вебхука должен указывать на публичный адрес",
+    },
+    Message {
+        key: "webhooks.not_found",
+        en: "Webhook not found",
+        ru: "Вебхук не найден",
+    },
+    Message {
+        key: "analytics.admin_only",
+        en: "Only admins can view analytics",
+        ru: "Только администраторы могут просматривать аналитику",
+    },
+    Message {
+        key: "analytics.super_admin_only_refresh",
+        en: "Only super-admins can refresh analytics",
+        ru: "Только главные администраторы могут обновлять аналитику",
+    },
+    Message {
+        key: "analytics.fetch_failed",
+        en: "Failed to fetch analytics",
+        ru: "Не удалось получить аналитику",
+    },
+    Message {
+        key: "analytics.refresh_failed",
+        en: "Failed to refresh analytics",
+        ru: "Не удалось обновить аналитику",
+    },
+    Message {
+        key: "analytics.query_timeout",
+        en: "Analytics query took too long, try a smaller range",
+        ru: "Запрос аналитики выполнялся слишком долго, попробуйте меньший период",
+    },
+    Message {
+        key: "analytics.refresh_timeout",
+        en: "Analytics refresh took too long",
+        ru: "Обновление аналитики заняло слишком много времени",
+    },
+    Message {
+        key: "analytics.busy",
+        en: "Analytics are busy, please retry shortly",
+        ru: "Аналитика сейчас перегружена, повторите попытку чуть позже",
+    },
+    Message {
+        key: "dashboard.admin_only",
+        en: "Only admins can view the admin dashboard",
+        ru: "Только администраторы могут просматривать панель администратора",
+    },
+    Message {
+        key: "dashboard.fetch_failed",
+        en: "Failed to fetch dashboard",
+        ru: "Не удалось получить сводку",
+    },
+    Message {
+        key: "dashboard.query_timeout",
+        en: "Dashboard query took too long",
+        ru: "Запрос сводки выполнялся слишком долго",
+    },
+    Message {
+        key: "maintenance.super_admin_only_backfill",
+        en: "Only super-admins can run backfills",
+        ru: "Только главные администраторы могут запускать заполнение данных",
+    },
+    Message {
+        key: "metrics.admin_only",
+        en: "Only admins can view metrics",
+        ru: "Только администраторы могут просматривать метрики",
+    },
+    Message {
+        key: "metrics.generate_failed",
+        en: "Failed to generate metrics",
+        ru: "Не удалось сформировать метрики",
+    },
+    Message {
+        key: "metrics.query_timeout",
+        en: "Metrics query took too long, try a smaller range",
+        ru: "Запрос метрик выполнялся слишком долго, попробуйте меньший период",
+    },
+    Message {
+        key: "metrics.days_out_of_range",
+        en: "days must be between 1 and 730",
+        ru: "days должен быть от 1 до 730",
+    },
+    Message {
+        key: "metrics.cache_duration_out_of_range",
+        en: "cache_duration must be between 1 and 1440",
+        ru: "cache_duration должен быть от 1 до 1440",
+    },
+    Message {
+        key: "metrics.admin_only_cache_stats",
+        en: "Only admins can view cache statistics",
+        ru: "Только администраторы могут просматривать статистику кэша",
+    },
+    Message {
+        key: "metrics.admin_only_cache_inspect",
+        en: "Only admins can inspect the cache",
+        ru: "Только администраторы могут просматривать записи кэша",
+    },
+    Message {
+        key: "metrics.admin_only_cache_invalidate",
+        en: "Only admins can invalidate the cache",
+        ru: "Только администраторы могут сбрасывать кэш",
+    },
+    Message {
+        key: "metrics.admin_only_cache_warm",
+        en: "Only admins can warm the cache",
+        ru: "Только администраторы могут прогревать кэш",
+    },
+    Message {
+        key: "metrics.cache_entry_not_found",
+        en: "Cache entry not found",
+        ru: "Запись кэша не найдена",
+    },
+    Message {
+        key: "metrics.cache_stats_failed",
+        en: "Failed to get cache statistics",
+        ru: "Не удалось получить статистику кэша",
+    },
+    Message {
+        key: "metrics.cache_inspect_failed",
+        en: "Failed to inspect cache entry",
+        ru: "Не удалось прочитать запись кэша",
+    },
+    Message {
+        key: "metrics.cache_invalidate_failed",
+        en: "Failed to invalidate cache",
+        ru: "Не удалось сбросить кэш",
+    },
+    Message {
+        key: "metrics.cache_warm_failed",
+        en: "Failed to warm cache",
+        ru: "Не удалось прогреть кэш",
+    },
+    Message {
+        key: "metrics.super_admin_only_cache_export",
+        en: "Only super-admins can export the cache",
+        ru: "Только главные администраторы могут выгружать кэш",
+    },
+    Message {
+        key: "metrics.super_admin_only_cache_import",
+        en: "Only super-admins can import the cache",
+        ru: "Только главные администраторы могут загружать кэш",
+    },
+    Message {
+        key: "metrics.cache_key_empty",
+        en: "Cache keys must not be empty",
+        ru: "Ключи кэша не могут быть пустыми",
+    },
+    Message {
+        key: "metrics.cache_ttl_out_of_range",
+        en: "ttl_seconds must be between 1 and 604800",
+        ru: "ttl_seconds должен быть от 1 до 604800",
+    },
+    Message {
+        key: "metrics.cache_export_failed",
+        en: "Failed to export cache",
+        ru: "Не удалось выгрузить кэш",
+    },
+    Message {
+        key: "metrics.cache_import_failed",
+        en: "Failed to import cache",
+        ru: "Не удалось загрузить кэш",
+    },
+    Message {
+        key: "files.admin_only_access_log",
+        en: "Only admins can view file access logs",
+        ru: "Только администраторы могут просматривать журнал доступа к файлам",
+    },
+    Message {
+        key: "files.invalid_filename",
+        en: "Invalid filename",
+        ru: "Недопустимое имя файла",
+    },
+    Message {
+        key: "files.not_allowed",
+        en: "Not allowed to access this file",
+        ru: "Нет доступа к этому файлу",
+    },
+    Message {
+        key: "files.admin_only_export",
+        en: "Only admins can export student recordings",
+        ru: "Только администраторы могут выгружать записи студентов",
+    },
+    Message {
+        key: "files.no_user_recordings",
+        en: "No recordings found for this user",
+        ru: "У этого пользователя нет записей",
+    },
+    Message {
+        key: "files.not_found",
+        en: "File not found",
+        ru: "Файл не найден",
+    },
+    Message {
+        key: "files.read_failed",
+        en: "Failed to read file",
+        ru: "Не удалось прочитать файл",
+    },
+    Message {
+        key: "files.no_recordings",
+        en: "No recordings found for this application",
+        ru: "У этого отклика нет записей",
+    },
+];
+
+fn entry(key: &str) -> Option<&'static Message> {
+    CATALOG.iter().find(|entry| entry.key == key)
+}
+
+/// The message for `key` in `locale`, falling back to English
+pub fn message(key: &str, locale: Locale) -> Option<&'static str> {
+    entry(key).map(|entry| entry.text(locale))
+}
+
+/// The catalog message for `key`, if the catalog has one
+pub fn lookup(key: &str) -> Option<ErrorText> {
+    entry(key).map(|entry| ErrorText {
+        key: Some(entry.key),
+        text: entry.en.to_string(),
+    })
+}
+
+/// The catalog message for `key`. An unknown key is a bug; it is served as is.
+pub fn text(key: &'static str) -> ErrorText {
+    lookup(key).unwrap_or_else(|| {
+        debug_assert!(false, "message key '{}' is not in the catalog", key);
+        ErrorText::from(key)
+    })
+}
+
+/// A message built at runtime, keyed to the catalog entry for `key` as long
+/// as that entry's English text still reads exactly `formatted`. Stale entries
+/// are never served in place of the real message.
+pub fn formatted(key: &str, formatted: String) -> ErrorText {
+    lookup(key)
+        .filter(|keyed| keyed.text == formatted)
+        .unwrap_or_else(|| formatted.into())
+}
+
+/// A client-facing message, with the catalog key it came from when it has one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorText {
+    key: Option<&'static str>,
+    text: String,
+}
+
+impl ErrorText {
+    /// The message in `locale`, or `None` for messages outside the catalog
+    pub fn translate(&self, locale: Locale) -> Option<ErrorText> {
+        let key = self.key?;
+        message(key, locale).map(|text| ErrorText {
+            key: Some(key),
+            text: text.to_string(),
+        })
+    }
+}
+
+impl From<String> for ErrorText {
+    fn from(text: String) -> Self {
+        Self { key: None, text }
+    }
+}
+
+impl From<&str> for ErrorText {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
+
+impl fmt::Display for ErrorText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Serialize for ErrorText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl Message {
+    fn text(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => self.en,
+            Locale::Ru => self.ru,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn catalog_keys_are_unique() {
+        let mut seen = HashSet::new();
+        for entry in CATALOG {
+            assert!(seen.insert(entry.key), "duplicate key {}", entry.key);
+        }
+    }
+
+    #[test]
+    fn keyed_messages_translate_by_key() {
+        let message = text("files.not_found");
+        assert_eq!(message.to_string(), "File not found");
+        assert_eq!(
+            message.translate(Locale::Ru).unwrap().to_string(),
+            "Файл не найден"
+        );
+    }
+
+    #[test]
+    fn ad_hoc_messages_are_not_translated_even_when_the_text_matches() {
+        assert_eq!(
+            ErrorText::from("File not found").translate(Locale::Ru),
+            None
+        );
+    }
+
+    #[test]
+    fn formatted_messages_only_keep_a_matching_key() {
+        let current = formatted(
+            "pagination.limit_not_integer",
+            "limit must be a whole number".into(),
+        );
+        assert!(current.translate(Locale::Ru).is_some());
+
+        let stale = formatted(
+            "pagination.limit_not_integer",
+            "limit must be a number".into(),
+        );
+        assert_eq!(stale.translate(Locale::Ru), None);
+        assert_eq!(stale.to_string(), "limit must be a number");
+    }
+
+    #[test]
+    fn every_key_used_in_the_source_is_in_the_catalog() {
+        fn visit(dir: &std::path::Path, found: &mut Vec<String>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    visit(&path, found);
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    let source = std::fs::read_to_string(&path).unwrap();
+                    let mut remaining = source.as_str();
+                    while let Some(start) = remaining.find("messages::text(\"") {
+                        remaining = &remaining[start + "messages::text(\"".len()..];
+                        let end = remaining.find('"').unwrap();
+                        found.push(remaining[..end].to_string());
+                        remaining = &remaining[end..];
+                    }
+                }
+            }
+        }
+
+        let mut keys = Vec::new();
+        visit(
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut keys,
+        );
+        assert!(!keys.is_empty());
+        for key in keys {
+            assert!(lookup(&key).is_some(), "unknown message key {}", key);
+        }
+    }
+}
@@ -1,4 +1,11 @@
+pub mod company;
+pub mod concurrency;
+pub mod currency;
 pub mod database;
+pub mod encryption;
 pub mod errors;
 pub mod jwt;
 pub mod logger;
+pub mod messages;
+pub mod pagination;
+pub mod time;
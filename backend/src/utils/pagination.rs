@@ -0,0 +1,113 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::utils::errors::AppError;
+use crate::utils::messages::{self, ErrorText};
+
+/// Page size when the client doesn't send `limit`
+pub const DEFAULT_LIMIT: i64 = 50;
+
+/// Largest page any list endpoint returns
+pub const MAX_LIMIT: i64 = 200;
+
+/// `limit` and `offset` query parameters, parsed the same way for every list endpoint.
+///
+/// Values that aren't whole numbers are rejected with a 400; numbers out of
+/// range are clamped, `limit` into `1..=MAX_LIMIT` and `offset` to at least 0.
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Pagination {
+    /// The same window from deprecated 1-based `page`/`per_page` parameters,
+    /// clamped like `limit` and `offset`
+    pub fn from_page(page: i64, per_page: Option<i64>) -> Self {
+        let limit = per_page.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        Self {
+            limit,
+            offset: (page.max(1) - 1).saturating_mul(limit),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawPagination {
+    limit: Option<String>,
+    offset: Option<String>,
+}
+
+/// Empty values count as absent, anything else must fit an `i64`
+fn parse_param(
+    name: &str,
+    value: Option<&str>,
+    errors: &mut HashMap<String, Vec<ErrorText>>,
+) -> Option<i64> {
+    let value = value.map(str::trim).filter(|value| !value.is_empty())?;
+    match value.parse::<i64>() {
+        Ok(number) => Some(number),
+        Err(_) => {
+            errors.insert(
+                name.to_string(),
+                vec![messages::formatted(
+                    &format!("pagination.{}_not_integer", name),
+                    format!("{} must be a whole number", name),
+                )],
+            );
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Pagination {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<RawPagination>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| AppError::BadRequest(messages::text("pagination.invalid_query")))?;
+
+        let mut errors = HashMap::new();
+        let limit = parse_param("limit", raw.limit.as_deref(), &mut errors);
+        let offset = parse_param("offset", raw.offset.as_deref(), &mut errors);
+        if !errors.is_empty() {
+            return Err(AppError::ValidationError(errors));
+        }
+
+        Ok(Self {
+            limit: limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+            offset: offset.unwrap_or(0).max(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_pages_map_onto_limit_and_offset() {
+        let first = Pagination::from_page(1, None);
+        assert_eq!((first.limit, first.offset), (DEFAULT_LIMIT, 0));
+
+        let third = Pagination::from_page(3, Some(20));
+        assert_eq!((third.limit, third.offset), (20, 40));
+    }
+
+    #[test]
+    fn legacy_pages_are_clamped() {
+        let page = Pagination::from_page(0, Some(10_000));
+        assert_eq!((page.limit, page.offset), (MAX_LIMIT, 0));
+
+        let page = Pagination::from_page(i64::MAX, Some(0));
+        assert_eq!(page.limit, 1);
+        assert!(page.offset > 0);
+    }
+}
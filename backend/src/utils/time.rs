@@ -0,0 +1,66 @@
+//! The one timestamp format of the API.
+//!
+//! Every instant in a response is RFC 3339 in UTC with a `Z` suffix and
+//! exactly six fractional digits, e.g. `2024-03-01T09:30:00.000000Z`, whatever
+//! precision the value happens to have. Six digits match what Postgres stores,
+//! so a timestamp sent back as a `since` cursor loses nothing.
+//!
+//! Calendar days without a time, like `applied_date` or the `date` of activity
+//! series, are ISO 8601 dates (`2024-03-01`) instead.
+//!
+//! Use with `#[serde(with = "crate::utils::time::rfc3339")]`, or
+//! `rfc3339::option` for optional fields.
+
+pub mod rfc3339 {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn format(value: &DateTime<Utc>) -> String {
+        value.to_rfc3339_opts(SecondsFormat::Micros, true)
+    }
+
+    pub fn serialize<S: Serializer>(
+        value: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format(value))
+    }
+
+    /// Accepts any RFC 3339 offset, so cached responses written before this
+    /// format still read back
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(|value| value.with_timezone(&Utc))
+            .map_err(serde::de::Error::custom)
+    }
+
+    pub mod option {
+        use chrono::{DateTime, Utc};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<DateTime<Utc>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => serializer.serialize_some(&super::format(value)),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|value| value.with_timezone(&Utc))
+                        .map_err(serde::de::Error::custom)
+                })
+                .transpose()
+        }
+    }
+}
@@ -206,15 +206,21 @@ export default function AdminPage() {
             {Object.entries(analytics.status_breakdown).map(([status, count], index) => {
               const statusNames: Record<string, string> = {
                 'waiting': 'Ожидание',
+                'screening_scheduled': 'Скрининг назначен',
                 'next_stage': 'Следующий этап', 
                 'rejected': 'Отклонена',
-                'ignored': 'Игнорируется'
+                'ignored': 'Игнорируется',
+                'offer': 'Оффер',
+                'accepted': 'Оффер принят'
               };
               const statusColors: Record<string, string> = {
                 'waiting': 'text-yellow-600 dark:text-yellow-400',
+                'screening_scheduled': 'text-violet-600 dark:text-violet-400',
                 'next_stage': 'text-blue-600 dark:text-blue-400',
                 'rejected': 'text-red-600 dark:text-red-400', 
-                'ignored': 'text-gray-600 dark:text-gray-400'
+                'ignored': 'text-gray-600 dark:text-gray-400',
+                'offer': 'text-emerald-600 dark:text-emerald-400',
+                'accepted': 'text-green-600 dark:text-green-400'
               };
               return (
                 <div key={status} className={`${index % 2 === 0 ? 'bg-gray-50 dark:bg-gray-800' : 'bg-white dark:bg-gray-700'} px-4 py-5 sm:grid sm:grid-cols-3 sm:gap-4 sm:px-6`}>
@@ -188,9 +188,12 @@ export default function ApplicationsPage() {
                       className="text-sm border border-gray-300 dark:border-gray-600 rounded-md px-2 py-1 bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100"
                     >
                       <option value="waiting">Ожидание</option>
+                      <option value="screening_scheduled">Скрининг назначен</option>
                       <option value="next_stage">Следующий этап</option>
                       <option value="rejected">Отклонена</option>
                       <option value="ignored">Игнорируется</option>
+                      <option value="offer">Оффер</option>
+                      <option value="accepted">Оффер принят</option>
                     </select>
                     <button
                       onClick={() => setEditingApp(application)}
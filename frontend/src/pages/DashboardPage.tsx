@@ -58,7 +58,7 @@ export default function DashboardPage() {
   const getStaleApplications = () => {
     return applications.filter(app => {
       const daysSinceUpdate = differenceInDays(new Date(), new Date(app.updated_at));
-      return daysSinceUpdate > 7 && ['waiting', 'next_stage'].includes(app.status);
+      return daysSinceUpdate > 7 && ['waiting', 'screening_scheduled', 'next_stage'].includes(app.status);
     });
   };
 
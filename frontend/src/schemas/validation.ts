@@ -19,7 +19,7 @@ export const UpdateApplicationSchema = z.object({
   company_name: z.string().min(1, { message: "Название компании обязательно" }).optional(),
   job_url: UrlSchema,
   application_date: DateSchema.optional(),
-  status: z.enum(['waiting', 'rejected', 'next_stage', 'ignored']).optional(),
+  status: z.enum(['waiting', 'screening_scheduled', 'rejected', 'next_stage', 'ignored', 'offer', 'accepted']).optional(),
 });
 
 // User schemas
@@ -18,7 +18,7 @@ export interface Application {
   company_name: string;
   job_url?: string;
   application_date: string;
-  status: 'waiting' | 'rejected' | 'next_stage' | 'ignored';
+  status: 'waiting' | 'screening_scheduled' | 'rejected' | 'next_stage' | 'ignored' | 'offer' | 'accepted';
   created_at: string;
   updated_at: string;
   screening?: Screening;
@@ -55,7 +55,7 @@ export interface UpdateApplicationRequest {
   company_name?: string;
   job_url?: string;
   application_date?: string;
-  status?: 'waiting' | 'rejected' | 'next_stage' | 'ignored';
+  status?: 'waiting' | 'screening_scheduled' | 'rejected' | 'next_stage' | 'ignored' | 'offer' | 'accepted';
 }
 
 export interface Analytics {
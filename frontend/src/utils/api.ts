@@ -82,17 +82,28 @@ export const applicationsApi = {
   },
 };
 
+// Admin lists are paged server-side; views that show everything walk the pages
+const ADMIN_PAGE_SIZE = 200;
+const fetchAllPages = async <T>(url: string, params: Record<string, unknown> = {}): Promise<T[]> => {
+  const items: T[] = [];
+  for (let offset = 0; ; offset += ADMIN_PAGE_SIZE) {
+    const page = await httpClient.get<T[]>(url, { ...params, limit: ADMIN_PAGE_SIZE, offset });
+    items.push(...page);
+    if (page.length < ADMIN_PAGE_SIZE) return items;
+  }
+};
+
 export const adminApi = {
   getAnalytics: async (params?: { company?: string; status?: string; days_stale?: number }): Promise<Analytics> => {
     return withErrorHandling(() => httpClient.get<Analytics>('/admin/analytics', params));
   },
 
   getAllStudents: async (): Promise<User[]> => {
-    return withErrorHandling(() => httpClient.get<User[]>('/admin/students'));
+    return withErrorHandling(() => fetchAllPages<User>('/admin/students'));
   },
 
   getAllApplications: async (params?: { company?: string; status?: string }): Promise<Application[]> => {
-    return withErrorHandling(() => httpClient.get<Application[]>('/admin/applications', params));
+    return withErrorHandling(() => fetchAllPages<Application>('/admin/applications', params));
   },
 
   getActivity: async (): Promise<ActivityData[]> => {
@@ -7,6 +7,11 @@ export const statusColors = {
     dark: 'bg-amber-900/20 text-amber-400 border-amber-800',
     icon: 'text-amber-500'
   },
+  screening_scheduled: {
+    light: 'bg-violet-100 text-violet-800 border-violet-200',
+    dark: 'bg-violet-900/20 text-violet-400 border-violet-800',
+    icon: 'text-violet-500'
+  },
   rejected: {
     light: 'bg-red-100 text-red-800 border-red-200',
     dark: 'bg-red-900/20 text-red-400 border-red-800',
@@ -22,6 +27,16 @@ export const statusColors = {
     dark: 'bg-gray-800 text-gray-400 border-gray-700',
     icon: 'text-gray-500'
   },
+  offer: {
+    light: 'bg-emerald-100 text-emerald-800 border-emerald-200',
+    dark: 'bg-emerald-900/20 text-emerald-400 border-emerald-800',
+    icon: 'text-emerald-500'
+  },
+  accepted: {
+    light: 'bg-green-100 text-green-800 border-green-200',
+    dark: 'bg-green-900/20 text-green-400 border-green-800',
+    icon: 'text-green-500'
+  },
   
   // Результаты скрининга/интервью
   passed: {
@@ -56,9 +71,12 @@ export function getStatusIconColor(status: string) {
 // Переводы статусов
 export const statusLabels = {
   waiting: 'Ожидание',
+  screening_scheduled: 'Скрининг назначен',
   rejected: 'Отклонена',
   next_stage: 'Следующий этап', 
   ignored: 'Игнорируется',
+  offer: 'Оффер',
+  accepted: 'Оффер принят',
   passed: 'Пройден',
   failed: 'Провален',
   pending: 'В ожидании'